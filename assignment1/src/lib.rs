@@ -1,3 +1,10 @@
-pub mod color;
-pub mod rasterizer;
-pub mod triangle;
+//! 作业 1 中需要实现的函数，渲染管线在 `games101_core` 中。
+/// 参考实现，评分程序用它检查作业中的函数
+pub mod solution;
+pub mod transform;
+
+// 开启 `solution` feature 时换成参考实现
+#[cfg(feature = "solution")]
+pub use solution::{get_model_matrix, get_projection_matrix};
+#[cfg(not(feature = "solution"))]
+pub use transform::{get_model_matrix, get_projection_matrix};
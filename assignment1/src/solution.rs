@@ -0,0 +1,12 @@
+//! 作业 1 的参考实现，开启 `solution` feature 时替换 [`crate::transform`] 中的函数，评分程序也用它作为标准答案。
+
+use games101_core::transform;
+use nalgebra_glm::Mat4;
+
+pub fn get_model_matrix(rotation_angle: f32) -> Mat4 {
+    transform::rotation_z(rotation_angle.to_radians())
+}
+
+pub fn get_projection_matrix(eye_fov: f32, aspect_ratio: f32, z_near: f32, z_far: f32) -> Mat4 {
+    transform::perspective(eye_fov.to_radians(), aspect_ratio, z_near, z_far)
+}
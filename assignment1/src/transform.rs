@@ -0,0 +1,19 @@
+use nalgebra_glm::Mat4;
+
+/// 绕 z 轴旋转 `rotation_angle` 度
+pub fn get_model_matrix(rotation_angle: f32) -> Mat4 {
+    let mut model = Mat4::identity();
+    // TODO: Implement this function
+    // Create the model matrix for rotating the triangle around the Z axis.
+    // Then return it.
+    todo!();
+    model
+}
+
+/// 透视投影，`eye_fov` 以度为单位，`z_near` 和 `z_far` 是到近、远平面的距离
+pub fn get_projection_matrix(eye_fov: f32, aspect_ratio: f32, z_near: f32, z_far: f32) -> Mat4 {
+    // TODO: Implement this function
+    // Create the model matrix for rotating the triangle around the Z axis.
+    // Then return it.
+    todo!()
+}
@@ -0,0 +1,118 @@
+//! 测量大网格上 `Rasterizer::draw` 的耗时。
+//!
+//! 网格是 400x400 个格子、32 万个三角形的起伏地面，相邻三角形共享顶点。
+//! 把同一份网格展开成每个三角形独立的三个顶点后顶点缓存不会命中，每个角都要重新变换，
+//! 两者的差别就是顶点缓存省下的时间；两种方式渲染出的图像必须完全相同。
+//! 用 `cargo run --release --example draw_timing -- [frames]` 运行。
+
+use games101_core::color::Color;
+use games101_core::rasterizer::{Buffers, Primitive, RasterMode, Rasterizer};
+use games101_core::transform;
+use nalgebra_glm::{vec3, Vec3};
+use std::env;
+use std::time::{Duration, Instant};
+
+const SIZE: u32 = 700;
+const CELLS: u32 = 400;
+
+struct Grid {
+    positions: Vec<Vec3>,
+    colors: Vec<Color>,
+    indices: Vec<Vec3>,
+}
+
+fn grid() -> Grid {
+    let (mut positions, mut colors, mut indices) = (vec![], vec![], vec![]);
+    for i in 0..=CELLS {
+        for j in 0..=CELLS {
+            let (u, v) = (i as f32 / CELLS as f32, j as f32 / CELLS as f32);
+            let height = 0.1 * (u * 20.0).sin() * (v * 20.0).cos();
+            positions.push(vec3(u * 4.0 - 2.0, height, v * 4.0 - 2.0));
+            colors.push(Color::new_rgb((u * 255.0) as u8, (v * 255.0) as u8, 128));
+        }
+    }
+    let row = CELLS + 1;
+    for i in 0..CELLS {
+        for j in 0..CELLS {
+            let (a, b) = ((i * row + j) as f32, ((i + 1) * row + j) as f32);
+            indices.push(vec3(a, b, a + 1.0));
+            indices.push(vec3(a + 1.0, b, b + 1.0));
+        }
+    }
+    Grid {
+        positions,
+        colors,
+        indices,
+    }
+}
+
+/// 每个三角形使用自己的三个顶点
+fn unrolled(grid: &Grid) -> Grid {
+    let corners = || grid.indices.iter().flat_map(|i| [i.x, i.y, i.z]);
+    Grid {
+        positions: corners().map(|k| grid.positions[k as usize]).collect(),
+        colors: corners().map(|k| grid.colors[k as usize]).collect(),
+        indices: (0..grid.indices.len())
+            .map(|i| {
+                let base = 3.0 * i as f32;
+                vec3(base, base + 1.0, base + 2.0)
+            })
+            .collect(),
+    }
+}
+
+/// 渲染 `frames` 帧，返回每帧的平均耗时和最后一帧的图像
+fn time(grid: &Grid, frames: u32) -> (Duration, Vec<Color>) {
+    let mut r = Rasterizer::new(SIZE, SIZE);
+    r.set_raster_mode(RasterMode::EdgeFunction);
+    r.set_model(transform::rotation_x(30f32.to_radians()));
+    r.set_view(transform::translation(vec3(0.0, 0.0, -5.0)));
+    r.set_projection(transform::perspective(45f32.to_radians(), 1.0, 0.1, 50.0));
+    let pos = r.load_positions(grid.positions.clone());
+    let ind = r.load_indices(grid.indices.clone());
+    let col = r.load_colors(grid.colors.clone());
+
+    // 第一帧预热，不计入耗时
+    r.clear(Buffers::all());
+    r.draw(pos, ind, col, Primitive::Triangle);
+    let start = Instant::now();
+    for _ in 0..frames {
+        r.clear(Buffers::all());
+        r.draw(pos, ind, col, Primitive::Triangle);
+    }
+    (start.elapsed() / frames, r.framebuffer().to_vec())
+}
+
+fn main() {
+    let frames = env::args()
+        .nth(1)
+        .map(|n| n.parse().expect("frames must be a positive integer"))
+        .unwrap_or(10)
+        .max(1);
+    let indexed = grid();
+    let unrolled = unrolled(&indexed);
+    println!(
+        "{} triangles, {} shared vertices, {}x{}, {} frames",
+        indexed.indices.len(),
+        indexed.positions.len(),
+        SIZE,
+        SIZE,
+        frames
+    );
+
+    let (cached, cached_image) = time(&indexed, frames);
+    let (uncached, uncached_image) = time(&unrolled, frames);
+    assert!(
+        cached_image == uncached_image,
+        "the vertex cache changed the rendered image"
+    );
+    println!("cached   (shared vertices):     {:>9.2?} per frame", cached);
+    println!(
+        "uncached (one vertex per corner): {:>7.2?} per frame",
+        uncached
+    );
+    println!(
+        "speedup: {:.2}x",
+        uncached.as_secs_f64() / cached.as_secs_f64()
+    );
+}
@@ -0,0 +1,240 @@
+//! 纹理过滤对比：一块向远处延伸的棋盘格地面，左右两半使用不同的过滤方式。
+//!
+//! 窗口中 `1`、`2` 分别切换左、右两半的过滤方式，空格在左右对比和整屏之间切换，
+//! 方向键上下、左右移动相机。
+//! 命令行 `cargo run --example mipmap -- output.png [left] [right] [texture]` 把对比图保存为图片，
+//! 过滤方式为 nearest、bilinear、trilinear、anisotropic。
+
+use games101_core::color::Color;
+use games101_core::rasterizer::{
+    Buffers, ColBufId, IndBufId, PosBufId, Primitive, RasterMode, Rasterizer, TextureId,
+};
+use games101_core::texture::{Filter, Texture};
+use games101_core::transform;
+use minifb::{Key, KeyRepeat, Window, WindowOptions};
+use nalgebra_glm::{vec2, vec3, Mat4, Vec3};
+use std::env;
+
+const WIDTH: usize = 960;
+const HEIGHT: usize = 540;
+
+const TITLE: &str = "Mipmap";
+
+const FILTERS: [Filter; 4] = [
+    Filter::Nearest,
+    Filter::Bilinear,
+    Filter::Trilinear,
+    Filter::Anisotropic,
+];
+
+/// 一组使用同一份几何、各自绑定一张纹理的光栅化器
+struct View {
+    rasterizer: Rasterizer,
+    texture: TextureId,
+}
+
+struct Scene {
+    pos: PosBufId,
+    ind: IndBufId,
+    col: ColBufId,
+}
+
+fn main() {
+    let argv: Vec<String> = env::args().collect();
+    let filter_arg = |i: usize, default: Filter| {
+        argv.get(i).map_or(default, |name| {
+            parse_filter(name).unwrap_or_else(|| panic!("unknown filter {}", name))
+        })
+    };
+    let mut filters = [
+        filter_arg(2, Filter::Nearest),
+        filter_arg(3, Filter::Anisotropic),
+    ];
+    let texture = match argv.get(4) {
+        Some(path) => Texture::load(path).unwrap(),
+        None => checkerboard(256, 32),
+    };
+
+    let mut views = filters.map(|filter| {
+        let mut rasterizer = Rasterizer::new(WIDTH as u32, HEIGHT as u32);
+        rasterizer.set_raster_mode(RasterMode::EdgeFunction);
+        rasterizer.set_threads(std::thread::available_parallelism().map_or(1, |n| n.get()));
+        let mut texture = texture.clone();
+        texture.generate_mipmaps();
+        texture.set_filter(filter);
+        let texture = rasterizer.load_texture(texture);
+        View {
+            rasterizer,
+            texture,
+        }
+    });
+    let scenes = views.each_mut().map(load_ground);
+
+    let mut eye = vec3(0.0, 1.0, 0.0);
+    let mut split = true;
+
+    // render to file
+    if let Some(filename) = argv.get(1) {
+        let buffer = render(&mut views, &scenes, eye, split);
+        let bytes: Vec<u8> = buffer
+            .iter()
+            .flat_map(|&c| {
+                let [a, r, g, b] = c.to_be_bytes();
+                [r, g, b, a]
+            })
+            .collect();
+        image::save_buffer(
+            filename,
+            &bytes,
+            WIDTH as u32,
+            HEIGHT as u32,
+            image::ExtendedColorType::Rgba8,
+        )
+        .unwrap();
+        return;
+    }
+
+    // render to window
+    let mut window =
+        Window::new(TITLE, WIDTH, HEIGHT, WindowOptions::default()).unwrap_or_else(|e| {
+            panic!("{}", e);
+        });
+    window.limit_update_rate(Some(std::time::Duration::from_micros(16600)));
+    while window.is_open() && !window.is_key_down(Key::Escape) {
+        let title = if split {
+            format!(
+                "{} - {:?} | {:?} - ESC to exit",
+                TITLE, filters[0], filters[1]
+            )
+        } else {
+            format!("{} - {:?} - ESC to exit", TITLE, filters[0])
+        };
+        window.set_title(&title);
+
+        let buffer = render(&mut views, &scenes, eye, split);
+        window.update_with_buffer(&buffer, WIDTH, HEIGHT).unwrap();
+
+        for (i, key) in [Key::Key1, Key::Key2].into_iter().enumerate() {
+            if window.is_key_pressed(key, KeyRepeat::No) {
+                let next = FILTERS.iter().position(|&f| f == filters[i]).unwrap_or(0) + 1;
+                filters[i] = FILTERS[next % FILTERS.len()];
+                let view = &mut views[i];
+                if let Some(texture) = view.rasterizer.texture_mut(view.texture) {
+                    texture.set_filter(filters[i]);
+                }
+            }
+        }
+        if window.is_key_pressed(Key::Space, KeyRepeat::No) {
+            split = !split;
+        }
+        // 没有近平面裁剪，只在与地面平行的方向上移动，地面始终在相机前方
+        if window.is_key_down(Key::Up) {
+            eye.y = (eye.y + 0.05).min(10.0);
+        } else if window.is_key_down(Key::Down) {
+            eye.y = (eye.y - 0.05).max(0.1);
+        }
+        if window.is_key_down(Key::Left) {
+            eye.x = (eye.x - 0.05).max(-100.0);
+        } else if window.is_key_down(Key::Right) {
+            eye.x = (eye.x + 0.05).min(100.0);
+        }
+    }
+}
+
+fn parse_filter(name: &str) -> Option<Filter> {
+    match name {
+        "nearest" => Some(Filter::Nearest),
+        "bilinear" => Some(Filter::Bilinear),
+        "trilinear" => Some(Filter::Trilinear),
+        "anisotropic" => Some(Filter::Anisotropic),
+        _ => None,
+    }
+}
+
+/// 黑白棋盘格，每格 `cell` 个纹素
+fn checkerboard(size: u32, cell: u32) -> Texture {
+    let data = (0..size * size)
+        .map(|i| {
+            let (x, y) = (i % size / cell, i / size / cell);
+            if (x + y) % 2 == 0 {
+                Color::WHITE
+            } else {
+                Color::new_rgb(30, 30, 30)
+            }
+        })
+        .collect();
+    Texture::new(size, size, data)
+}
+
+/// y = 0 平面上、相机前方的网格。
+///
+/// 越近的行越密，近处的三角形不会因为超出保护带而被整个剔除。
+fn load_ground(view: &mut View) -> Scene {
+    const CELLS: usize = 64;
+    const EXTENT: f32 = 200.0;
+    const NEAR: f32 = 0.5;
+    let mut pos = vec![];
+    let mut uv = vec![];
+    for j in 0..=CELLS {
+        for i in 0..=CELLS {
+            let x = (i as f32 / CELLS as f32 - 0.5) * 2.0 * EXTENT;
+            let z = -NEAR * (2.0 * EXTENT / NEAR).powf(j as f32 / CELLS as f32);
+            pos.push(vec3(x, 0.0, z));
+            // 每 4 个单位重复一次纹理
+            uv.push(vec2(x / 4.0, -z / 4.0));
+        }
+    }
+    let mut ind = vec![];
+    for j in 0..CELLS {
+        for i in 0..CELLS {
+            let a = (j * (CELLS + 1) + i) as f32;
+            let c = a + (CELLS + 1) as f32;
+            ind.push(vec3(a, c + 1.0, a + 1.0));
+            ind.push(vec3(a, c, c + 1.0));
+        }
+    }
+
+    let r = &mut view.rasterizer;
+    let cols = vec![Color::WHITE; pos.len()];
+    let tex_coords = r.load_tex_coords(uv);
+    r.bind_tex_coords(Some(tex_coords));
+    r.bind_texture(Some(view.texture));
+    Scene {
+        pos: r.load_positions(pos),
+        ind: r.load_indices(ind),
+        col: r.load_colors(cols),
+    }
+}
+
+/// 渲染两个视图并合成，`split` 时左半边取第一个视图、右半边取第二个视图，中间画一条分隔线
+fn render(views: &mut [View; 2], scenes: &[Scene; 2], eye: Vec3, split: bool) -> Vec<u32> {
+    let count = if split { 2 } else { 1 };
+    for (view, scene) in views.iter_mut().zip(scenes).take(count) {
+        let r = &mut view.rasterizer;
+        r.clear(Buffers::all());
+        r.set_view(get_view_matrix(eye));
+        r.set_projection(transform::perspective(
+            60f32.to_radians(),
+            WIDTH as f32 / HEIGHT as f32,
+            0.1,
+            50.0,
+        ));
+        r.draw(scene.pos, scene.ind, scene.col, Primitive::Triangle);
+    }
+
+    let mut buffer = vec![0u32; WIDTH * HEIGHT];
+    for (i, pixel) in buffer.iter_mut().enumerate() {
+        let x = i % WIDTH;
+        let view = if split && x >= WIDTH / 2 { 1 } else { 0 };
+        *pixel = views[view].rasterizer.framebuffer()[i].argb();
+        if split && x == WIDTH / 2 {
+            *pixel = Color::RED.argb();
+        }
+    }
+    buffer
+}
+
+fn get_view_matrix(eye: Vec3) -> Mat4 {
+    // 稍微俯视，让地面占据更多画面
+    transform::rotation_x(10f32.to_radians()) * transform::translation(-eye)
+}
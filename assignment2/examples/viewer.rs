@@ -0,0 +1,345 @@
+//! 模型查看器，例如 GAMES101 作业 3 中的 spot 奶牛或 Stanford bunny。
+//!
+//! `cargo run --example viewer -r -- model.obj [-t texture.png] [-n normal.png | -b height.png | -d height.png]
+//! [-a angle] [-s shading] [-p metallic,roughness] [-o output.png|.gif|.apng] [-f frames]`
+//!
+//! 支持 OBJ、PLY 和 STL 格式，按扩展名区分。
+//!
+//! 材质中的 `map_Kd` 贴图会按分组绑定，`-t` 指定的贴图用于没有贴图的分组。
+//! 模型使用 Blinn-Phong 光照，`-s` 指定着色方式（flat、gouraud 或 phong）。
+//! `-p metallic,roughness` 改用 Cook-Torrance 模型的 PBR 材质。
+//! `-n`、`-b`、`-d` 分别把图片作为法线贴图、凹凸贴图和位移贴图，逐像素着色时生效。
+//! `-f frames` 与 `-o` 一起使用时保存模型转一圈的动画，`.gif`、`.apng` 保存为动画文件，其他文件名保存为编号的 PNG 序列。
+//! 窗口中拖动鼠标左键旋转视角（arcball），右键或中键平移，滚轮缩放；`F` 切换到漫游模式，
+//! `WASD` 前后左右移动、`Q`、`E` 下降上升，按住 `Shift` 加速，拖动鼠标左键转向。
+//! `R` 重置相机，`TAB` 切换着色方式。
+
+use games101_core::animation;
+use games101_core::camera::Camera;
+use games101_core::controls::{window_to_ndc, FlyControls, OrbitControls};
+use games101_core::lighting::{Light, Material, PbrMaterial, ShadingMode};
+use games101_core::mesh::{Mesh, MeshBuffers};
+use games101_core::rasterizer::{Buffers, Primitive, RasterMode, Rasterizer, TextureId};
+use games101_core::surface::SurfaceMap;
+use games101_core::texture::{Filter, Texture};
+use games101_core::transform;
+use games101_core::{obj, ply, stl};
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode, Window, WindowOptions};
+use nalgebra_glm::{vec3, Mat4, Vec3};
+use std::env;
+use std::f32::consts::PI;
+use std::time::Instant;
+
+const WIDTH: usize = 700;
+const HEIGHT: usize = 700;
+
+const TITLE: &str = "Viewer";
+
+const SHADING_MODES: [ShadingMode; 3] =
+    [ShadingMode::Flat, ShadingMode::Gouraud, ShadingMode::Phong];
+
+struct Model {
+    buffers: MeshBuffers,
+    /// 每个分组绑定的贴图
+    textures: Vec<Option<TextureId>>,
+    /// 把模型移到原点并缩放到大约 2.5 个单位大小
+    normalize: Mat4,
+}
+
+fn main() {
+    let mut angle = 0f32;
+    let mut path = None;
+    let mut texture_path = None;
+    let mut surface_map = None;
+    let mut filename = None;
+    let mut shading = ShadingMode::default();
+    let mut pbr = None;
+    let mut frames = 1;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-t" => texture_path = args.next(),
+            "-n" => surface_map = args.next().map(|p| (p, SurfaceMap::Normal)),
+            "-b" => surface_map = args.next().map(|p| (p, SurfaceMap::Bump { strength: 5.0 })),
+            "-d" => {
+                let map = SurfaceMap::Displacement {
+                    strength: 5.0,
+                    depth: 0.05,
+                };
+                surface_map = args.next().map(|p| (p, map));
+            }
+            "-o" => filename = args.next(),
+            "-s" => match args.next().as_deref().and_then(parse_shading) {
+                Some(mode) => shading = mode,
+                None => eprintln!("unknown shading mode, expected flat, gouraud or phong"),
+            },
+            "-p" => match args.next().as_deref().and_then(parse_pbr) {
+                Some(material) => pbr = Some(material),
+                None => eprintln!("expected -p metallic,roughness"),
+            },
+            "-a" => angle = args.next().and_then(|a| a.parse().ok()).unwrap_or(0.0),
+            "-f" => frames = args.next().and_then(|n| n.parse().ok()).unwrap_or(1),
+            _ => path = Some(arg),
+        }
+    }
+    let Some(path) = path else {
+        eprintln!(
+            "usage: viewer model.obj [-t texture.png] [-n|-b|-d map.png] [-a angle] [-s shading] [-p metallic,roughness] [-o output.png|.gif|.apng] [-f frames]"
+        );
+        return;
+    };
+
+    let extension = std::path::Path::new(&path)
+        .extension()
+        .map(|e| e.to_ascii_lowercase());
+    let mesh = match extension.as_ref().and_then(|e| e.to_str()) {
+        Some("ply") => ply::load(&path).map_err(|e| e.to_string()),
+        Some("stl") => stl::load(&path).map_err(|e| e.to_string()),
+        _ => obj::load(&path).map_err(|e| e.to_string()),
+    };
+    let mut mesh = mesh.unwrap_or_else(|e| panic!("{}: {}", path, e));
+    for warning in &mesh.warnings {
+        eprintln!("{}: {}", path, warning);
+    }
+    println!(
+        "{}: {} vertices, {} triangles, {} groups",
+        path,
+        mesh.positions.len(),
+        mesh.indices.len(),
+        mesh.groups.len()
+    );
+
+    let mut rasterizer = Rasterizer::new(WIDTH as u32, HEIGHT as u32);
+    rasterizer.set_raster_mode(RasterMode::EdgeFunction);
+    rasterizer.set_threads(std::thread::available_parallelism().map_or(1, |n| n.get()));
+    let model = load_model(&mut rasterizer, &mut mesh, texture_path.as_deref());
+    if let Some((path, map)) = surface_map {
+        let texture = load_texture(&mut rasterizer, path.as_ref());
+        rasterizer.bind_surface_map(texture.map(|texture| (texture, map)));
+    }
+    rasterizer.set_material(Some(Material::default()));
+    rasterizer.set_pbr_material(pbr);
+    // PBR 的漫反射项除以了 π，光强相应加大
+    let intensity = if pbr.is_some() { 500.0 * PI } else { 500.0 };
+    rasterizer.set_lights(vec![
+        Light::point(vec3(20.0, 20.0, 20.0), Vec3::repeat(intensity)),
+        Light::point(vec3(-20.0, 20.0, 0.0), Vec3::repeat(intensity)),
+    ]);
+    rasterizer.set_shading_mode(shading);
+
+    let mut camera = Camera::default();
+    camera.projection.set_aspect(WIDTH as f32 / HEIGHT as f32);
+
+    // render to file
+    if let Some(filename) = filename {
+        if frames > 1 {
+            // 转台动画，模型绕 y 轴转一整圈
+            animation::render(&filename, WIDTH as u32, HEIGHT as u32, frames, 30, |t| {
+                draw(&mut rasterizer, &model, &camera, angle + 360.0 * t);
+                rasterizer.framebuffer().to_vec()
+            })
+            .unwrap();
+        } else {
+            draw(&mut rasterizer, &model, &camera, angle);
+            rasterizer.save_framebuffer_to_png(&filename).unwrap();
+        }
+        return;
+    }
+
+    // render to window
+    let mut fly = false;
+    let mut window = Window::new(
+        &title(shading, fly),
+        WIDTH,
+        HEIGHT,
+        WindowOptions::default(),
+    )
+    .unwrap_or_else(|e| {
+        panic!("{}", e);
+    });
+    window.limit_update_rate(Some(std::time::Duration::from_micros(16600)));
+    let mut orbit = OrbitControls::from_camera(&camera);
+    let mut fly_controls = FlyControls::from_camera(&camera);
+    let mut last_mouse = None;
+    let mut last_frame = Instant::now();
+    while window.is_open() && !window.is_key_down(Key::Escape) {
+        draw(&mut rasterizer, &model, &camera, angle);
+        window
+            .update_with_buffer(rasterizer.framebuffer_u32(), WIDTH, HEIGHT)
+            .unwrap();
+        let now = Instant::now();
+        let dt = (now - last_frame).as_secs_f32();
+        last_frame = now;
+
+        if window.is_key_pressed(Key::Tab, KeyRepeat::No) {
+            let next = SHADING_MODES
+                .iter()
+                .position(|&m| m == shading)
+                .map_or(0, |i| i + 1);
+            shading = SHADING_MODES[next % SHADING_MODES.len()];
+            rasterizer.set_shading_mode(shading);
+            window.set_title(&title(shading, fly));
+        }
+        if window.is_key_pressed(Key::F, KeyRepeat::No) {
+            fly = !fly;
+            if fly {
+                fly_controls = FlyControls::from_camera(&camera);
+            } else {
+                // 回到轨道模式时保持原来的距离，绕前方的点旋转
+                camera.target = camera.position + camera.forward() * orbit.distance;
+                orbit = OrbitControls::from_camera(&camera);
+            }
+            window.set_title(&title(shading, fly));
+        }
+        if window.is_key_pressed(Key::R, KeyRepeat::No) {
+            camera = Camera {
+                projection: camera.projection,
+                ..Camera::default()
+            };
+            orbit = OrbitControls::from_camera(&camera);
+            fly_controls = FlyControls::from_camera(&camera);
+        }
+
+        let mouse = window
+            .get_mouse_pos(MouseMode::Pass)
+            .map(|(x, y)| window_to_ndc(x, y, WIDTH, HEIGHT));
+        let left = window.get_mouse_down(MouseButton::Left);
+        let right =
+            window.get_mouse_down(MouseButton::Right) || window.get_mouse_down(MouseButton::Middle);
+        if let (Some(from), Some(to)) = (last_mouse, mouse) {
+            if fly {
+                if left {
+                    fly_controls.look(to - from);
+                }
+            } else if left {
+                orbit.rotate(from, to);
+            } else if right {
+                orbit.pan(to - from, &camera.projection);
+            }
+        }
+        last_mouse = mouse;
+
+        if fly {
+            let axis = |positive: Key, negative: Key| {
+                window.is_key_down(positive) as i32 as f32
+                    - window.is_key_down(negative) as i32 as f32
+            };
+            let movement = vec3(
+                axis(Key::D, Key::A),
+                axis(Key::E, Key::Q),
+                axis(Key::W, Key::S),
+            );
+            let boost = if window.is_key_down(Key::LeftShift) {
+                4.0
+            } else {
+                1.0
+            };
+            fly_controls.update(&mut camera, movement * boost, dt);
+        } else {
+            if let Some((_, scroll)) = window.get_scroll_wheel() {
+                orbit.zoom(scroll.signum());
+            }
+            orbit.apply(&mut camera);
+        }
+    }
+}
+
+fn parse_shading(name: &str) -> Option<ShadingMode> {
+    match name {
+        "flat" => Some(ShadingMode::Flat),
+        "gouraud" => Some(ShadingMode::Gouraud),
+        "phong" => Some(ShadingMode::Phong),
+        _ => None,
+    }
+}
+
+fn parse_pbr(arg: &str) -> Option<PbrMaterial> {
+    let (metallic, roughness) = arg.split_once(',')?;
+    Some(PbrMaterial {
+        metallic: metallic.trim().parse().ok()?,
+        roughness: roughness.trim().parse().ok()?,
+        ..PbrMaterial::default()
+    })
+}
+
+fn title(shading: ShadingMode, fly: bool) -> String {
+    let controls = if fly {
+        "WASD/QE to move, drag to look"
+    } else {
+        "drag to rotate, right drag to pan, scroll to zoom"
+    };
+    format!(
+        "{} - {:?} shading - {}, F to fly, TAB to switch shading, ESC to exit",
+        TITLE, shading, controls
+    )
+}
+
+fn load_texture(rasterizer: &mut Rasterizer, path: &std::path::Path) -> Option<TextureId> {
+    match Texture::load(path) {
+        Ok(mut texture) => {
+            texture.generate_mipmaps();
+            texture.set_filter(Filter::Trilinear);
+            Some(rasterizer.load_texture(texture))
+        }
+        Err(e) => {
+            eprintln!("failed to load texture {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+fn load_model(rasterizer: &mut Rasterizer, mesh: &mut Mesh, texture_path: Option<&str>) -> Model {
+    let fallback = texture_path.and_then(|path| load_texture(rasterizer, path.as_ref()));
+    let textures: Vec<Option<TextureId>> = mesh
+        .groups
+        .iter()
+        .map(|group| {
+            let material = group.material.map(|m| &mesh.materials[m]);
+            match material.and_then(|m| m.diffuse_texture.as_ref()) {
+                Some(path) => load_texture(rasterizer, path),
+                None => fallback,
+            }
+        })
+        .collect();
+
+    // 光照需要法线
+    if mesh.normals.is_empty() {
+        mesh.compute_normals();
+    }
+
+    let (min, max) = mesh.bounds().unwrap_or_default();
+    let size = (max - min).max().max(f32::EPSILON);
+    let normalize = transform::scaling(Vec3::repeat(2.5 / size))
+        * transform::translation(-(min + max).scale(0.5));
+    Model {
+        buffers: rasterizer.load_mesh(mesh),
+        textures,
+        normalize,
+    }
+}
+
+fn draw(rasterizer: &mut Rasterizer, model: &Model, camera: &Camera, angle: f32) {
+    rasterizer.clear(Buffers::all());
+    let rotation = transform::rotation_y(angle.to_radians());
+    rasterizer.set_model(rotation * model.normalize);
+    camera.apply(rasterizer);
+
+    let buffers = &model.buffers;
+    rasterizer.bind_normals(buffers.normals);
+    rasterizer.bind_tex_coords(buffers.tex_coords);
+    // PLY、STL 没有分组，整个网格画一次
+    if buffers.groups.is_empty() {
+        rasterizer.bind_texture(None);
+        rasterizer.draw_mesh(buffers);
+    }
+    for (indices, &texture) in buffers.groups.iter().zip(&model.textures) {
+        // 没有纹理坐标时无法采样贴图
+        rasterizer.bind_texture(texture.filter(|_| buffers.tex_coords.is_some()));
+        rasterizer.draw(
+            buffers.positions,
+            *indices,
+            buffers.colors,
+            Primitive::Triangle,
+        );
+    }
+}
@@ -1,3 +1,10 @@
-pub mod color;
+//! 作业 2 中需要实现的函数，渲染管线在 `games101_core` 中。
 pub mod rasterizer;
-pub mod triangle;
+/// 参考实现，评分程序用它检查作业中的函数
+pub mod solution;
+
+// 交给 `Rasterizer::set_exercises` 的函数，开启 `solution` feature 时换成参考实现
+#[cfg(not(feature = "solution"))]
+pub use rasterizer::EXERCISES;
+#[cfg(feature = "solution")]
+pub use solution::EXERCISES;
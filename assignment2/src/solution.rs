@@ -0,0 +1,45 @@
+//! 作业 2 的参考实现，开启 `solution` feature 时替换 [`crate::rasterizer`] 中的函数，评分程序也用它作为标准答案。
+
+use games101_core::edge::EdgeTriangle;
+use games101_core::rasterizer::{Exercises, Rasterizer};
+use games101_core::triangle::Triangle;
+use nalgebra_glm::Vec4;
+
+pub const EXERCISES: Exercises = Exercises {
+    inside_triangle,
+    rasterize_triangle,
+};
+
+/// 像素中心在三角形内时返回 `true`。
+///
+/// 恰好落在边上的像素按 top-left 规则判断，与 `RasterMode::EdgeFunction` 相同，
+/// 两个三角形的公共边上的像素只会被其中一个覆盖
+pub fn inside_triangle(x: u32, y: u32, v: [Vec4; 3]) -> bool {
+    EdgeTriangle::new(&v).is_some_and(|t| t.covers_pixel(x, y))
+}
+
+/// 遍历与屏幕相交的包围盒，逐像素做深度测试并着色
+pub fn rasterize_triangle(r: &mut Rasterizer, t: &Triangle) {
+    let v = t.to_vector4();
+    let min_x = v[0].x.min(v[1].x).min(v[2].x).floor().max(0.0) as u32;
+    let min_y = v[0].y.min(v[1].y).min(v[2].y).floor().max(0.0) as u32;
+    let max_x = v[0].x.max(v[1].x).max(v[2].x).ceil().max(0.0) as u32;
+    let max_y = v[0].y.max(v[1].y).max(v[2].y).ceil().max(0.0) as u32;
+    let max_x = max_x.min(r.width().saturating_sub(1));
+    let max_y = max_y.min(r.height().saturating_sub(1));
+
+    for x in min_x..=max_x {
+        for y in min_y..=max_y {
+            if !inside_triangle(x, y, v) {
+                continue;
+            }
+            let (alpha, beta, gamma) = Rasterizer::compute_barycentric2d(x, y, v);
+            let w_reciprocal = 1.0 / (alpha / v[0].w + beta / v[1].w + gamma / v[2].w);
+            let z_interpolated =
+                alpha * v[0].z / v[0].w + beta * v[1].z / v[1].w + gamma * v[2].z / v[2].w;
+            let z_interpolated = z_interpolated * w_reciprocal;
+            let color = r.shader().shade(t, alpha, beta, gamma);
+            r.set_pixel(x, y, color, z_interpolated);
+        }
+    }
+}
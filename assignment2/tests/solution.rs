@@ -0,0 +1,122 @@
+#![cfg(feature = "solution")]
+
+use assignment1::{get_model_matrix, get_projection_matrix};
+use assignment2::solution::inside_triangle;
+use assignment2::EXERCISES;
+use games101_core::color::Color;
+use games101_core::edge::EdgeTriangle;
+use games101_core::rasterizer::{Buffers, Primitive, RasterMode, Rasterizer};
+use games101_core::transform;
+use nalgebra_glm::{vec3, vec4};
+
+/// 作业 2 的两个三角形
+fn render(threads: usize, mode: RasterMode) -> Vec<Color> {
+    let mut r = Rasterizer::new(64, 64);
+    r.set_exercises(EXERCISES);
+    r.set_threads(threads);
+    r.set_raster_mode(mode);
+    let pos = r.load_positions(vec![
+        vec3(2.0, 0.0, -2.0),
+        vec3(0.0, 2.0, -2.0),
+        vec3(-2.0, 0.0, -2.0),
+        vec3(3.5, -1.0, -5.0),
+        vec3(2.5, 1.5, -5.0),
+        vec3(-1.0, 0.5, -5.0),
+    ]);
+    let ind = r.load_indices(vec![vec3(0.0, 1.0, 2.0), vec3(3.0, 4.0, 5.0)]);
+    let col = r.load_colors(vec![
+        Color::RED,
+        Color::RED,
+        Color::RED,
+        Color::BLUE,
+        Color::BLUE,
+        Color::BLUE,
+    ]);
+    r.clear(Buffers::all());
+    r.set_model(get_model_matrix(10.0));
+    r.set_view(transform::translation(vec3(0.0, 0.0, -5.0)));
+    r.set_projection(get_projection_matrix(45.0, 1.0, 0.1, 50.0));
+    r.draw(pos, ind, col, Primitive::Triangle);
+    r.framebuffer().to_vec()
+}
+
+#[test]
+fn reference_matches_builtin_rasterizers() {
+    let reference = render(1, RasterMode::BoundingBox);
+    // 近处的红色三角形挡住了远处的蓝色三角形
+    // 顶点颜色插值会截断，所以只比较主要分量
+    let red = |c: &Color| c.r > 250 && c.b == 0;
+    let blue = |c: &Color| c.b > 250 && c.r == 0;
+    assert!(reference.iter().any(red) && reference.iter().any(blue));
+    assert!(red(&reference[(63 - 40) * 64 + 32]));
+    // 包围盒方式在多线程时也串行调用参考实现
+    assert_eq!(render(4, RasterMode::BoundingBox), reference);
+    // 覆盖的像素与边函数完全相同，颜色只有插值的舍入误差
+    let edge = render(1, RasterMode::EdgeFunction);
+    let covered = |image: &[Color]| image.iter().map(|c| *c != Color::BLACK).collect::<Vec<_>>();
+    assert_eq!(covered(&edge), covered(&reference));
+    let diff = edge.iter().zip(&reference).filter(|(a, b)| a != b).count();
+    assert!(diff < 64, "{}", diff);
+}
+
+#[test]
+fn shared_edge_pixels_are_covered_once() {
+    // 正方形沿对角线拆成两个三角形，对角线正好穿过一排像素中心
+    let v = |x: f32, y: f32| vec4(x, y, 0.0, 1.0);
+    let (a, b, c, d) = (v(8.0, 8.0), v(40.0, 8.0), v(40.0, 40.0), v(8.0, 40.0));
+    let triangles = [[a, b, c], [a, c, d]];
+    let mut counts = [[0; 48]; 48];
+    for t in triangles {
+        let edge = EdgeTriangle::new(&t).unwrap();
+        for (y, row) in counts.iter_mut().enumerate() {
+            for (x, count) in row.iter_mut().enumerate() {
+                let (x, y) = (x as u32, y as u32);
+                let inside = inside_triangle(x, y, t);
+                assert_eq!(inside, edge.covers_pixel(x, y), "({}, {})", x, y);
+                *count += inside as u32;
+            }
+        }
+    }
+    let diagonal = (8..40).filter(|&i| counts[i][i] == 1).count();
+    assert_eq!(diagonal, 32);
+    for (y, row) in counts.iter().enumerate() {
+        for (x, &count) in row.iter().enumerate() {
+            let interior = (8..40).contains(&x) && (8..40).contains(&y);
+            assert_eq!(count, interior as u32, "({}, {})", x, y);
+        }
+    }
+}
+
+#[test]
+fn reference_matrices() {
+    let p = get_model_matrix(90.0) * vec3(1.0, 0.0, 0.0).push(1.0);
+    assert!((p - vec3(0.0, 1.0, 0.0).push(1.0)).norm() < 1e-6);
+    assert_eq!(
+        get_projection_matrix(60.0, 1.5, 0.1, 50.0),
+        transform::perspective(60f32.to_radians(), 1.5, 0.1, 50.0)
+    );
+}
+
+#[test]
+fn unwritable_output_is_reported() {
+    let dir = std::env::temp_dir().join(format!("games101-assignment2-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let missing = dir.join("missing").join("out.png");
+    let unsupported = dir.join("out.xyz");
+    for path in [missing, unsupported] {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_assignment2"))
+            .arg("-o")
+            .arg(&path)
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(1), "{:?}", path);
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(
+            stderr.starts_with("assignment2: failed to write"),
+            "{}",
+            stderr
+        );
+        assert!(!path.exists());
+    }
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,309 @@
+//! 把渲染出的一组帧保存为编号的 PNG 序列、GIF 或 APNG 动画。
+
+use crate::color::Color;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::codecs::png::PngEncoder;
+use image::error::{ParameterError, ParameterErrorKind};
+use image::{Delay, ExtendedColorType, ImageEncoder, ImageError, ImageResult, RgbaImage};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// 动画的保存格式
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum AnimationFormat {
+    /// 每帧一个 PNG 文件，见 [`sequence_path`]
+    PngSequence,
+    Gif,
+    Apng,
+}
+
+impl AnimationFormat {
+    /// 按扩展名选择，`.gif` 和 `.apng` 以外的都保存为 PNG 序列
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path.extension().map(|e| e.to_ascii_lowercase());
+        match extension.as_ref().and_then(|e| e.to_str()) {
+            Some("gif") => AnimationFormat::Gif,
+            Some("apng") => AnimationFormat::Apng,
+            _ => AnimationFormat::PngSequence,
+        }
+    }
+}
+
+/// PNG 序列中第 `index` 帧的文件名，`turntable.png` 的第 3 帧是 `turntable_0003.png`
+pub fn sequence_path(path: &Path, index: u32) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path
+        .extension()
+        .map_or("png".into(), |e| e.to_string_lossy());
+    path.with_file_name(format!("{}_{:04}.{}", stem, index, extension))
+}
+
+/// GIF 编码器和 `finish` 共用的输出文件。
+///
+/// 编码器在析构时才写入 GIF 的结尾，析构中的写入错误会被丢掉，
+/// 所以这里记下第一个写入错误，由 [`AnimationWriter::finish`] 返回
+#[derive(Clone)]
+struct GifFile(Arc<Mutex<GifFileState>>);
+
+struct GifFileState {
+    file: BufWriter<File>,
+    error: Option<io::Error>,
+}
+
+impl GifFile {
+    fn new(file: File) -> Self {
+        Self(Arc::new(Mutex::new(GifFileState {
+            file: BufWriter::new(file),
+            error: None,
+        })))
+    }
+
+    /// 写入所有缓冲的数据，返回之前记下的错误或者 flush 的错误
+    fn finish(&self) -> io::Result<()> {
+        let mut state = self.0.lock().unwrap();
+        match state.error.take() {
+            Some(e) => Err(e),
+            None => state.file.flush(),
+        }
+    }
+
+    fn record<T>(state: &mut GifFileState, result: io::Result<T>) -> io::Result<T> {
+        result.map_err(|e| {
+            let copy = io::Error::new(e.kind(), e.to_string());
+            state.error.get_or_insert(e);
+            copy
+        })
+    }
+}
+
+impl Write for GifFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let state = &mut *self.0.lock().unwrap();
+        let result = state.file.write(buf);
+        Self::record(state, result)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let state = &mut *self.0.lock().unwrap();
+        let result = state.file.flush();
+        Self::record(state, result)
+    }
+}
+
+enum Output {
+    Sequence(PathBuf),
+    Gif {
+        encoder: Box<GifEncoder<GifFile>>,
+        file: GifFile,
+    },
+    Apng {
+        file: BufWriter<File>,
+        /// fcTL 和 fdAT 共用的序号
+        sequence: u32,
+    },
+}
+
+/// 逐帧写入的动画，循环播放
+pub struct AnimationWriter {
+    output: Output,
+    width: u32,
+    height: u32,
+    frame_count: u32,
+    fps: u32,
+    written: u32,
+}
+
+impl AnimationWriter {
+    /// 按 [`AnimationFormat::from_path`] 选择格式。APNG 的帧数需要写在文件开头，所以要事先给出 `frame_count`
+    pub fn create(
+        path: impl AsRef<Path>,
+        width: u32,
+        height: u32,
+        frame_count: u32,
+        fps: u32,
+    ) -> ImageResult<Self> {
+        let path = path.as_ref();
+        let output = match AnimationFormat::from_path(path) {
+            AnimationFormat::PngSequence => Output::Sequence(path.to_path_buf()),
+            AnimationFormat::Gif => {
+                // 速度 10 是 NeuQuant 量化质量和速度的折中，默认的 1 非常慢
+                let file = GifFile::new(File::create(path)?);
+                let mut encoder = GifEncoder::new_with_speed(file.clone(), 10);
+                encoder.set_repeat(Repeat::Infinite)?;
+                Output::Gif {
+                    encoder: Box::new(encoder),
+                    file,
+                }
+            }
+            AnimationFormat::Apng => Output::Apng {
+                file: BufWriter::new(File::create(path)?),
+                sequence: 0,
+            },
+        };
+        Ok(Self {
+            output,
+            width,
+            height,
+            frame_count,
+            fps: fps.max(1),
+            written: 0,
+        })
+    }
+
+    /// 写入一帧，`frame` 按行从上到下排列，与 [`Rasterizer::framebuffer`](crate::rasterizer::Rasterizer::framebuffer) 相同
+    pub fn write_frame(&mut self, frame: &[Color]) -> ImageResult<()> {
+        if frame.len() != (self.width * self.height) as usize {
+            return Err(parameter_error(ParameterErrorKind::DimensionMismatch));
+        }
+        if self.written == self.frame_count {
+            return Err(parameter_error(ParameterErrorKind::Generic(format!(
+                "animation has only {} frames",
+                self.frame_count
+            ))));
+        }
+        let bytes: &[u8] = bytemuck::cast_slice(frame);
+        match &mut self.output {
+            Output::Sequence(path) => image::save_buffer(
+                sequence_path(path, self.written),
+                bytes,
+                self.width,
+                self.height,
+                ExtendedColorType::Rgba8,
+            )?,
+            Output::Gif { encoder, .. } => {
+                let image = RgbaImage::from_raw(self.width, self.height, bytes.to_vec()).unwrap();
+                let delay = Delay::from_numer_denom_ms(1000, self.fps);
+                encoder.encode_frame(image::Frame::from_parts(image, 0, 0, delay))?;
+            }
+            Output::Apng { file, sequence } => {
+                let mut png = Vec::new();
+                PngEncoder::new(&mut png).write_image(
+                    bytes,
+                    self.width,
+                    self.height,
+                    ExtendedColorType::Rgba8,
+                )?;
+                let chunks = png_chunks(&png);
+                if self.written == 0 {
+                    file.write_all(PNG_SIGNATURE)?;
+                    for (kind, data) in chunks.iter().filter(|(kind, _)| kind == b"IHDR") {
+                        write_chunk(file, kind, data)?;
+                    }
+                    let mut actl = self.frame_count.to_be_bytes().to_vec();
+                    actl.extend(0u32.to_be_bytes()); // 无限循环
+                    write_chunk(file, b"acTL", &actl)?;
+                }
+                let mut fctl = Vec::with_capacity(26);
+                for value in [*sequence, self.width, self.height, 0, 0] {
+                    fctl.extend(value.to_be_bytes());
+                }
+                fctl.extend(1u16.to_be_bytes());
+                fctl.extend((self.fps.min(u16::MAX as u32) as u16).to_be_bytes());
+                // dispose_op = NONE，blend_op = SOURCE
+                fctl.extend([0, 0]);
+                write_chunk(file, b"fcTL", &fctl)?;
+                *sequence += 1;
+                // 第一帧就是普通 PNG 的图像数据，之后的帧放在带序号的 fdAT 中
+                for (_, data) in chunks.iter().filter(|(kind, _)| kind == b"IDAT") {
+                    if self.written == 0 {
+                        write_chunk(file, b"IDAT", data)?;
+                    } else {
+                        let mut fdat = sequence.to_be_bytes().to_vec();
+                        fdat.extend_from_slice(data);
+                        write_chunk(file, b"fdAT", &fdat)?;
+                        *sequence += 1;
+                    }
+                }
+            }
+        }
+        self.written += 1;
+        Ok(())
+    }
+
+    /// 写完所有帧后调用，帧数必须与 `create` 时给出的相同
+    pub fn finish(self) -> ImageResult<()> {
+        if self.written != self.frame_count {
+            return Err(parameter_error(ParameterErrorKind::Generic(format!(
+                "expected {} frames, got {}",
+                self.frame_count, self.written
+            ))));
+        }
+        match self.output {
+            Output::Sequence(_) => {}
+            Output::Gif { encoder, file } => {
+                // GIF 的结尾在编码器析构时写入，之后再 flush 并检查写入错误
+                drop(encoder);
+                file.finish()?;
+            }
+            Output::Apng { mut file, .. } => {
+                write_chunk(&mut file, b"IEND", &[])?;
+                file.flush()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 渲染 `frame_count` 帧并保存。`render` 的参数 `t` 从 0 均匀增加到 1（不含 1），
+/// 转台或者相机路径按 `t` 计算，首尾相接就能无缝循环
+pub fn render(
+    path: impl AsRef<Path>,
+    width: u32,
+    height: u32,
+    frame_count: u32,
+    fps: u32,
+    mut render: impl FnMut(f32) -> Vec<Color>,
+) -> ImageResult<()> {
+    let mut writer = AnimationWriter::create(path, width, height, frame_count, fps)?;
+    for i in 0..frame_count {
+        writer.write_frame(&render(i as f32 / frame_count as f32))?;
+    }
+    writer.finish()
+}
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+fn parameter_error(kind: ParameterErrorKind) -> ImageError {
+    ImageError::Parameter(ParameterError::from_kind(kind))
+}
+
+/// 拆分 PNG 文件中的块，返回类型和数据
+fn png_chunks(png: &[u8]) -> Vec<([u8; 4], &[u8])> {
+    let mut chunks = Vec::new();
+    let mut pos = PNG_SIGNATURE.len();
+    while pos + 12 <= png.len() {
+        let len = u32::from_be_bytes(png[pos..pos + 4].try_into().unwrap()) as usize;
+        let kind = png[pos + 4..pos + 8].try_into().unwrap();
+        let Some(data) = png.get(pos + 8..pos + 8 + len) else {
+            break;
+        };
+        chunks.push((kind, data));
+        pos += 12 + len;
+    }
+    chunks
+}
+
+fn write_chunk(w: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> std::io::Result<()> {
+    w.write_all(&(data.len() as u32).to_be_bytes())?;
+    w.write_all(kind)?;
+    w.write_all(data)?;
+    w.write_all(&crc32(&[kind, data]).to_be_bytes())
+}
+
+/// PNG 块使用的 CRC-32（多项式 0xEDB88320）
+fn crc32(parts: &[&[u8]]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in parts.iter().flat_map(|p| p.iter()) {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
@@ -0,0 +1,181 @@
+//! 作业程序共用的主程序：解析命令行参数，渲染到文件，或者打开窗口。
+//!
+//! 窗口中 A/D 键旋转模型，鼠标拖动绕原点旋转相机，右键拖动平移，滚轮缩放，R 键恢复命令行指定的相机。
+
+use crate::cli::RenderArgs;
+use crate::color::Color;
+use crate::controls::{window_to_ndc, OrbitControls};
+use crate::rasterizer::{Buffers, ColBufId, Exercises, IndBufId, PosBufId, Primitive, Rasterizer};
+use crate::transform;
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode, Window, WindowOptions};
+use nalgebra_glm::{Mat4, Vec3};
+
+/// 作业中实现的变换矩阵，角度都以度为单位
+#[derive(Debug, Clone, Copy)]
+pub struct Transforms {
+    /// 绕 z 轴旋转 `angle` 的模型矩阵
+    pub model: fn(f32) -> Mat4,
+    /// 透视投影矩阵，参数依次为竖直方向的视角、宽高比、近平面和远平面的距离
+    pub projection: fn(f32, f32, f32, f32) -> Mat4,
+}
+
+/// 作业程序绘制的三角形
+#[derive(Debug, Clone, PartialEq)]
+pub struct Geometry {
+    pub positions: Vec<Vec3>,
+    pub indices: Vec<Vec3>,
+    pub colors: Vec<Color>,
+    pub primitive: Primitive,
+}
+
+/// 作业程序
+#[derive(Debug, Clone, Copy)]
+pub struct App {
+    /// 命令行用法中的程序名
+    pub program: &'static str,
+    /// 窗口标题
+    pub title: &'static str,
+    pub transforms: Transforms,
+    /// 交给 [`Rasterizer::set_exercises`] 的函数，为 `None` 时使用默认值
+    pub exercises: Option<Exercises>,
+}
+
+/// 上传到光栅化器中的 [`Geometry`]
+#[derive(Debug, Clone, Copy)]
+struct Loaded {
+    pos: PosBufId,
+    ind: IndBufId,
+    col: ColBufId,
+    primitive: Primitive,
+}
+
+impl App {
+    /// 解析 `std::env::args()`，有 `-o` 时渲染到文件，否则打开窗口。
+    ///
+    /// 无法写入输出文件时打印错误并以状态码 1 退出
+    pub fn run(&self, geometry: &Geometry) {
+        let mut args = RenderArgs::from_env(self.program);
+        let (mut rasterizer, loaded) = self.rasterizer(&args, geometry);
+
+        // render to file
+        if let Some(filename) = &args.output {
+            let result = args.render_to_file(&mut rasterizer, filename, |r, args| {
+                self.draw(r, args, args.camera().view_matrix(), loaded)
+            });
+            if let Err(e) = result {
+                eprintln!("{}: failed to write {}: {}", self.program, filename, e);
+                std::process::exit(1);
+            }
+            return;
+        }
+
+        // render to window
+        let (width, height) = (args.width as usize, args.height as usize);
+        let mut window = Window::new(
+            format!(
+                "{} - A/D to rotate the model, drag to orbit, right drag to pan, scroll to zoom, R to reset, ESC to exit",
+                self.title
+            )
+            .as_str(),
+            width,
+            height,
+            WindowOptions::default(),
+        )
+        .unwrap_or_else(|e| {
+            panic!("{}", e);
+        });
+        window.limit_update_rate(Some(std::time::Duration::from_micros(16600)));
+        let mut camera = args.camera();
+        let mut orbit = OrbitControls::from_camera(&camera);
+        let mut last_mouse = None;
+        while window.is_open() && !window.is_key_down(Key::Escape) {
+            self.draw(&mut rasterizer, &args, camera.view_matrix(), loaded);
+            let buffer = args
+                .resolve(&rasterizer)
+                .iter()
+                .map(|c| c.argb())
+                .collect::<Vec<u32>>();
+
+            window.update_with_buffer(&buffer, width, height).unwrap();
+
+            if window.is_key_down(Key::A) {
+                args.angle += 0.5;
+            } else if window.is_key_down(Key::D) {
+                args.angle -= 0.5;
+            }
+            if window.is_key_pressed(Key::R, KeyRepeat::No) {
+                orbit = OrbitControls::from_camera(&args.camera());
+            }
+
+            let mouse = window
+                .get_mouse_pos(MouseMode::Pass)
+                .map(|(x, y)| window_to_ndc(x, y, width, height));
+            if let (Some(from), Some(to)) = (last_mouse, mouse) {
+                if window.get_mouse_down(MouseButton::Left) {
+                    orbit.rotate(from, to);
+                } else if window.get_mouse_down(MouseButton::Right)
+                    || window.get_mouse_down(MouseButton::Middle)
+                {
+                    orbit.pan(to - from, &camera.projection);
+                }
+            }
+            last_mouse = mouse;
+            if let Some((_, scroll)) = window.get_scroll_wheel() {
+                orbit.zoom(scroll.signum());
+            }
+            orbit.apply(&mut camera);
+        }
+    }
+
+    /// 按 `args` 渲染一帧并保存到 `path`，见 [`RenderArgs::render_to_file`]
+    pub fn render_to_file(
+        &self,
+        args: &RenderArgs,
+        geometry: &Geometry,
+        path: &str,
+    ) -> image::ImageResult<()> {
+        let (mut rasterizer, loaded) = self.rasterizer(args, geometry);
+        args.render_to_file(&mut rasterizer, path, |r, args| {
+            self.draw(r, args, args.camera().view_matrix(), loaded)
+        })
+    }
+
+    /// 绕 z 轴旋转时使用作业中的模型矩阵，其他的轴用罗德里格斯旋转公式
+    pub fn model_matrix(&self, args: &RenderArgs) -> Mat4 {
+        if args.axis == Vec3::z() {
+            (self.transforms.model)(args.angle)
+        } else {
+            transform::rotation(args.angle.to_radians(), args.axis)
+        }
+    }
+
+    fn rasterizer(&self, args: &RenderArgs, geometry: &Geometry) -> (Rasterizer, Loaded) {
+        let (width, height) = args.framebuffer_size();
+        let mut rasterizer = Rasterizer::new(width, height);
+        rasterizer.set_raster_mode(args.mode);
+        if let Some(exercises) = self.exercises {
+            rasterizer.set_exercises(exercises);
+        }
+        let loaded = Loaded {
+            pos: rasterizer.load_positions(geometry.positions.clone()),
+            ind: rasterizer.load_indices(geometry.indices.clone()),
+            col: rasterizer.load_colors(geometry.colors.clone()),
+            primitive: geometry.primitive,
+        };
+        (rasterizer, loaded)
+    }
+
+    /// 观察矩阵由窗口中的相机控制，投影矩阵使用作业中的函数
+    fn draw(&self, rasterizer: &mut Rasterizer, args: &RenderArgs, view: Mat4, loaded: Loaded) {
+        rasterizer.clear(Buffers::all());
+        rasterizer.set_model(self.model_matrix(args));
+        rasterizer.set_view(view);
+        rasterizer.set_projection((self.transforms.projection)(
+            args.fov,
+            args.aspect(),
+            args.near,
+            args.far,
+        ));
+        rasterizer.draw(loaded.pos, loaded.ind, loaded.col, loaded.primitive);
+    }
+}
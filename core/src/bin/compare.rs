@@ -0,0 +1,90 @@
+use games101_core::metrics::{self, Metrics, DEFAULT_PPD};
+use std::env;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+usage: compare <reference> <test> [--heat-map out.png] [--map flip|ssim|diff] [--ppd pixels]
+
+Prints MSE, PSNR, SSIM and a FLIP-style perceptual difference between two images
+of the same size. --heat-map saves the per-pixel error of --map [default: flip].";
+
+fn main() -> ExitCode {
+    let mut paths = Vec::new();
+    let mut heat_map = None;
+    let mut map = "flip".to_string();
+    let mut ppd = DEFAULT_PPD;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--heat-map" | "--map" | "--ppd" => {
+                let Some(value) = args.next() else {
+                    eprintln!("compare: missing value for {}", arg);
+                    return usage();
+                };
+                match arg.as_str() {
+                    "--heat-map" => heat_map = Some(value),
+                    "--map" if ["flip", "ssim", "diff"].contains(&value.as_str()) => map = value,
+                    "--ppd" => match value.parse::<f32>() {
+                        Ok(p) if p.is_finite() && p > 0.0 => ppd = p,
+                        _ => {
+                            eprintln!("compare: invalid value for --ppd: {}", value);
+                            return usage();
+                        }
+                    },
+                    _ => {
+                        eprintln!("compare: invalid value for {}: {}", arg, value);
+                        return usage();
+                    }
+                }
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            }
+            _ if !arg.starts_with('-') => paths.push(arg),
+            _ => return usage(),
+        }
+    }
+    let [reference, test] = &paths[..] else {
+        return usage();
+    };
+
+    let load = |path: &str| {
+        metrics::load_image(path).map_err(|e| eprintln!("failed to read {}: {}", path, e))
+    };
+    let (Ok((width, height, a)), Ok((w, h, b))) = (load(reference), load(test)) else {
+        return ExitCode::FAILURE;
+    };
+    if (width, height) != (w, h) {
+        eprintln!(
+            "image sizes differ: {} is {}x{}, {} is {}x{}",
+            reference, width, height, test, w, h
+        );
+        return ExitCode::FAILURE;
+    }
+
+    let result = Metrics {
+        mse: metrics::mse(&a, &b),
+        psnr: metrics::psnr(&a, &b),
+        ssim: metrics::ssim(&a, &b, width, height),
+        flip: metrics::flip(&a, &b, width, height, ppd),
+    };
+    println!("{}", result);
+    if let Some(path) = heat_map {
+        let errors = match map.as_str() {
+            "ssim" => metrics::ssim_map(&a, &b, width, height),
+            "diff" => metrics::difference_map(&a, &b, width, height),
+            _ => metrics::flip_map(&a, &b, width, height, ppd),
+        };
+        if let Err(e) = errors.save_heat_map(&path) {
+            eprintln!("failed to write {}: {}", path, e);
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+fn usage() -> ExitCode {
+    eprintln!("{}", USAGE);
+    ExitCode::from(2)
+}
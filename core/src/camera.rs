@@ -0,0 +1,157 @@
+use crate::rasterizer::Rasterizer;
+use crate::transform;
+use nalgebra_glm::{vec3, Mat4, Vec3};
+
+/// 投影方式，都是右手系，相机看向 -z 方向
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// 透视投影，`fov_y` 是竖直方向的视角（弧度），`far` 为 `None` 时远平面在无穷远处
+    Perspective {
+        fov_y: f32,
+        aspect: f32,
+        near: f32,
+        far: Option<f32>,
+    },
+    /// 正交投影，`height` 是可见范围的高度，宽度为 `height * aspect`
+    Orthographic {
+        height: f32,
+        aspect: f32,
+        near: f32,
+        far: f32,
+    },
+}
+
+impl Default for Projection {
+    /// 与作业框架相同：45° 视角，近平面 0.1，远平面 50
+    fn default() -> Self {
+        Projection::Perspective {
+            fov_y: 45f32.to_radians(),
+            aspect: 1.0,
+            near: 0.1,
+            far: Some(50.0),
+        }
+    }
+}
+
+impl Projection {
+    /// 投影矩阵。
+    ///
+    /// 默认把近平面映射到 NDC 的 z = -1、远平面映射到 1；`reversed_z` 时近平面映射到 1、远平面映射到 0，
+    /// 浮点数在 0 附近更密，远处的深度精度更高
+    pub fn matrix(&self, reversed_z: bool) -> Mat4 {
+        let m = match *self {
+            Projection::Perspective {
+                fov_y,
+                aspect,
+                near,
+                far: Some(far),
+            } => transform::perspective(fov_y, aspect, near, far),
+            Projection::Perspective {
+                fov_y,
+                aspect,
+                near,
+                far: None,
+            } => transform::infinite_perspective(fov_y, aspect, near),
+            Projection::Orthographic {
+                height,
+                aspect,
+                near,
+                far,
+            } => {
+                let (w, h) = (height * aspect / 2.0, height / 2.0);
+                transform::orthographic(-w, w, -h, h, near, far)
+            }
+        };
+        if reversed_z {
+            transform::reverse_depth(&m)
+        } else {
+            m
+        }
+    }
+
+    /// 距离相机 `distance` 处可见范围的一半高度
+    pub fn half_height_at(&self, distance: f32) -> f32 {
+        match *self {
+            Projection::Perspective { fov_y, .. } => distance * (fov_y / 2.0).tan(),
+            Projection::Orthographic { height, .. } => height / 2.0,
+        }
+    }
+
+    pub fn aspect(&self) -> f32 {
+        match *self {
+            Projection::Perspective { aspect, .. } | Projection::Orthographic { aspect, .. } => {
+                aspect
+            }
+        }
+    }
+
+    /// 宽高比，窗口大小改变时需要更新
+    pub fn set_aspect(&mut self, value: f32) {
+        match self {
+            Projection::Perspective { aspect, .. } | Projection::Orthographic { aspect, .. } => {
+                *aspect = value
+            }
+        }
+    }
+}
+
+/// 用位置、观察目标和上方向描述的相机
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    pub position: Vec3,
+    pub target: Vec3,
+    pub up: Vec3,
+    pub projection: Projection,
+    /// 使用反转的深度，见 [`Projection::matrix`]
+    pub reversed_z: bool,
+}
+
+impl Default for Camera {
+    /// 与作业框架相同，位于 (0, 0, 5) 看向原点
+    fn default() -> Self {
+        Self::look_at(vec3(0.0, 0.0, 5.0), Vec3::zeros(), vec3(0.0, 1.0, 0.0))
+    }
+}
+
+impl Camera {
+    /// 使用默认透视投影的相机
+    pub fn look_at(position: Vec3, target: Vec3, up: Vec3) -> Self {
+        Self {
+            position,
+            target,
+            up,
+            projection: Projection::default(),
+            reversed_z: false,
+        }
+    }
+
+    pub fn with_projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    pub fn with_reversed_z(mut self, reversed_z: bool) -> Self {
+        self.reversed_z = reversed_z;
+        self
+    }
+
+    /// 相机看向的单位方向
+    pub fn forward(&self) -> Vec3 {
+        (self.target - self.position).normalize()
+    }
+
+    pub fn view_matrix(&self) -> Mat4 {
+        transform::look_at(self.position, self.target, self.up)
+    }
+
+    pub fn projection_matrix(&self) -> Mat4 {
+        self.projection.matrix(self.reversed_z)
+    }
+
+    /// 设置光栅化器的观察矩阵、投影矩阵和深度方向
+    pub fn apply(&self, rasterizer: &mut Rasterizer) {
+        rasterizer.set_view(self.view_matrix());
+        rasterizer.set_projection(self.projection_matrix());
+        rasterizer.set_reversed_z(self.reversed_z);
+    }
+}
@@ -0,0 +1,350 @@
+//! 作业程序共用的命令行参数。
+
+use crate::animation;
+use crate::camera::{Camera, Projection};
+use crate::color::Color;
+use crate::rasterizer::{RasterMode, Rasterizer};
+use nalgebra_glm::{vec3, Vec3};
+use std::fmt;
+use std::str::FromStr;
+
+const OPTIONS: &str = "\
+Options:
+  -o, --output <file>    render to a file instead of opening a window; with --frames, a .gif or
+                         .apng file is animated and other names become a numbered PNG sequence
+  -s, --size <WxH>       image size [default: 700x700]
+  -a, --angle <degrees>  model rotation angle [default: 0]
+      --axis <x,y,z>     model rotation axis [default: 0,0,1]
+  -e, --eye <x,y,z>      eye position, looking at the origin [default: 0,0,5]
+      --fov <degrees>    vertical field of view [default: 45]
+      --near <distance>  near plane distance [default: 0.1]
+      --far <distance>   far plane distance [default: 50]
+      --msaa <samples>   samples per pixel, 1, 4, 9, 16, ... [default: 1]
+      --mode <mode>      bbox, edge or scanline [default: bbox]
+      --frames <count>   render a turntable of <count> frames, one full turn [default: 1]
+      --fps <rate>       animation frame rate [default: 30]
+  -r <degrees> [file]    same as --angle <degrees> --output <file>, file defaults to output.png
+  -h, --help             print this help";
+
+/// 渲染一帧需要的参数
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderArgs {
+    /// 保存图像的路径，为 `None` 时打开窗口
+    pub output: Option<String>,
+    pub width: u32,
+    pub height: u32,
+    /// 模型的旋转角度，以度为单位
+    pub angle: f32,
+    /// 模型的旋转轴，不需要是单位向量
+    pub axis: Vec3,
+    /// 相机的位置，相机看向原点
+    pub eye: Vec3,
+    /// 竖直方向的视角，以度为单位
+    pub fov: f32,
+    pub near: f32,
+    pub far: f32,
+    /// 每个像素的采样数，必须是平方数。
+    ///
+    /// 用超采样实现：以 `sqrt(msaa)` 倍的分辨率渲染，再对每个像素的采样取平均
+    pub msaa: u32,
+    pub mode: RasterMode,
+    /// 大于 1 时保存转台动画，模型在这些帧中转一整圈
+    pub frames: u32,
+    pub fps: u32,
+}
+
+impl Default for RenderArgs {
+    fn default() -> Self {
+        Self {
+            output: None,
+            width: 700,
+            height: 700,
+            angle: 0.0,
+            axis: Vec3::z(),
+            eye: vec3(0.0, 0.0, 5.0),
+            fov: 45.0,
+            near: 0.1,
+            far: 50.0,
+            msaa: 1,
+            mode: RasterMode::BoundingBox,
+            frames: 1,
+            fps: 30,
+        }
+    }
+}
+
+/// 解析命令行参数时的错误
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgsError {
+    /// 请求打印帮助，不算真正的错误
+    Help,
+    Unknown(String),
+    MissingValue(&'static str),
+    InvalidValue {
+        option: &'static str,
+        value: String,
+    },
+}
+
+impl fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgsError::Help => write!(f, "help requested"),
+            ArgsError::Unknown(arg) => write!(f, "unknown argument '{}'", arg),
+            ArgsError::MissingValue(option) => write!(f, "missing value for {}", option),
+            ArgsError::InvalidValue { option, value } => {
+                write!(f, "invalid value '{}' for {}", value, option)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArgsError {}
+
+/// 光栅化器每一边的最大像素数，超采样后的大小也不能超过它
+pub const MAX_FRAMEBUFFER_SIZE: u32 = 16384;
+
+/// 程序 `program` 的用法说明
+pub fn usage(program: &str) -> String {
+    format!("Usage: {} [options]\n\n{}", program, OPTIONS)
+}
+
+impl RenderArgs {
+    /// 解析不含程序名的参数
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, ArgsError> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter().peekable();
+        while let Some(arg) = args.next() {
+            let mut value =
+                |option: &'static str| args.next().ok_or(ArgsError::MissingValue(option));
+            match arg.as_str() {
+                "-o" | "--output" => parsed.output = Some(value("--output")?),
+                "-s" | "--size" => {
+                    let size = value("--size")?;
+                    (parsed.width, parsed.height) =
+                        parse_size(&size).ok_or_else(|| invalid("--size", size))?;
+                }
+                "-a" | "--angle" => parsed.angle = parse_number("--angle", value("--angle")?)?,
+                "--axis" => {
+                    let axis = value("--axis")?;
+                    parsed.axis = parse_vec3(&axis)
+                        .filter(|a| a.norm() > 0.0)
+                        .ok_or_else(|| invalid("--axis", axis))?;
+                }
+                "-e" | "--eye" => {
+                    let eye = value("--eye")?;
+                    parsed.eye = parse_vec3(&eye).ok_or_else(|| invalid("--eye", eye))?;
+                }
+                "--fov" => {
+                    let fov = value("--fov")?;
+                    parsed.fov = parse_number("--fov", fov.clone())?;
+                    if !(parsed.fov > 0.0 && parsed.fov < 180.0) {
+                        return Err(invalid("--fov", fov));
+                    }
+                }
+                "--near" => parsed.near = parse_number("--near", value("--near")?)?,
+                "--far" => parsed.far = parse_number("--far", value("--far")?)?,
+                "--msaa" => {
+                    let msaa = value("--msaa")?;
+                    parsed.msaa = parse_number("--msaa", msaa.clone())?;
+                    let n = (parsed.msaa as f64).sqrt().round() as u32;
+                    if n == 0 || n.checked_mul(n) != Some(parsed.msaa) {
+                        return Err(invalid("--msaa", msaa));
+                    }
+                }
+                "--mode" => {
+                    let mode = value("--mode")?;
+                    parsed.mode = match mode.as_str() {
+                        "bbox" => RasterMode::BoundingBox,
+                        "edge" => RasterMode::EdgeFunction,
+                        "scanline" => RasterMode::Scanline,
+                        _ => return Err(invalid("--mode", mode)),
+                    };
+                }
+                "--frames" => parsed.frames = parse_positive("--frames", value("--frames")?)?,
+                "--fps" => parsed.fps = parse_positive("--fps", value("--fps")?)?,
+                // 旧的 `-r angle [file]` 写法
+                "-r" => {
+                    parsed.angle = parse_number("-r", value("-r")?)?;
+                    let file = args.next_if(|a| !a.starts_with('-'));
+                    parsed.output = Some(file.unwrap_or_else(|| "output.png".to_string()));
+                }
+                "-h" | "--help" => return Err(ArgsError::Help),
+                _ => return Err(ArgsError::Unknown(arg)),
+            }
+        }
+        if !(parsed.near > 0.0 && parsed.far > parsed.near) {
+            let value = format!("{}..{}", parsed.near, parsed.far);
+            return Err(invalid("--near/--far", value));
+        }
+        if parsed.checked_framebuffer_size().is_none() {
+            let value = format!(
+                "{}x{} with {} samples",
+                parsed.width, parsed.height, parsed.msaa
+            );
+            return Err(invalid("--size/--msaa", value));
+        }
+        Ok(parsed)
+    }
+
+    /// 解析 `std::env::args()`，出错时打印用法并退出
+    pub fn from_env(program: &str) -> Self {
+        match Self::parse(std::env::args().skip(1)) {
+            Ok(args) => args,
+            Err(ArgsError::Help) => {
+                println!("{}", usage(program));
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("{}: {}\n\n{}", program, e, usage(program));
+                std::process::exit(2);
+            }
+        }
+    }
+
+    pub fn aspect(&self) -> f32 {
+        self.width as f32 / self.height as f32
+    }
+
+    /// 位于 `eye` 看向原点的相机，投影参数与命令行相同。
+    ///
+    /// 相机在 y 轴上时屏幕上方为 -z 方向，在原点时看向 -z
+    pub fn camera(&self) -> Camera {
+        let target = if self.eye == Vec3::zeros() {
+            -Vec3::z()
+        } else {
+            Vec3::zeros()
+        };
+        let up = if self.eye.x == 0.0 && self.eye.z == 0.0 && self.eye.y != 0.0 {
+            -Vec3::z()
+        } else {
+            Vec3::y()
+        };
+        Camera::look_at(self.eye, target, up).with_projection(Projection::Perspective {
+            fov_y: self.fov.to_radians(),
+            aspect: self.aspect(),
+            near: self.near,
+            far: Some(self.far),
+        })
+    }
+
+    /// 每个方向上的采样数
+    pub fn samples_per_axis(&self) -> u32 {
+        (self.msaa as f64).sqrt().round() as u32
+    }
+
+    /// 光栅化器的大小，超采样时是输出图像的 `sqrt(msaa)` 倍。
+    ///
+    /// 任意一边超过 [`MAX_FRAMEBUFFER_SIZE`] 时 panic，[`RenderArgs::parse`] 已经拒绝了这样的参数
+    pub fn framebuffer_size(&self) -> (u32, u32) {
+        self.checked_framebuffer_size()
+            .expect("framebuffer size exceeds MAX_FRAMEBUFFER_SIZE")
+    }
+
+    fn checked_framebuffer_size(&self) -> Option<(u32, u32)> {
+        let n = self.samples_per_axis();
+        let side = |s: u32| s.checked_mul(n).filter(|&s| s <= MAX_FRAMEBUFFER_SIZE);
+        Some((side(self.width)?, side(self.height)?))
+    }
+
+    /// 把光栅化器的帧缓冲缩小到输出大小，每个像素取对应采样的平均值
+    pub fn resolve(&self, r: &Rasterizer) -> Vec<Color> {
+        let n = self.samples_per_axis() as usize;
+        let frame = r.framebuffer();
+        if n == 1 {
+            return frame.to_vec();
+        }
+        let (width, height) = (self.width as usize, self.height as usize);
+        let stride = width * n;
+        let mut resolved = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = [0u32; 4];
+                for sy in 0..n {
+                    let row = (y * n + sy) * stride + x * n;
+                    for c in &frame[row..row + n] {
+                        sum[0] += c.r as u32;
+                        sum[1] += c.g as u32;
+                        sum[2] += c.b as u32;
+                        sum[3] += c.a as u32;
+                    }
+                }
+                let count = (n * n) as u32;
+                let avg = |s: u32| ((s + count / 2) / count) as u8;
+                resolved.push(Color::new_rgba(
+                    avg(sum[0]),
+                    avg(sum[1]),
+                    avg(sum[2]),
+                    avg(sum[3]),
+                ));
+            }
+        }
+        resolved
+    }
+
+    /// 用 `draw` 渲染并保存到 `path`。`frames` 大于 1 时保存转台动画，
+    /// 每帧的旋转角度从 `angle` 开始增加 `360 / frames` 度，见 [`animation::render`]
+    pub fn render_to_file(
+        &self,
+        r: &mut Rasterizer,
+        path: &str,
+        mut draw: impl FnMut(&mut Rasterizer, &RenderArgs),
+    ) -> image::ImageResult<()> {
+        if self.frames <= 1 {
+            draw(r, self);
+            return self.save(r, path);
+        }
+        let mut frame = self.clone();
+        animation::render(path, self.width, self.height, self.frames, self.fps, |t| {
+            frame.angle = self.angle + 360.0 * t;
+            draw(r, &frame);
+            self.resolve(r)
+        })
+    }
+
+    /// 把 [`resolve`](Self::resolve) 后的图像保存为 PNG
+    pub fn save(&self, r: &Rasterizer, path: &str) -> image::ImageResult<()> {
+        let frame = self.resolve(r);
+        image::save_buffer(
+            path,
+            bytemuck::cast_slice(&frame),
+            self.width,
+            self.height,
+            image::ExtendedColorType::Rgba8,
+        )
+    }
+}
+
+fn invalid(option: &'static str, value: String) -> ArgsError {
+    ArgsError::InvalidValue { option, value }
+}
+
+fn parse_number<T: FromStr>(option: &'static str, value: String) -> Result<T, ArgsError> {
+    value.parse().map_err(|_| invalid(option, value))
+}
+
+fn parse_positive(option: &'static str, value: String) -> Result<u32, ArgsError> {
+    match value.parse() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(invalid(option, value)),
+    }
+}
+
+/// `WxH`，宽和高都必须大于零
+fn parse_size(s: &str) -> Option<(u32, u32)> {
+    let (w, h) = s.split_once(['x', 'X'])?;
+    let (w, h) = (w.parse().ok()?, h.parse().ok()?);
+    (w > 0 && h > 0).then_some((w, h))
+}
+
+/// `x,y,z`
+fn parse_vec3(s: &str) -> Option<Vec3> {
+    let v: Vec<f32> = s
+        .split(',')
+        .map(|c| c.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    match v[..] {
+        [x, y, z] if v.iter().all(|c| c.is_finite()) => Some(vec3(x, y, z)),
+        _ => None,
+    }
+}
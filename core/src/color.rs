@@ -1,3 +1,4 @@
+use nalgebra_glm::Vec3;
 use std::ops::{Add, Mul};
 
 #[repr(C)]
@@ -57,6 +58,28 @@ impl Color {
     }
 }
 
+/// 把 [0, 1] 范围的线性颜色转换到 sRGB 空间
+pub fn linear_to_srgb(c: Vec3) -> Vec3 {
+    c.map(|x| {
+        if x <= 0.0031308 {
+            x * 12.92
+        } else {
+            1.055 * x.powf(1.0 / 2.4) - 0.055
+        }
+    })
+}
+
+/// 把 [0, 1] 范围的 sRGB 颜色转换到线性空间
+pub fn srgb_to_linear(c: Vec3) -> Vec3 {
+    c.map(|x| {
+        if x <= 0.04045 {
+            x / 12.92
+        } else {
+            ((x + 0.055) / 1.055).powf(2.4)
+        }
+    })
+}
+
 impl Mul<f32> for Color {
     type Output = Color;
 
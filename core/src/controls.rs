@@ -0,0 +1,153 @@
+//! 与窗口库无关的相机交互：绕目标旋转、平移、缩放的轨道相机，以及第一人称的漫游相机。
+//!
+//! 鼠标位置都用 NDC 表示，x、y 的范围是 [-1, 1]，y 朝上。
+
+use crate::camera::{Camera, Projection};
+use nalgebra_glm::{vec3, Mat3, Quat, Vec2, Vec3};
+
+/// 把窗口中的像素坐标（原点在左上角）转换为 NDC
+pub fn window_to_ndc(x: f32, y: f32, width: usize, height: usize) -> Vec2 {
+    Vec2::new(2.0 * x / width as f32 - 1.0, 1.0 - 2.0 * y / height as f32)
+}
+
+/// 绕目标点旋转的轨道相机，旋转使用 arcball
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitControls {
+    pub target: Vec3,
+    pub distance: f32,
+    /// 相机坐标系相对世界坐标系的旋转，相机位于 `target + rotation * (0, 0, distance)`
+    pub rotation: Quat,
+    pub min_distance: f32,
+    pub max_distance: f32,
+}
+
+impl OrbitControls {
+    /// 与相机当前的位置和朝向一致。
+    ///
+    /// 相机与目标重合时从 +z 方向看向目标，距离取 `min_distance`；上方向与视线平行时另选一个与视线垂直的方向
+    pub fn from_camera(camera: &Camera) -> Self {
+        let offset = camera.position - camera.target;
+        let z = offset.try_normalize(f32::EPSILON).unwrap_or(Vec3::z());
+        let x = camera
+            .up
+            .cross(&z)
+            .try_normalize(f32::EPSILON)
+            .or_else(|| Vec3::y().cross(&z).try_normalize(f32::EPSILON))
+            .unwrap_or(Vec3::x());
+        let y = z.cross(&x);
+        let (min_distance, max_distance) = (0.01, 1000.0);
+        Self {
+            target: camera.target,
+            distance: offset.norm().clamp(min_distance, max_distance),
+            rotation: nalgebra_glm::mat3_to_quat(&Mat3::from_columns(&[x, y, z])),
+            min_distance,
+            max_distance,
+        }
+    }
+
+    /// 鼠标从 `from` 拖到 `to`，模型跟着鼠标转动
+    pub fn rotate(&mut self, from: Vec2, to: Vec2) {
+        let (a, b) = (arcball_point(from), arcball_point(to));
+        let axis = a.cross(&b);
+        let Some(axis) = axis.try_normalize(f32::EPSILON) else {
+            return;
+        };
+        let angle = a.dot(&b).clamp(-1.0, 1.0).acos();
+        // 观察空间中模型转动 angle，相当于相机反向转动
+        let turn = nalgebra_glm::quat_angle_axis(-angle, &axis);
+        self.rotation = nalgebra_glm::quat_normalize(&(self.rotation * turn));
+    }
+
+    /// 鼠标移动 `delta`（NDC），目标所在平面上的点跟着鼠标移动
+    pub fn pan(&mut self, delta: Vec2, projection: &Projection) {
+        let half_height = projection.half_height_at(self.distance);
+        let shift = vec3(
+            delta.x * half_height * projection.aspect(),
+            delta.y * half_height,
+            0.0,
+        );
+        self.target -= nalgebra_glm::quat_rotate_vec3(&self.rotation, &shift);
+    }
+
+    /// 按滚轮的格数缩放，正数靠近目标
+    pub fn zoom(&mut self, steps: f32) {
+        self.distance =
+            (self.distance * 0.9f32.powf(steps)).clamp(self.min_distance, self.max_distance);
+    }
+
+    /// 更新相机的位置、目标和上方向
+    pub fn apply(&self, camera: &mut Camera) {
+        let rotate = |v: Vec3| nalgebra_glm::quat_rotate_vec3(&self.rotation, &v);
+        camera.target = self.target;
+        camera.position = self.target + rotate(vec3(0.0, 0.0, self.distance));
+        camera.up = rotate(vec3(0.0, 1.0, 0.0));
+    }
+}
+
+/// 把 NDC 中的点投影到 arcball 上，球外的部分使用双曲面，拖动时旋转是连续的
+fn arcball_point(p: Vec2) -> Vec3 {
+    let d2 = p.norm_squared();
+    let z = if d2 <= 0.5 {
+        (1.0 - d2).sqrt()
+    } else {
+        0.5 / d2.sqrt()
+    };
+    vec3(p.x, p.y, z).normalize()
+}
+
+/// 第一人称漫游相机，上方向固定为世界 +y
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlyControls {
+    /// 绕 y 轴的转角（弧度），0 时看向 -z，正数向右转
+    pub yaw: f32,
+    /// 俯仰角（弧度），限制在 ±89° 内
+    pub pitch: f32,
+    /// 移动速度（单位每秒）
+    pub speed: f32,
+    /// 鼠标移动整个 NDC 宽度（2）时转动的角度（弧度）
+    pub sensitivity: f32,
+}
+
+const MAX_PITCH: f32 = 89.0 * std::f32::consts::PI / 180.0;
+
+impl FlyControls {
+    /// 与相机当前的朝向一致
+    pub fn from_camera(camera: &Camera) -> Self {
+        let f = camera.forward();
+        Self {
+            yaw: f.x.atan2(-f.z),
+            pitch: f.y.clamp(-1.0, 1.0).asin().clamp(-MAX_PITCH, MAX_PITCH),
+            speed: 2.0,
+            sensitivity: std::f32::consts::PI / 2.0,
+        }
+    }
+
+    pub fn forward(&self) -> Vec3 {
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+        vec3(cos_pitch * sin_yaw, sin_pitch, -cos_pitch * cos_yaw)
+    }
+
+    /// 鼠标移动 `delta`（NDC），向右、向上移动时相机向右、向上看
+    pub fn look(&mut self, delta: Vec2) {
+        let scale = self.sensitivity / 2.0;
+        self.yaw += delta.x * scale;
+        self.pitch = (self.pitch + delta.y * scale).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    /// 按 `movement`（x 向右、y 向上、z 向前，各分量在 [-1, 1] 内）移动 `dt` 秒，
+    /// 移动距离只与时间有关，与帧率无关
+    pub fn update(&self, camera: &mut Camera, movement: Vec3, dt: f32) {
+        let up = vec3(0.0, 1.0, 0.0);
+        let forward = self.forward();
+        let right = forward.cross(&up).normalize();
+        let mut direction = right * movement.x + up * movement.y + forward * movement.z;
+        // 斜着走不会更快
+        if direction.norm() > 1.0 {
+            direction.normalize_mut();
+        }
+        camera.position += direction * (self.speed * dt);
+        camera.target = camera.position + forward;
+        camera.up = up;
+    }
+}
@@ -0,0 +1,241 @@
+use nalgebra_glm::Vec4;
+
+/// 顶点坐标的亚像素精度（位），屏幕坐标会被吸附到 1/256 像素的定点网格上
+pub const SUBPIXEL_BITS: u32 = 8;
+
+pub(crate) const SUBPIXEL_ONE: i64 = 1 << SUBPIXEL_BITS;
+pub(crate) const SUBPIXEL_HALF: i64 = SUBPIXEL_ONE / 2;
+
+/// 定点化前允许的最大坐标绝对值（像素），保证边函数的乘积不会溢出 i64
+const MAX_COORDINATE: f32 = (1 << 20) as f32;
+
+/// 坐标是否是有限值并且可以无溢出地定点化
+pub(crate) fn representable(x: f32) -> bool {
+    x.abs() <= MAX_COORDINATE
+}
+
+/// 把屏幕坐标吸附到亚像素定点网格上
+pub(crate) fn snap(x: f32) -> i64 {
+    (x * SUBPIXEL_ONE as f32).round() as i64
+}
+
+/// 一条有向边 a -> b 的边函数 E(p) = A * p.x + B * p.y + C。
+///
+/// 三角形按逆时针排列时，内部点的边函数值为正。
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    a: i64,
+    b: i64,
+    c: i64,
+    /// 不满足 top-left 规则的边需要把 E == 0 的点排除在外
+    bias: i64,
+}
+
+impl Edge {
+    fn new(from: (i64, i64), to: (i64, i64)) -> Self {
+        let a = from.1 - to.1;
+        let b = to.0 - from.0;
+        let c = from.0 * to.1 - from.1 * to.0;
+        // 屏幕坐标 y 轴向上，逆时针三角形中：
+        // 左边是向下走的边，上边是水平且向 -x 方向走的边
+        let is_left = a > 0;
+        let is_top = a == 0 && b < 0;
+        let bias = if is_left || is_top { 0 } else { -1 };
+        Self { a, b, c, bias }
+    }
+
+    fn eval(&self, x: i64, y: i64) -> i64 {
+        self.a * x + self.b * y + self.c
+    }
+}
+
+/// 完成定点化和建立边函数的屏幕空间三角形。
+///
+/// 采样点位于像素中心，两个三角形的公共边上的像素按 top-left 规则只会被其中一个覆盖。
+#[derive(Debug, Clone, Copy)]
+pub struct EdgeTriangle {
+    /// edges[i] 是顶点 i 对边的边函数，其值与顶点 i 的重心坐标成正比
+    edges: [Edge; 3],
+    area: i64,
+    /// 调整为逆时针时是否交换过顶点 1 和 2
+    swapped: bool,
+    min: (i64, i64),
+    max: (i64, i64),
+}
+
+impl EdgeTriangle {
+    /// 由屏幕空间顶点建立三角形，面积为零、坐标不是有限值或超出定点数表示范围时返回 `None`。
+    pub fn new(v: &[Vec4; 3]) -> Option<Self> {
+        if !v
+            .iter()
+            .all(|vertex| representable(vertex.x) && representable(vertex.y))
+        {
+            return None;
+        }
+        let mut p = v.map(|vertex| (snap(vertex.x), snap(vertex.y)));
+
+        let mut area = Edge::new(p[1], p[2]).eval(p[0].0, p[0].1);
+        if area == 0 {
+            return None;
+        }
+        let swapped = area < 0;
+        if swapped {
+            p.swap(1, 2);
+            area = -area;
+        }
+
+        let edges = [
+            Edge::new(p[1], p[2]),
+            Edge::new(p[2], p[0]),
+            Edge::new(p[0], p[1]),
+        ];
+        let min = (
+            p[0].0.min(p[1].0).min(p[2].0),
+            p[0].1.min(p[1].1).min(p[2].1),
+        );
+        let max = (
+            p[0].0.max(p[1].0).max(p[2].0),
+            p[0].1.max(p[1].1).max(p[2].1),
+        );
+        Some(Self {
+            edges,
+            area,
+            swapped,
+            min,
+            max,
+        })
+    }
+
+    /// 三角形可能覆盖的像素范围（闭区间），即中心落在包围盒内的像素。
+    pub fn pixel_bounds(&self) -> ((i64, i64), (i64, i64)) {
+        let first = |v: i64| (v - SUBPIXEL_HALF + SUBPIXEL_ONE - 1).div_euclid(SUBPIXEL_ONE);
+        let last = |v: i64| (v - SUBPIXEL_HALF).div_euclid(SUBPIXEL_ONE);
+        (
+            (first(self.min.0), first(self.min.1)),
+            (last(self.max.0), last(self.max.1)),
+        )
+    }
+
+    /// 像素 `(x, y)` 的中心是否被三角形覆盖，与 [`for_each_pixel`](Self::for_each_pixel) 的结果一致
+    pub fn covers_pixel(&self, x: u32, y: u32) -> bool {
+        let center = |p: u32| p as i64 * SUBPIXEL_ONE + SUBPIXEL_HALF;
+        self.covers(&self.edges.map(|e| e.eval(center(x), center(y))))
+    }
+
+    /// 遍历 `[x_min, x_max] x [y_min, y_max]` 内被三角形覆盖的像素。
+    ///
+    /// 回调参数为像素坐标和按原顶点顺序排列的重心坐标 `(alpha, beta, gamma)`。
+    /// 边函数在行内和行间都是增量计算的。
+    pub fn for_each_pixel(
+        &self,
+        x_min: u32,
+        y_min: u32,
+        x_max: u32,
+        y_max: u32,
+        mut f: impl FnMut(u32, u32, (f32, f32, f32)),
+    ) {
+        let (lo, hi) = self.pixel_bounds();
+        let x_begin = lo.0.max(x_min as i64);
+        let y_begin = lo.1.max(y_min as i64);
+        let x_end = hi.0.min(x_max as i64);
+        let y_end = hi.1.min(y_max as i64);
+        if x_begin > x_end || y_begin > y_end {
+            return;
+        }
+
+        let center = |p: i64| p * SUBPIXEL_ONE + SUBPIXEL_HALF;
+        let mut row = self.edges.map(|e| e.eval(center(x_begin), center(y_begin)));
+        let step_x = self.edges.map(|e| e.a * SUBPIXEL_ONE);
+        let step_y = self.edges.map(|e| e.b * SUBPIXEL_ONE);
+
+        for y in y_begin..=y_end {
+            let mut w = row;
+            for x in x_begin..=x_end {
+                if self.covers(&w) {
+                    f(x as u32, y as u32, self.barycentric(&w));
+                }
+                w.iter_mut().zip(step_x).for_each(|(w, step)| *w += step);
+            }
+            row.iter_mut().zip(step_y).for_each(|(w, step)| *w += step);
+        }
+    }
+
+    /// 以对齐到偶数坐标的 2x2 像素块为单位遍历 `[x_min, x_max] x [y_min, y_max]` 内被三角形覆盖的像素。
+    ///
+    /// 回调参数为块左下角的像素坐标、块内 4 个像素的重心坐标和覆盖掩码，
+    /// 顺序为 `(x, y)`、`(x + 1, y)`、`(x, y + 1)`、`(x + 1, y + 1)`。
+    /// 只有覆盖了至少一个像素的块才会回调；块内未覆盖的像素的重心坐标可能落在三角形外，
+    /// 只用于和相邻像素做差求导数，不应写入。
+    pub fn for_each_quad(
+        &self,
+        x_min: u32,
+        y_min: u32,
+        x_max: u32,
+        y_max: u32,
+        mut f: impl FnMut(u32, u32, [(f32, f32, f32); 4], [bool; 4]),
+    ) {
+        let (lo, hi) = self.pixel_bounds();
+        let x_begin = lo.0.max(x_min as i64);
+        let y_begin = lo.1.max(y_min as i64);
+        let x_end = hi.0.min(x_max as i64);
+        let y_end = hi.1.min(y_max as i64);
+        if x_begin > x_end || y_begin > y_end {
+            return;
+        }
+        // 块的位置只取决于像素坐标，分块光栅化时求出的导数与串行时一致
+        let (quad_x, quad_y) = (x_begin & !1, y_begin & !1);
+
+        let center = |p: i64| p * SUBPIXEL_ONE + SUBPIXEL_HALF;
+        let mut row = self.edges.map(|e| e.eval(center(quad_x), center(quad_y)));
+        let step_x = self.edges.map(|e| e.a * SUBPIXEL_ONE);
+        let step_y = self.edges.map(|e| e.b * SUBPIXEL_ONE);
+
+        for y in (quad_y..=y_end).step_by(2) {
+            let mut w = row;
+            for x in (quad_x..=x_end).step_by(2) {
+                let mut lanes = [w; 4];
+                for i in 0..3 {
+                    lanes[1][i] += step_x[i];
+                    lanes[2][i] += step_y[i];
+                    lanes[3][i] += step_x[i] + step_y[i];
+                }
+                let mask = [0, 1, 2, 3].map(|lane| {
+                    let (px, py) = (x + (lane & 1) as i64, y + (lane >> 1) as i64);
+                    (x_begin..=x_end).contains(&px)
+                        && (y_begin..=y_end).contains(&py)
+                        && self.covers(&lanes[lane])
+                });
+                if mask.contains(&true) {
+                    f(
+                        x as u32,
+                        y as u32,
+                        lanes.map(|w| self.barycentric(&w)),
+                        mask,
+                    );
+                }
+                w.iter_mut()
+                    .zip(step_x)
+                    .for_each(|(w, step)| *w += 2 * step);
+            }
+            row.iter_mut()
+                .zip(step_y)
+                .for_each(|(w, step)| *w += 2 * step);
+        }
+    }
+
+    /// 按 top-left 规则判断边函数值为 `w` 的采样点是否被覆盖
+    fn covers(&self, w: &[i64; 3]) -> bool {
+        (0..3).all(|i| w[i] + self.edges[i].bias >= 0)
+    }
+
+    /// 由边函数值得到按原顶点顺序排列的重心坐标
+    fn barycentric(&self, w: &[i64; 3]) -> (f32, f32, f32) {
+        let area = self.area as f32;
+        let alpha = w[0] as f32 / area;
+        let (mut beta, mut gamma) = (w[1] as f32 / area, w[2] as f32 / area);
+        if self.swapped {
+            std::mem::swap(&mut beta, &mut gamma);
+        }
+        (alpha, beta, gamma)
+    }
+}
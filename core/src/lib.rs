@@ -0,0 +1,22 @@
+pub mod animation;
+pub mod app;
+pub mod camera;
+pub mod cli;
+pub mod color;
+pub mod controls;
+pub mod edge;
+pub mod lighting;
+pub mod mesh;
+pub mod metrics;
+pub mod obj;
+pub mod ply;
+pub mod rasterizer;
+pub mod scanline;
+pub mod scene;
+pub mod shader;
+pub mod shadow;
+pub mod stl;
+pub mod surface;
+pub mod texture;
+pub mod transform;
+pub mod triangle;
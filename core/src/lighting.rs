@@ -0,0 +1,367 @@
+use crate::color::{linear_to_srgb, srgb_to_linear, Color};
+use crate::shadow::Shadow;
+use nalgebra_glm::{vec3, Mat4, Vec3};
+use std::f32::consts::PI;
+
+/// 点光源和聚光灯的距离衰减 `1 / (constant + linear * d + quadratic * d^2)`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Attenuation {
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+}
+
+impl Default for Attenuation {
+    /// 与距离的平方成反比
+    fn default() -> Self {
+        Self {
+            constant: 0.0,
+            linear: 0.0,
+            quadratic: 1.0,
+        }
+    }
+}
+
+impl Attenuation {
+    /// 不随距离衰减
+    pub const NONE: Attenuation = Attenuation {
+        constant: 1.0,
+        linear: 0.0,
+        quadratic: 0.0,
+    };
+
+    pub fn factor(&self, distance: f32) -> f32 {
+        let denom = self.constant + self.linear * distance + self.quadratic * distance * distance;
+        if denom > 0.0 {
+            1.0 / denom
+        } else {
+            0.0
+        }
+    }
+}
+
+/// 光源，坐标和方向都在世界空间中
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Light {
+    Point {
+        position: Vec3,
+        intensity: Vec3,
+        attenuation: Attenuation,
+    },
+    Directional {
+        /// 光线传播的方向
+        direction: Vec3,
+        intensity: Vec3,
+    },
+    Spot {
+        position: Vec3,
+        /// 聚光灯的朝向
+        direction: Vec3,
+        intensity: Vec3,
+        attenuation: Attenuation,
+        /// 内锥角（半角，弧度），以内不衰减
+        inner_angle: f32,
+        /// 外锥角（半角，弧度），以外没有光照，内外锥角之间平滑过渡
+        outer_angle: f32,
+    },
+}
+
+impl Light {
+    /// 按距离平方衰减的点光源
+    pub fn point(position: Vec3, intensity: Vec3) -> Self {
+        Light::Point {
+            position,
+            intensity,
+            attenuation: Attenuation::default(),
+        }
+    }
+
+    pub fn directional(direction: Vec3, intensity: Vec3) -> Self {
+        Light::Directional {
+            direction,
+            intensity,
+        }
+    }
+
+    /// 按距离平方衰减的聚光灯
+    pub fn spot(
+        position: Vec3,
+        direction: Vec3,
+        intensity: Vec3,
+        inner_angle: f32,
+        outer_angle: f32,
+    ) -> Self {
+        Light::Spot {
+            position,
+            direction,
+            intensity,
+            attenuation: Attenuation::default(),
+            inner_angle,
+            outer_angle,
+        }
+    }
+
+    /// 用矩阵变换光源的位置和方向，例如用观察矩阵变换到观察空间
+    pub fn transform(&self, m: &Mat4) -> Light {
+        let point = |p: Vec3| (m * p.push(1.0)).xyz();
+        let vector = |d: Vec3| (m * d.push(0.0)).xyz();
+        match *self {
+            Light::Point {
+                position,
+                intensity,
+                attenuation,
+            } => Light::Point {
+                position: point(position),
+                intensity,
+                attenuation,
+            },
+            Light::Directional {
+                direction,
+                intensity,
+            } => Light::Directional {
+                direction: vector(direction),
+                intensity,
+            },
+            Light::Spot {
+                position,
+                direction,
+                intensity,
+                attenuation,
+                inner_angle,
+                outer_angle,
+            } => Light::Spot {
+                position: point(position),
+                direction: vector(direction),
+                intensity,
+                attenuation,
+                inner_angle,
+                outer_angle,
+            },
+        }
+    }
+
+    /// 照射到点 `p` 的光：指向光源的单位向量和到达该点的光强
+    pub fn incident(&self, p: Vec3) -> Option<(Vec3, Vec3)> {
+        match *self {
+            Light::Point {
+                position,
+                intensity,
+                attenuation,
+            } => {
+                let d = position - p;
+                let distance = d.norm();
+                let l = d.try_normalize(f32::EPSILON)?;
+                Some((l, intensity * attenuation.factor(distance)))
+            }
+            Light::Directional {
+                direction,
+                intensity,
+            } => Some((-direction.try_normalize(f32::EPSILON)?, intensity)),
+            Light::Spot {
+                position,
+                direction,
+                intensity,
+                attenuation,
+                inner_angle,
+                outer_angle,
+            } => {
+                let d = position - p;
+                let distance = d.norm();
+                let l = d.try_normalize(f32::EPSILON)?;
+                let axis = direction.try_normalize(f32::EPSILON)?;
+                let cos = (-l).dot(&axis);
+                let (cos_inner, cos_outer) = (inner_angle.cos(), outer_angle.cos());
+                let falloff = if cos_inner > cos_outer {
+                    smoothstep((cos - cos_outer) / (cos_inner - cos_outer))
+                } else if cos >= cos_outer {
+                    1.0
+                } else {
+                    0.0
+                };
+                Some((l, intensity * attenuation.factor(distance) * falloff))
+            }
+        }
+    }
+}
+
+fn smoothstep(x: f32) -> f32 {
+    let x = x.clamp(0.0, 1.0);
+    x * x * (3.0 - 2.0 * x)
+}
+
+/// 光照的计算频率
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum ShadingMode {
+    /// 每个三角形使用面法线，在重心处计算一次光照
+    Flat,
+    /// 在顶点处计算光照，再在三角形内插值颜色。绑定纹理时纹理颜色与插值的光照颜色相乘
+    Gouraud,
+    /// 插值法线，逐像素计算光照
+    #[default]
+    Phong,
+}
+
+/// Blinn-Phong 材质
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Material {
+    /// 环境光反射系数
+    pub ka: Vec3,
+    /// 漫反射系数，与顶点颜色或纹理颜色相乘
+    pub kd: Vec3,
+    /// 镜面反射系数
+    pub ks: Vec3,
+    /// 高光指数
+    pub shininess: f32,
+}
+
+impl Default for Material {
+    /// 与作业 3 框架中 Blinn-Phong 着色器的参数相同
+    fn default() -> Self {
+        Self {
+            ka: vec3(0.005, 0.005, 0.005),
+            kd: vec3(1.0, 1.0, 1.0),
+            ks: vec3(0.7937, 0.7937, 0.7937),
+            shininess: 150.0,
+        }
+    }
+}
+
+/// 金属度/粗糙度工作流的 PBR 材质，使用 Cook-Torrance 模型
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PbrMaterial {
+    /// 线性空间的基础颜色，与（转换到线性空间的）顶点颜色或纹理颜色相乘
+    pub base_color: Vec3,
+    /// 0 为电介质，1 为金属
+    pub metallic: f32,
+    /// 感知粗糙度，GGX 分布的 alpha 为它的平方
+    pub roughness: f32,
+    /// 电介质垂直入射时的反射率
+    pub reflectance: f32,
+    /// 环境光反射系数，与基础颜色相乘
+    pub ambient: Vec3,
+}
+
+impl Default for PbrMaterial {
+    fn default() -> Self {
+        Self {
+            base_color: vec3(1.0, 1.0, 1.0),
+            metallic: 0.0,
+            roughness: 0.5,
+            reflectance: 0.04,
+            ambient: vec3(0.005, 0.005, 0.005),
+        }
+    }
+}
+
+impl PbrMaterial {
+    /// 光线方向 `l`、视线方向 `v` 都是单位向量时的 BRDF 乘以 `n·l`
+    fn reflect(&self, albedo: Vec3, n: Vec3, v: Vec3, l: Vec3) -> Vec3 {
+        let n_dot_l = n.dot(&l).max(0.0);
+        let n_dot_v = n.dot(&v).max(1e-4);
+        let h = (l + v).try_normalize(f32::EPSILON).unwrap_or(n);
+        let n_dot_h = n.dot(&h).max(0.0);
+        let v_dot_h = v.dot(&h).max(0.0);
+        let alpha = (self.roughness * self.roughness).max(1e-3);
+        let alpha2 = alpha * alpha;
+
+        // GGX 法线分布
+        let d = n_dot_h * n_dot_h * (alpha2 - 1.0) + 1.0;
+        let distribution = alpha2 / (PI * d * d);
+        // Smith 遮蔽函数，光线和视线方向分别计算
+        let g1 = |x: f32| 2.0 * x / (x + (alpha2 + (1.0 - alpha2) * x * x).sqrt());
+        let geometry = g1(n_dot_l) * g1(n_dot_v);
+        // Schlick 近似的菲涅尔项，金属的 F0 是基础颜色
+        let f0 = Vec3::repeat(self.reflectance).lerp(&albedo, self.metallic);
+        let fresnel = f0 + (Vec3::repeat(1.0) - f0) * (1.0 - v_dot_h).powi(5);
+
+        let specular = fresnel * (distribution * geometry / (4.0 * n_dot_l.max(1e-4) * n_dot_v));
+        // 被镜面反射的能量不再参与漫反射，金属没有漫反射
+        let kd = (Vec3::repeat(1.0) - fresnel) * (1.0 - self.metallic);
+        let diffuse = kd.component_mul(&albedo) / PI;
+        (diffuse + specular) * n_dot_l
+    }
+}
+
+/// 一次 draw 使用的光照，光源都已变换到观察空间，观察点在原点
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Lighting {
+    pub material: Material,
+    pub lights: Vec<Light>,
+    /// 环境光强度
+    pub ambient: Vec3,
+    /// 与 `lights` 一一对应的阴影贴图，缺少或为 `None` 的光源没有阴影
+    pub shadows: Vec<Option<Shadow>>,
+    /// 设置后使用 Cook-Torrance 模型代替 Blinn-Phong，`material` 不再起作用
+    pub pbr: Option<PbrMaterial>,
+}
+
+impl Lighting {
+    /// 计算观察空间中点 `p`、法线 `n` 处的光照，`base` 是物体本身的颜色。
+    ///
+    /// 法线不需要是单位向量，长度为零时不计算光照，直接返回 `base`。
+    pub fn shade(&self, p: Vec3, n: Vec3, base: Color) -> Color {
+        let Some(n) = n.try_normalize(f32::EPSILON) else {
+            return base;
+        };
+        let albedo = vec3(base.r as f32, base.g as f32, base.b as f32) / 255.0;
+        let v = (-p).try_normalize(f32::EPSILON).unwrap_or(n);
+        let result = match &self.pbr {
+            Some(pbr) => self.cook_torrance(pbr, p, n, v, albedo),
+            None => self.blinn_phong(p, n, v, albedo),
+        };
+
+        let channel = |x: f32| (x * 255.0).round().clamp(0.0, 255.0) as u8;
+        Color::new_rgba(
+            channel(result.x),
+            channel(result.y),
+            channel(result.z),
+            base.a,
+        )
+    }
+
+    /// 照射到 `p` 处并且在法线正面的光：指向光源的单位向量、乘以阴影可见度之后的光强
+    fn incident(&self, p: Vec3, n: Vec3) -> impl Iterator<Item = (Vec3, Vec3)> + '_ {
+        self.lights
+            .iter()
+            .enumerate()
+            .filter_map(move |(i, light)| {
+                let (l, radiance) = light.incident(p)?;
+                let cos = n.dot(&l);
+                if cos <= 0.0 {
+                    return None;
+                }
+                let visibility = self
+                    .shadows
+                    .get(i)
+                    .and_then(Option::as_ref)
+                    .map_or(1.0, |shadow| shadow.visibility(p, cos));
+                (visibility > 0.0).then(|| (l, radiance * visibility))
+            })
+    }
+
+    /// 直接在 sRGB 颜色上计算的 Blinn-Phong 光照
+    fn blinn_phong(&self, p: Vec3, n: Vec3, v: Vec3, albedo: Vec3) -> Vec3 {
+        let kd = self.material.kd.component_mul(&albedo);
+        let mut result = self.material.ka.component_mul(&self.ambient);
+        for (l, radiance) in self.incident(p, n) {
+            let diffuse = n.dot(&l);
+            let h = (l + v).try_normalize(f32::EPSILON).unwrap_or(n);
+            let specular = n.dot(&h).max(0.0).powf(self.material.shininess);
+            result += (kd * diffuse + self.material.ks * specular).component_mul(&radiance);
+        }
+        result
+    }
+
+    /// 在线性空间中累加各个光源的 Cook-Torrance 光照，最后转换回 sRGB
+    fn cook_torrance(&self, pbr: &PbrMaterial, p: Vec3, n: Vec3, v: Vec3, albedo: Vec3) -> Vec3 {
+        let albedo = srgb_to_linear(albedo).component_mul(&pbr.base_color);
+        let mut result = pbr
+            .ambient
+            .component_mul(&self.ambient)
+            .component_mul(&albedo);
+        for (l, radiance) in self.incident(p, n) {
+            result += pbr.reflect(albedo, n, v, l).component_mul(&radiance);
+        }
+        linear_to_srgb(result.map(|x| x.clamp(0.0, 1.0)))
+    }
+}
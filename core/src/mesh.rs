@@ -0,0 +1,213 @@
+use crate::color::Color;
+use crate::rasterizer::{ColBufId, IndBufId, NormBufId, PosBufId, Primitive, Rasterizer, TexBufId};
+use nalgebra_glm::{Vec2, Vec3};
+use std::fmt;
+use std::ops::Range;
+use std::path::PathBuf;
+
+/// 读取 PLY、STL 等网格文件时的错误
+#[derive(Debug)]
+pub enum MeshError {
+    Io(std::io::Error),
+    /// 文件内容不符合格式
+    Format(String),
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeshError::Io(e) => write!(f, "{}", e),
+            MeshError::Format(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for MeshError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MeshError::Io(e) => Some(e),
+            MeshError::Format(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for MeshError {
+    fn from(e: std::io::Error) -> Self {
+        MeshError::Io(e)
+    }
+}
+
+/// 三角形网格，所有顶点属性共用一套索引。
+///
+/// `normals`、`tex_coords` 为空表示网格没有这项属性，否则长度与 `positions` 相同。
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Mesh {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    pub tex_coords: Vec<Vec2>,
+    pub colors: Vec<Color>,
+    pub indices: Vec<[u32; 3]>,
+    /// 按三角形区间划分的分组，每组使用一种材质
+    pub groups: Vec<Group>,
+    pub materials: Vec<Material>,
+    /// 读取时跳过的内容，例如找不到的材质库，由调用者决定是否提示
+    pub warnings: Vec<String>,
+}
+
+/// 网格中连续的一段三角形
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Group {
+    pub name: String,
+    /// `Mesh::materials` 中的下标
+    pub material: Option<usize>,
+    /// `Mesh::indices` 中的下标范围
+    pub triangles: Range<usize>,
+}
+
+/// MTL 格式描述的材质
+#[derive(Debug, Clone, PartialEq)]
+pub struct Material {
+    pub name: String,
+    /// Ka
+    pub ambient: Vec3,
+    /// Kd
+    pub diffuse: Vec3,
+    /// Ks
+    pub specular: Vec3,
+    /// Ns
+    pub shininess: f32,
+    /// d，1 为不透明
+    pub opacity: f32,
+    /// map_Kd
+    pub diffuse_texture: Option<PathBuf>,
+    /// map_Bump、bump 或 norm
+    pub bump_texture: Option<PathBuf>,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            ambient: Vec3::zeros(),
+            diffuse: Vec3::new(0.8, 0.8, 0.8),
+            specular: Vec3::zeros(),
+            shininess: 1.0,
+            opacity: 1.0,
+            diffuse_texture: None,
+            bump_texture: None,
+        }
+    }
+}
+
+/// 网格上传到 [`Rasterizer`] 之后的各个缓冲区
+#[derive(Debug, Clone)]
+pub struct MeshBuffers {
+    pub positions: PosBufId,
+    pub indices: IndBufId,
+    pub colors: ColBufId,
+    pub normals: Option<NormBufId>,
+    pub tex_coords: Option<TexBufId>,
+    /// 与 `Mesh::groups` 一一对应的索引缓冲区，用于按材质分组绘制
+    pub groups: Vec<IndBufId>,
+}
+
+impl Mesh {
+    /// 按面积加权平均相邻三角形的法线，重新计算每个顶点的法线
+    pub fn compute_normals(&mut self) {
+        let mut normals = vec![Vec3::zeros(); self.positions.len()];
+        for &[a, b, c] in &self.indices {
+            let (a, b, c) = (a as usize, b as usize, c as usize);
+            // 叉积的长度是面积的两倍，直接累加即为面积加权
+            let n = (self.positions[b] - self.positions[a])
+                .cross(&(self.positions[c] - self.positions[a]));
+            normals[a] += n;
+            normals[b] += n;
+            normals[c] += n;
+        }
+        for n in &mut normals {
+            *n = n.try_normalize(f32::EPSILON).unwrap_or_else(Vec3::zeros);
+        }
+        self.normals = normals;
+    }
+
+    /// 所有顶点的包围盒 `(min, max)`，没有顶点时返回 `None`
+    pub fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        let first = *self.positions.first()?;
+        Some(
+            self.positions
+                .iter()
+                .fold((first, first), |(min, max), p| (min.inf(p), max.sup(p))),
+        )
+    }
+
+    /// 第 `group` 组三角形的索引，格式与 [`Rasterizer::load_indices`] 相同
+    pub fn group_indices(&self, group: usize) -> Vec<Vec3> {
+        self.indices[self.groups[group].triangles.clone()]
+            .iter()
+            .map(|&[a, b, c]| Vec3::new(a as f32, b as f32, c as f32))
+            .collect()
+    }
+}
+
+impl Rasterizer {
+    /// 把网格的各项顶点属性和索引上传为缓冲区。
+    ///
+    /// 没有顶点颜色的网格按所在分组材质的漫反射颜色着色，没有材质时为白色。
+    pub fn load_mesh(&mut self, mesh: &Mesh) -> MeshBuffers {
+        let colors = if mesh.colors.len() == mesh.positions.len() {
+            mesh.colors.clone()
+        } else {
+            let mut colors = vec![Color::WHITE; mesh.positions.len()];
+            for group in &mesh.groups {
+                let Some(material) = group.material.map(|m| &mesh.materials[m]) else {
+                    continue;
+                };
+                let color = to_color(material.diffuse);
+                for &triangle in &mesh.indices[group.triangles.clone()] {
+                    for i in triangle {
+                        colors[i as usize] = color;
+                    }
+                }
+            }
+            colors
+        };
+        let indices = mesh
+            .indices
+            .iter()
+            .map(|&[a, b, c]| Vec3::new(a as f32, b as f32, c as f32))
+            .collect();
+
+        MeshBuffers {
+            positions: self.load_positions(mesh.positions.clone()),
+            indices: self.load_indices(indices),
+            colors: self.load_colors(colors),
+            normals: (!mesh.normals.is_empty()).then(|| self.load_normals(mesh.normals.clone())),
+            tex_coords: (!mesh.tex_coords.is_empty())
+                .then(|| self.load_tex_coords(mesh.tex_coords.clone())),
+            groups: (0..mesh.groups.len())
+                .map(|i| self.load_indices(mesh.group_indices(i)))
+                .collect(),
+        }
+    }
+
+    /// 绑定网格的法线和纹理坐标后绘制整个网格，绘制之后恢复原来的绑定
+    pub fn draw_mesh(&mut self, buffers: &MeshBuffers) {
+        let (normals, tex_coords) = (self.bound_normals(), self.bound_tex_coords());
+        self.bind_normals(buffers.normals);
+        self.bind_tex_coords(buffers.tex_coords);
+        self.draw(
+            buffers.positions,
+            buffers.indices,
+            buffers.colors,
+            Primitive::Triangle,
+        );
+        self.bind_normals(normals);
+        self.bind_tex_coords(tex_coords);
+    }
+}
+
+/// 把 `[0, 1]` 范围的颜色转换为 [`Color`]
+pub(crate) fn to_color(c: Vec3) -> Color {
+    let channel = |x: f32| (x * 255.0).round().clamp(0.0, 255.0) as u8;
+    Color::new_rgb(channel(c.x), channel(c.y), channel(c.z))
+}
@@ -0,0 +1,417 @@
+//! 比较两张图像的误差指标：MSE/PSNR、SSIM 和近似 [FLIP](https://research.nvidia.com/publication/2020-07_flip-difference-evaluator-alternating-images)
+//! 的感知误差，以及把逐像素误差画成热力图。
+//!
+//! 图像按行从上到下存储，与 [`Rasterizer::framebuffer`](crate::rasterizer::Rasterizer::framebuffer) 相同，
+//! 只比较 RGB 分量。两张图像的大小必须相同。
+
+use crate::color::{srgb_to_linear, Color};
+use nalgebra_glm::{mat3, vec3, Mat3, Vec3};
+use std::fmt;
+use std::path::Path;
+
+/// 默认的每度像素数，对应在 0.7 米外观看 24 英寸的 4K 显示器，与 FLIP 的默认值相同
+pub const DEFAULT_PPD: f32 = 67.0;
+
+/// 两张图像间的各项指标
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Metrics {
+    pub mse: f64,
+    /// 以分贝为单位，图像相同时为无穷大
+    pub psnr: f64,
+    pub ssim: f64,
+    /// 见 [`flip`]，[`compare`] 按 [`DEFAULT_PPD`] 计算
+    pub flip: f64,
+}
+
+impl fmt::Display for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "MSE   {:.4}", self.mse)?;
+        writeln!(f, "PSNR  {:.2} dB", self.psnr)?;
+        writeln!(f, "SSIM  {:.5}", self.ssim)?;
+        write!(f, "FLIP  {:.5}", self.flip)
+    }
+}
+
+/// 计算所有指标
+pub fn compare(a: &[Color], b: &[Color], width: u32, height: u32) -> Metrics {
+    Metrics {
+        mse: mse(a, b),
+        psnr: psnr(a, b),
+        ssim: ssim(a, b, width, height),
+        flip: flip(a, b, width, height, DEFAULT_PPD),
+    }
+}
+
+/// 每个像素的误差，按行从上到下存储
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorMap {
+    pub width: u32,
+    pub height: u32,
+    pub values: Vec<f32>,
+}
+
+impl ErrorMap {
+    pub fn mean(&self) -> f64 {
+        self.values.iter().map(|&v| v as f64).sum::<f64>() / self.values.len().max(1) as f64
+    }
+
+    pub fn max(&self) -> f32 {
+        self.values.iter().copied().fold(0.0, f32::max)
+    }
+
+    /// 用 magma 色表把 [0, 1] 的误差映射为颜色，0 为黑色，1 为浅黄色
+    pub fn heat_map(&self) -> Vec<Color> {
+        self.values.iter().map(|&v| magma(v)).collect()
+    }
+
+    pub fn save_heat_map(&self, path: impl AsRef<Path>) -> image::ImageResult<()> {
+        image::save_buffer(
+            path,
+            bytemuck::cast_slice(&self.heat_map()),
+            self.width,
+            self.height,
+            image::ExtendedColorType::Rgba8,
+        )
+    }
+}
+
+/// 读取图像文件，返回宽、高和像素
+pub fn load_image(path: impl AsRef<Path>) -> image::ImageResult<(u32, u32, Vec<Color>)> {
+    let image = image::open(path)?.to_rgba8();
+    let (width, height) = image.dimensions();
+    Ok((width, height, bytemuck::cast_vec(image.into_raw())))
+}
+
+/// 均方误差，颜色分量的范围是 [0, 255]
+pub fn mse(a: &[Color], b: &[Color]) -> f64 {
+    assert_eq!(a.len(), b.len(), "images have different sizes");
+    let sum: f64 = a
+        .iter()
+        .zip(b)
+        .map(|(a, b)| {
+            let d = |x: u8, y: u8| (x as f64 - y as f64).powi(2);
+            d(a.r, b.r) + d(a.g, b.g) + d(a.b, b.b)
+        })
+        .sum();
+    sum / (3 * a.len().max(1)) as f64
+}
+
+/// 峰值信噪比 `10 log10(255^2 / MSE)`
+pub fn psnr(a: &[Color], b: &[Color]) -> f64 {
+    let mse = mse(a, b);
+    if mse == 0.0 {
+        f64::INFINITY
+    } else {
+        10.0 * (255.0 * 255.0 / mse).log10()
+    }
+}
+
+/// 每个像素的最大分量差，除以 255
+pub fn difference_map(a: &[Color], b: &[Color], width: u32, height: u32) -> ErrorMap {
+    check_size(a, b, width, height);
+    let values = a
+        .iter()
+        .zip(b)
+        .map(|(a, b)| {
+            a.r.abs_diff(b.r)
+                .max(a.g.abs_diff(b.g))
+                .max(a.b.abs_diff(b.b)) as f32
+                / 255.0
+        })
+        .collect();
+    ErrorMap {
+        width,
+        height,
+        values,
+    }
+}
+
+/// 平均结构相似度，范围是 [-1, 1]，图像相同时为 1
+pub fn ssim(a: &[Color], b: &[Color], width: u32, height: u32) -> f64 {
+    let values = ssim_values(a, b, width, height);
+    values.iter().map(|&v| v as f64).sum::<f64>() / values.len().max(1) as f64
+}
+
+/// 每个像素的 `1 - SSIM`，截断到 [0, 1]
+pub fn ssim_map(a: &[Color], b: &[Color], width: u32, height: u32) -> ErrorMap {
+    let values = ssim_values(a, b, width, height)
+        .into_iter()
+        .map(|s| (1.0 - s).clamp(0.0, 1.0))
+        .collect();
+    ErrorMap {
+        width,
+        height,
+        values,
+    }
+}
+
+/// 按 Wang 等人的定义在亮度上计算每个像素的 SSIM，窗口是 σ = 1.5 的高斯函数
+fn ssim_values(a: &[Color], b: &[Color], width: u32, height: u32) -> Vec<f32> {
+    check_size(a, b, width, height);
+    const C1: f32 = (0.01 * 255.0) * (0.01 * 255.0);
+    const C2: f32 = (0.03 * 255.0) * (0.03 * 255.0);
+    let luma = |c: &Color| 0.299 * c.r as f32 + 0.587 * c.g as f32 + 0.114 * c.b as f32;
+    let x: Vec<f32> = a.iter().map(luma).collect();
+    let y: Vec<f32> = b.iter().map(luma).collect();
+    let product =
+        |p: &[f32], q: &[f32]| -> Vec<f32> { p.iter().zip(q).map(|(p, q)| p * q).collect() };
+
+    let kernel = gaussian(1.5);
+    let blur = |v: &[f32]| convolve(v, width, height, &kernel, &kernel);
+    let (mu_x, mu_y) = (blur(&x), blur(&y));
+    let (xx, yy, xy) = (
+        blur(&product(&x, &x)),
+        blur(&product(&y, &y)),
+        blur(&product(&x, &y)),
+    );
+    (0..x.len())
+        .map(|i| {
+            let (mx, my) = (mu_x[i], mu_y[i]);
+            let var_x = xx[i] - mx * mx;
+            let var_y = yy[i] - my * my;
+            let cov = xy[i] - mx * my;
+            ((2.0 * mx * my + C1) * (2.0 * cov + C2))
+                / ((mx * mx + my * my + C1) * (var_x + var_y + C2))
+        })
+        .collect()
+}
+
+/// 平均感知误差，范围是 [0, 1]，见 [`flip_map`]
+pub fn flip(a: &[Color], b: &[Color], width: u32, height: u32, ppd: f32) -> f64 {
+    flip_map(a, b, width, height, ppd).mean()
+}
+
+/// 简化的 FLIP 感知误差，`ppd` 是观看时每度视角的像素数。
+///
+/// 颜色误差：在 YyCxCz 空间中用近似人眼对比敏感度的高斯函数模糊两张图像，再计算
+/// Hunt 调整后 L\*a\*b\* 颜色的 HyAB 距离。特征误差：比较亮度的边缘和点的强度。
+/// 两者按 FLIP 的方式合成为 `color^(1 - feature)`。与 FLIP 的区别是对比敏感度只用了单个高斯函数。
+pub fn flip_map(a: &[Color], b: &[Color], width: u32, height: u32, ppd: f32) -> ErrorMap {
+    check_size(a, b, width, height);
+    let (ycc_a, ycc_b) = (to_ycxcz(a), to_ycxcz(b));
+
+    // 颜色误差
+    let filtered_a = filter_ycxcz(&ycc_a, width, height, ppd);
+    let filtered_b = filter_ycxcz(&ycc_b, width, height, ppd);
+    let max_distance = hyab(hunt(GREEN_LAB), hunt(BLUE_LAB)).powf(COLOR_EXPONENT);
+    let color_error = filtered_a.iter().zip(&filtered_b).map(|(a, b)| {
+        let distance = hyab(hunt(ycxcz_to_lab(*a)), hunt(ycxcz_to_lab(*b))).powf(COLOR_EXPONENT);
+        // 把小误差压缩到 [0, 0.95]，大误差压缩到 [0.95, 1]
+        let knee = 0.4 * max_distance;
+        if distance < knee {
+            0.95 * distance / knee
+        } else {
+            (0.95 + 0.05 * (distance - knee) / (max_distance - knee)).min(1.0)
+        }
+    });
+
+    // 特征误差
+    let (edges_a, points_a) = features(&ycc_a, width, height, ppd);
+    let (edges_b, points_b) = features(&ycc_b, width, height, ppd);
+    let feature_error = (0..a.len()).map(|i| {
+        let difference = (edges_a[i] - edges_b[i])
+            .abs()
+            .max((points_a[i] - points_b[i]).abs());
+        (difference / std::f32::consts::SQRT_2).powf(0.5)
+    });
+
+    let values = color_error
+        .zip(feature_error)
+        .map(|(color, feature)| color.powf(1.0 - feature))
+        .collect();
+    ErrorMap {
+        width,
+        height,
+        values,
+    }
+}
+
+fn check_size(a: &[Color], b: &[Color], width: u32, height: u32) {
+    let size = (width * height) as usize;
+    assert!(
+        a.len() == size && b.len() == size,
+        "expected two {}x{} images, got {} and {} pixels",
+        width,
+        height,
+        a.len(),
+        b.len()
+    );
+}
+
+/// 归一化的一维高斯函数，半径为 3σ
+fn gaussian(sigma: f32) -> Vec<f32> {
+    let radius = (3.0 * sigma).ceil().max(1.0) as i32;
+    let weights: Vec<f32> = (-radius..=radius)
+        .map(|x| (-(x * x) as f32 / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let sum: f32 = weights.iter().sum();
+    weights.into_iter().map(|w| w / sum).collect()
+}
+
+/// 先在水平方向用 `horizontal`、再在竖直方向用 `vertical` 做卷积，边界外取最近的像素
+fn convolve(
+    values: &[f32],
+    width: u32,
+    height: u32,
+    horizontal: &[f32],
+    vertical: &[f32],
+) -> Vec<f32> {
+    let (w, h) = (width as i32, height as i32);
+    let pass = |input: &[f32], kernel: &[f32], step: (i32, i32)| -> Vec<f32> {
+        let radius = (kernel.len() / 2) as i32;
+        let mut output = vec![0.0; input.len()];
+        for y in 0..h {
+            for x in 0..w {
+                output[(y * w + x) as usize] = kernel
+                    .iter()
+                    .enumerate()
+                    .map(|(k, weight)| {
+                        let offset = k as i32 - radius;
+                        let sx = (x + offset * step.0).clamp(0, w - 1);
+                        let sy = (y + offset * step.1).clamp(0, h - 1);
+                        weight * input[(sy * w + sx) as usize]
+                    })
+                    .sum();
+            }
+        }
+        output
+    };
+    pass(&pass(values, horizontal, (1, 0)), vertical, (0, 1))
+}
+
+/// 线性 RGB 到 CIE XYZ（D65）
+const RGB_TO_XYZ: [[f32; 3]; 3] = [
+    [0.412_456_4, 0.357_576_1, 0.180_437_5],
+    [0.212_672_9, 0.715_152_2, 0.072_175],
+    [0.019_333_9, 0.119_192, 0.950_304_1],
+];
+const XYZ_TO_RGB: [[f32; 3]; 3] = [
+    [3.240_454_2, -1.537_138_5, -0.498_531_4],
+    [-0.969_266, 1.876_010_8, 0.041_556],
+    [0.055_643_4, -0.204_025_9, 1.057_225_2],
+];
+const WHITE_POINT: [f32; 3] = [0.950_47, 1.0, 1.088_83];
+/// 纯绿色和纯蓝色的 L*a*b*，两者的 HyAB 距离是最大的颜色误差
+const GREEN_LAB: [f32; 3] = [87.735, -86.183, 83.179];
+const BLUE_LAB: [f32; 3] = [32.303, 79.197, -107.864];
+const COLOR_EXPONENT: f32 = 0.7;
+
+/// 按行给出的矩阵
+fn matrix(m: [[f32; 3]; 3]) -> Mat3 {
+    mat3(
+        m[0][0], m[0][1], m[0][2], m[1][0], m[1][1], m[1][2], m[2][0], m[2][1], m[2][2],
+    )
+}
+
+/// sRGB 颜色转换到线性化的 L*a*b* 空间 YyCxCz
+fn to_ycxcz(colors: &[Color]) -> Vec<Vec3> {
+    let m = matrix(RGB_TO_XYZ);
+    colors
+        .iter()
+        .map(|c| {
+            let rgb = vec3(c.r as f32, c.g as f32, c.b as f32) / 255.0;
+            let xyz = (m * srgb_to_linear(rgb)).component_div(&Vec3::from(WHITE_POINT));
+            vec3(
+                116.0 * xyz.y - 16.0,
+                500.0 * (xyz.x - xyz.y),
+                200.0 * (xyz.y - xyz.z),
+            )
+        })
+        .collect()
+}
+
+/// 近似人眼对比敏感度的低通滤波。亮度和红绿通道的高斯函数较窄，蓝黄通道较宽
+fn filter_ycxcz(ycc: &[Vec3], width: u32, height: u32, ppd: f32) -> Vec<Vec3> {
+    // 空间域中 exp(-π²x²/b) 的标准差为 sqrt(b / 2π²) 度
+    let sigmas =
+        [0.0047f32, 0.0053, 0.04].map(|b| (b / (2.0 * std::f32::consts::PI.powi(2))).sqrt() * ppd);
+    let mut channels = sigmas.iter().enumerate().map(|(i, &sigma)| {
+        let channel: Vec<f32> = ycc.iter().map(|c| c[i]).collect();
+        let kernel = gaussian(sigma);
+        convolve(&channel, width, height, &kernel, &kernel)
+    });
+    let (y, cx, cz) = (
+        channels.next().unwrap(),
+        channels.next().unwrap(),
+        channels.next().unwrap(),
+    );
+    (0..ycc.len()).map(|i| vec3(y[i], cx[i], cz[i])).collect()
+}
+
+fn ycxcz_to_lab(ycc: Vec3) -> Vec3 {
+    let y = (ycc.x + 16.0) / 116.0;
+    let xyz = vec3(y + ycc.y / 500.0, y, y - ycc.z / 200.0).component_mul(&Vec3::from(WHITE_POINT));
+    // 模糊后可能超出色域，截断到线性 RGB 的范围内
+    let rgb = (matrix(XYZ_TO_RGB) * xyz).map(|c| c.clamp(0.0, 1.0));
+    let xyz = (matrix(RGB_TO_XYZ) * rgb).component_div(&Vec3::from(WHITE_POINT));
+    let f = xyz.map(|t| {
+        if t > (6.0f32 / 29.0).powi(3) {
+            t.cbrt()
+        } else {
+            t / (3.0 * (6.0f32 / 29.0).powi(2)) + 4.0 / 29.0
+        }
+    });
+    vec3(116.0 * f.y - 16.0, 500.0 * (f.x - f.y), 200.0 * (f.y - f.z))
+}
+
+/// Hunt 效应：亮度越低，色度越不明显
+fn hunt(lab: impl Into<Vec3>) -> Vec3 {
+    let lab = lab.into();
+    vec3(lab.x, 0.01 * lab.x * lab.y, 0.01 * lab.x * lab.z)
+}
+
+/// 亮度差的绝对值加上色度差的欧氏距离
+fn hyab(a: Vec3, b: Vec3) -> f32 {
+    (a.x - b.x).abs() + (a.yz() - b.yz()).norm()
+}
+
+/// 亮度的边缘强度和点强度，用高斯函数的一阶和二阶导数检测
+fn features(ycc: &[Vec3], width: u32, height: u32, ppd: f32) -> (Vec<f32>, Vec<f32>) {
+    let luminance: Vec<f32> = ycc.iter().map(|c| (c.x + 16.0) / 116.0).collect();
+    let sigma = 0.5 * 0.082 * ppd;
+    let smooth = gaussian(sigma);
+    let radius = (smooth.len() / 2) as i32;
+    // 正负部分分别归一化为 1 和 -1
+    let normalize = |kernel: Vec<f32>| -> Vec<f32> {
+        let positive: f32 = kernel.iter().filter(|&&w| w > 0.0).sum();
+        let negative: f32 = -kernel.iter().filter(|&&w| w < 0.0).sum::<f32>();
+        kernel
+            .into_iter()
+            .map(|w| if w > 0.0 { w / positive } else { w / negative })
+            .collect()
+    };
+    let edge = normalize(
+        (-radius..=radius)
+            .zip(&smooth)
+            .map(|(x, w)| -x as f32 * w)
+            .collect(),
+    );
+    let point = normalize(
+        (-radius..=radius)
+            .zip(&smooth)
+            .map(|(x, w)| ((x * x) as f32 / (sigma * sigma) - 1.0) * w)
+            .collect(),
+    );
+    let magnitude = |kernel: &[f32]| -> Vec<f32> {
+        let dx = convolve(&luminance, width, height, kernel, &smooth);
+        let dy = convolve(&luminance, width, height, &smooth, kernel);
+        dx.iter().zip(&dy).map(|(x, y)| x.hypot(*y)).collect()
+    };
+    (magnitude(&edge), magnitude(&point))
+}
+
+/// matplotlib 的 magma 色表，在几个控制点之间线性插值
+fn magma(t: f32) -> Color {
+    const STOPS: [[f32; 3]; 5] = [
+        [0.0, 0.0, 4.0],
+        [81.0, 18.0, 124.0],
+        [183.0, 55.0, 121.0],
+        [252.0, 137.0, 97.0],
+        [252.0, 253.0, 191.0],
+    ];
+    let t = if t.is_nan() { 1.0 } else { t.clamp(0.0, 1.0) } * (STOPS.len() - 1) as f32;
+    let i = (t as usize).min(STOPS.len() - 2);
+    let f = t - i as f32;
+    let channel = |c: usize| (STOPS[i][c] + (STOPS[i + 1][c] - STOPS[i][c]) * f).round() as u8;
+    Color::new_rgb(channel(0), channel(1), channel(2))
+}
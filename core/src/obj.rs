@@ -0,0 +1,435 @@
+use crate::mesh::{to_color, Group, Material, Mesh};
+use nalgebra_glm::{vec2, vec3, Vec2, Vec3};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+/// 读取 OBJ、MTL 文件时的错误
+#[derive(Debug)]
+pub enum ObjError {
+    Io(std::io::Error),
+    /// 第 `line` 行（从 1 开始）无法解析
+    Parse {
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjError::Io(e) => write!(f, "{}", e),
+            ObjError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ObjError::Io(e) => Some(e),
+            ObjError::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ObjError {
+    fn from(e: std::io::Error) -> Self {
+        ObjError::Io(e)
+    }
+}
+
+fn parse_error(line: usize, message: impl Into<String>) -> ObjError {
+    ObjError::Parse {
+        line,
+        message: message.into(),
+    }
+}
+
+/// 读取 OBJ 文件，`mtllib` 引用的材质库相对 OBJ 文件所在的目录查找。
+///
+/// 找不到的材质库记录在 `Mesh::warnings` 中，引用其中材质的分组使用默认材质。
+pub fn load(path: impl AsRef<Path>) -> Result<Mesh, ObjError> {
+    let path = path.as_ref();
+    let source = std::fs::read_to_string(path)?;
+    Parser::new(Some(path.parent().unwrap_or(Path::new("")))).parse(&source)
+}
+
+/// 解析 OBJ 文本，忽略 `mtllib`，`usemtl` 引用的材质使用默认值并保留名字
+pub fn parse(source: &str) -> Result<Mesh, ObjError> {
+    Parser::new(None).parse(source)
+}
+
+/// 读取 MTL 文件，贴图路径相对 MTL 文件所在的目录
+pub fn load_mtl(path: impl AsRef<Path>) -> Result<Vec<Material>, ObjError> {
+    let path = path.as_ref();
+    let source = std::fs::read_to_string(path)?;
+    parse_mtl(&source, path.parent().unwrap_or(Path::new("")))
+}
+
+/// 解析 MTL 文本，贴图路径相对 `base_dir`
+pub fn parse_mtl(source: &str, base_dir: &Path) -> Result<Vec<Material>, ObjError> {
+    let mut materials: Vec<Material> = vec![];
+    for (number, line) in lines(source) {
+        let mut tokens = line.split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+        let args: Vec<&str> = tokens.collect();
+        if keyword == "newmtl" {
+            materials.push(Material {
+                name: args.join(" "),
+                ..Default::default()
+            });
+            continue;
+        }
+        let Some(material) = materials.last_mut() else {
+            // newmtl 之前的语句不属于任何材质
+            continue;
+        };
+        let texture = || {
+            // 贴图选项（如 -bm 1.0）在文件名之前，取最后一项作为文件名
+            args.last()
+                .map(|name| base_dir.join(name))
+                .ok_or_else(|| parse_error(number, "missing texture file name"))
+        };
+        match keyword {
+            "Ka" => material.ambient = parse_color(&args, number)?,
+            "Kd" => material.diffuse = parse_color(&args, number)?,
+            "Ks" => material.specular = parse_color(&args, number)?,
+            "Ns" => material.shininess = parse_floats::<1>(&args, number)?[0],
+            "d" => material.opacity = parse_floats::<1>(&args, number)?[0],
+            "Tr" => material.opacity = 1.0 - parse_floats::<1>(&args, number)?[0],
+            "map_Kd" => material.diffuse_texture = Some(texture()?),
+            "map_Bump" | "map_bump" | "bump" | "norm" => material.bump_texture = Some(texture()?),
+            _ => {}
+        }
+    }
+    Ok(materials)
+}
+
+/// 去掉注释和首尾空白后的非空行，以及从 1 开始的行号
+fn lines(source: &str) -> impl Iterator<Item = (usize, &str)> {
+    source.lines().enumerate().filter_map(|(i, line)| {
+        let line = line.split('#').next().unwrap_or("").trim();
+        (!line.is_empty()).then_some((i + 1, line))
+    })
+}
+
+fn parse_floats<const N: usize>(args: &[&str], line: usize) -> Result<[f32; N], ObjError> {
+    if args.len() < N {
+        return Err(parse_error(
+            line,
+            format!("expected {} numbers, found {}", N, args.len()),
+        ));
+    }
+    let mut values = [0.0; N];
+    for (value, arg) in values.iter_mut().zip(args) {
+        *value = arg
+            .parse()
+            .map_err(|_| parse_error(line, format!("invalid number {:?}", arg)))?;
+    }
+    Ok(values)
+}
+
+/// `r g b` 或只有一个分量的灰度
+fn parse_color(args: &[&str], line: usize) -> Result<Vec3, ObjError> {
+    if args.len() == 1 {
+        let [gray] = parse_floats::<1>(args, line)?;
+        return Ok(vec3(gray, gray, gray));
+    }
+    let [r, g, b] = parse_floats::<3>(args, line)?;
+    Ok(vec3(r, g, b))
+}
+
+/// 面中一个顶点引用的位置、纹理坐标和法线的下标
+type VertexKey = (usize, Option<usize>, Option<usize>);
+
+struct Parser<'a> {
+    /// 查找材质库的目录，为 `None` 时忽略 `mtllib`
+    base_dir: Option<&'a Path>,
+    positions: Vec<Vec3>,
+    /// `v x y z r g b` 扩展格式中的顶点颜色
+    colors: Vec<Option<Vec3>>,
+    tex_coords: Vec<Vec2>,
+    normals: Vec<Vec3>,
+    /// 已经输出的顶点，同一材质下相同的 `v/vt/vn` 组合只输出一次。
+    /// 不同材质的分组不共享顶点，这样顶点颜色可以取各自材质的颜色
+    vertices: HashMap<(VertexKey, Option<usize>), u32>,
+    keys: Vec<VertexKey>,
+    mesh: Mesh,
+    group_name: String,
+    group_start: usize,
+    material: Option<usize>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(base_dir: Option<&'a Path>) -> Self {
+        Self {
+            base_dir,
+            positions: vec![],
+            colors: vec![],
+            tex_coords: vec![],
+            normals: vec![],
+            vertices: HashMap::new(),
+            keys: vec![],
+            mesh: Mesh::default(),
+            group_name: String::new(),
+            group_start: 0,
+            material: None,
+        }
+    }
+
+    fn parse(mut self, source: &str) -> Result<Mesh, ObjError> {
+        for (number, line) in lines(source) {
+            let mut tokens = line.split_whitespace();
+            let Some(keyword) = tokens.next() else {
+                continue;
+            };
+            let args: Vec<&str> = tokens.collect();
+            match keyword {
+                "v" => {
+                    let [x, y, z] = parse_floats::<3>(&args, number)?;
+                    self.positions.push(vec3(x, y, z));
+                    let color = if args.len() >= 6 {
+                        let [r, g, b] = parse_floats::<3>(&args[3..], number)?;
+                        Some(vec3(r, g, b))
+                    } else {
+                        None
+                    };
+                    self.colors.push(color);
+                }
+                "vt" => {
+                    let [u] = parse_floats::<1>(&args, number)?;
+                    let v = match args.get(1) {
+                        Some(_) => parse_floats::<1>(&args[1..], number)?[0],
+                        None => 0.0,
+                    };
+                    self.tex_coords.push(vec2(u, v));
+                }
+                "vn" => {
+                    let [x, y, z] = parse_floats::<3>(&args, number)?;
+                    self.normals.push(vec3(x, y, z));
+                }
+                "f" => self.face(&args, number)?,
+                "g" | "o" => {
+                    self.flush_group();
+                    self.group_name = args.join(" ");
+                }
+                "usemtl" => {
+                    self.flush_group();
+                    self.material = Some(self.find_material(&args.join(" ")));
+                }
+                "mtllib" => self.load_libraries(&args),
+                // 线、点、平滑组、曲面等不影响三角形网格
+                _ => {}
+            }
+        }
+        self.flush_group();
+        Ok(self.finish())
+    }
+
+    fn flush_group(&mut self) {
+        let end = self.mesh.indices.len();
+        if end > self.group_start {
+            self.mesh.groups.push(Group {
+                name: self.group_name.clone(),
+                material: self.material,
+                triangles: self.group_start..end,
+            });
+        }
+        self.group_start = end;
+    }
+
+    fn find_material(&mut self, name: &str) -> usize {
+        if let Some(i) = self.mesh.materials.iter().position(|m| m.name == name) {
+            return i;
+        }
+        self.mesh.materials.push(Material {
+            name: name.to_string(),
+            ..Default::default()
+        });
+        self.mesh.materials.len() - 1
+    }
+
+    fn load_libraries(&mut self, names: &[&str]) {
+        let Some(base_dir) = self.base_dir else {
+            return;
+        };
+        for name in names {
+            let path = base_dir.join(name);
+            match load_mtl(&path) {
+                Ok(materials) => self.mesh.materials.extend(materials),
+                Err(e) => self
+                    .mesh
+                    .warnings
+                    .push(format!("failed to load material library {:?}: {}", path, e)),
+            }
+        }
+    }
+
+    /// 把 OBJ 的下标（从 1 开始，负数表示倒数）转换为数组下标
+    fn resolve(index: &str, len: usize, line: usize) -> Result<usize, ObjError> {
+        let i: i64 = index
+            .parse()
+            .map_err(|_| parse_error(line, format!("invalid index {:?}", index)))?;
+        let resolved = if i > 0 { i - 1 } else { len as i64 + i };
+        if i == 0 || resolved < 0 || resolved >= len as i64 {
+            return Err(parse_error(line, format!("index {} out of range", i)));
+        }
+        Ok(resolved as usize)
+    }
+
+    fn vertex(&mut self, token: &str, line: usize) -> Result<u32, ObjError> {
+        let mut parts = token.split('/');
+        let v = Self::resolve(parts.next().unwrap_or(""), self.positions.len(), line)?;
+        let vt = match parts.next() {
+            Some(vt) if !vt.is_empty() => Some(Self::resolve(vt, self.tex_coords.len(), line)?),
+            _ => None,
+        };
+        let vn = match parts.next() {
+            Some(vn) if !vn.is_empty() => Some(Self::resolve(vn, self.normals.len(), line)?),
+            _ => None,
+        };
+
+        let key = (v, vt, vn);
+        if let Some(&index) = self.vertices.get(&(key, self.material)) {
+            return Ok(index);
+        }
+        let index = self.keys.len() as u32;
+        self.vertices.insert((key, self.material), index);
+        self.keys.push(key);
+        Ok(index)
+    }
+
+    fn face(&mut self, args: &[&str], line: usize) -> Result<(), ObjError> {
+        if args.len() < 3 {
+            return Err(parse_error(line, "face has fewer than 3 vertices"));
+        }
+        let polygon = args
+            .iter()
+            .map(|token| self.vertex(token, line))
+            .collect::<Result<Vec<u32>, ObjError>>()?;
+        let points: Vec<Vec3> = polygon
+            .iter()
+            .map(|&i| self.positions[self.keys[i as usize].0])
+            .collect();
+        for [a, b, c] in triangulate(&points) {
+            self.mesh.indices.push([polygon[a], polygon[b], polygon[c]]);
+        }
+        Ok(())
+    }
+
+    /// 按面引用的 `v/vt/vn` 组合展开顶点属性
+    fn finish(mut self) -> Mesh {
+        let mesh = &mut self.mesh;
+        mesh.positions = self.keys.iter().map(|k| self.positions[k.0]).collect();
+
+        if self.keys.iter().any(|k| k.1.is_some()) {
+            mesh.tex_coords = self
+                .keys
+                .iter()
+                .map(|k| k.1.map_or(Vec2::zeros(), |i| self.tex_coords[i]))
+                .collect();
+        }
+
+        if self.keys.iter().any(|k| k.2.is_some()) {
+            // 没有指定法线的顶点使用按相邻面计算的平滑法线
+            let smooth = if self.keys.iter().any(|k| k.2.is_none()) {
+                mesh.compute_normals();
+                std::mem::take(&mut mesh.normals)
+            } else {
+                vec![]
+            };
+            mesh.normals = self
+                .keys
+                .iter()
+                .enumerate()
+                .map(|(i, k)| k.2.map_or_else(|| smooth[i], |n| self.normals[n]))
+                .collect();
+        }
+
+        if self.colors.iter().all(Option::is_some) && !self.colors.is_empty() {
+            mesh.colors = self
+                .keys
+                .iter()
+                .map(|k| to_color(self.colors[k.0].unwrap_or_else(Vec3::zeros)))
+                .collect();
+        }
+        self.mesh
+    }
+}
+
+/// 用耳切法把（可能是凹的）平面多边形切成三角形，返回顶点下标，三角形的绕序与多边形一致。
+///
+/// 多边形投影到与其法线最接近的坐标平面上处理；退化或自相交导致找不到耳朵时，剩余部分按扇形切分。
+pub fn triangulate(polygon: &[Vec3]) -> Vec<[usize; 3]> {
+    let n = polygon.len();
+    if n < 3 {
+        return vec![];
+    }
+    if n == 3 {
+        return vec![[0, 1, 2]];
+    }
+
+    // Newell 法计算多边形法线
+    let mut normal = Vec3::zeros();
+    for i in 0..n {
+        let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+        normal += vec3(
+            (a.y - b.y) * (a.z + b.z),
+            (a.z - b.z) * (a.x + b.x),
+            (a.x - b.x) * (a.y + b.y),
+        );
+    }
+    let abs = normal.abs();
+    let (u, v, sign) = if abs.x >= abs.y && abs.x >= abs.z {
+        (1, 2, normal.x)
+    } else if abs.y >= abs.z {
+        (2, 0, normal.y)
+    } else {
+        (0, 1, normal.z)
+    };
+    // 投影后保证多边形是逆时针的
+    let sign = if sign < 0.0 { -1.0 } else { 1.0 };
+    let points: Vec<Vec2> = polygon.iter().map(|p| vec2(p[u], p[v] * sign)).collect();
+
+    let cross = |o: Vec2, a: Vec2, b: Vec2| (a - o).perp(&(b - o));
+    let mut remaining: Vec<usize> = (0..n).collect();
+    let mut triangles = Vec::with_capacity(n - 2);
+    while remaining.len() > 3 {
+        let m = remaining.len();
+        // 从第二个顶点开始找，凸四边形会切成 (0, 1, 2)、(0, 2, 3)
+        let ear = (1..=m).map(|k| k % m).find(|&i| {
+            let (prev, cur, next) = (
+                remaining[(i + m - 1) % m],
+                remaining[i],
+                remaining[(i + 1) % m],
+            );
+            let (a, b, c) = (points[prev], points[cur], points[next]);
+            if cross(a, b, c) <= 0.0 {
+                return false;
+            }
+            remaining.iter().all(|&j| {
+                if j == prev || j == cur || j == next {
+                    return true;
+                }
+                let p = points[j];
+                !(cross(a, b, p) >= 0.0 && cross(b, c, p) >= 0.0 && cross(c, a, p) >= 0.0)
+            })
+        });
+        let Some(i) = ear else {
+            break;
+        };
+        triangles.push([
+            remaining[(i + m - 1) % m],
+            remaining[i],
+            remaining[(i + 1) % m],
+        ]);
+        remaining.remove(i);
+    }
+    for i in 1..remaining.len() - 1 {
+        triangles.push([remaining[0], remaining[i], remaining[i + 1]]);
+    }
+    triangles
+}
@@ -0,0 +1,467 @@
+use crate::color::Color;
+use crate::mesh::{Mesh, MeshError};
+use crate::obj::triangulate;
+use nalgebra_glm::{vec2, vec3, Vec3};
+use std::io::Write;
+use std::path::Path;
+
+/// PLY 文件的数据格式
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum PlyFormat {
+    Ascii,
+    #[default]
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+/// PLY 属性的标量类型
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Scalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl Scalar {
+    fn parse(name: &str) -> Result<Self, MeshError> {
+        Ok(match name {
+            "char" | "int8" => Scalar::I8,
+            "uchar" | "uint8" => Scalar::U8,
+            "short" | "int16" => Scalar::I16,
+            "ushort" | "uint16" => Scalar::U16,
+            "int" | "int32" => Scalar::I32,
+            "uint" | "uint32" => Scalar::U32,
+            "float" | "float32" => Scalar::F32,
+            "double" | "float64" => Scalar::F64,
+            _ => return Err(format_error(format!("unknown property type {:?}", name))),
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Scalar::I8 | Scalar::U8 => 1,
+            Scalar::I16 | Scalar::U16 => 2,
+            Scalar::I32 | Scalar::U32 | Scalar::F32 => 4,
+            Scalar::F64 => 8,
+        }
+    }
+
+    fn is_integer(self) -> bool {
+        !matches!(self, Scalar::F32 | Scalar::F64)
+    }
+}
+
+#[derive(Debug)]
+struct Property {
+    name: String,
+    ty: Scalar,
+    /// 列表属性的长度类型
+    count: Option<Scalar>,
+}
+
+#[derive(Debug)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+fn format_error(message: impl Into<String>) -> MeshError {
+    MeshError::Format(message.into())
+}
+
+/// 读取 PLY 文件
+pub fn load(path: impl AsRef<Path>) -> Result<Mesh, MeshError> {
+    read(&std::fs::read(path)?)
+}
+
+/// 解析 ASCII 或二进制的 PLY 数据。
+///
+/// `vertex` 元素中的 `x y z`、`nx ny nz`、`red green blue` 和 `u v`（或 `s t`）会被读取，
+/// `face` 元素中的多边形切分为三角形，其他元素和属性被忽略。
+pub fn read(data: &[u8]) -> Result<Mesh, MeshError> {
+    let (format, elements, body) = read_header(data)?;
+    let mut reader = match format {
+        PlyFormat::Ascii => {
+            let text = std::str::from_utf8(body).map_err(|_| format_error("invalid ASCII body"))?;
+            Body::Ascii(text.split_ascii_whitespace())
+        }
+        _ => Body::Binary {
+            data: body,
+            big_endian: format == PlyFormat::BinaryBigEndian,
+        },
+    };
+
+    let mut mesh = Mesh::default();
+    for element in &elements {
+        match element.name.as_str() {
+            "vertex" => read_vertices(&mut reader, element, &mut mesh)?,
+            "face" => read_faces(&mut reader, element, &mut mesh)?,
+            _ => {
+                for _ in 0..element.count {
+                    for property in &element.properties {
+                        reader.property(property)?;
+                    }
+                }
+            }
+        }
+    }
+
+    let vertex_count = mesh.positions.len() as u32;
+    if mesh.indices.iter().flatten().any(|&i| i >= vertex_count) {
+        return Err(format_error("face index out of range"));
+    }
+    Ok(mesh)
+}
+
+/// 逐行读取头部，直到第一个等于 `end_header` 的行，返回之前的各行和之后的数据部分。
+///
+/// 不在整个文件中查找 `end_header`，二进制数据中碰巧出现的这些字节不会被当成头部的结尾
+fn split_header(data: &[u8]) -> Result<(Vec<&str>, &[u8]), MeshError> {
+    let mut lines = vec![];
+    let mut rest = data;
+    while !rest.is_empty() {
+        // 行可能以 \r\n 结束，trim 会去掉 \r
+        let (line, next) = match rest.iter().position(|&b| b == b'\n') {
+            Some(i) => (&rest[..i], &rest[i + 1..]),
+            None => (rest, &rest[rest.len()..]),
+        };
+        let line = std::str::from_utf8(line)
+            .map_err(|_| format_error("invalid PLY header"))?
+            .trim();
+        if line == "end_header" {
+            return Ok((lines, next));
+        }
+        lines.push(line);
+        rest = next;
+    }
+    Err(format_error("missing end_header"))
+}
+
+fn read_header(data: &[u8]) -> Result<(PlyFormat, Vec<Element>, &[u8]), MeshError> {
+    let (lines, body) = split_header(data)?;
+    let mut lines = lines.into_iter();
+    if lines.next() != Some("ply") {
+        return Err(format_error("not a PLY file"));
+    }
+    let mut format = None;
+    let mut elements: Vec<Element> = vec![];
+    for line in lines {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["format", name, _version] => {
+                format = Some(match *name {
+                    "ascii" => PlyFormat::Ascii,
+                    "binary_little_endian" => PlyFormat::BinaryLittleEndian,
+                    "binary_big_endian" => PlyFormat::BinaryBigEndian,
+                    _ => return Err(format_error(format!("unknown format {:?}", name))),
+                });
+            }
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count
+                    .parse()
+                    .map_err(|_| format_error(format!("invalid element count {:?}", count)))?,
+                properties: vec![],
+            }),
+            ["property", rest @ ..] => {
+                let element = elements
+                    .last_mut()
+                    .ok_or_else(|| format_error("property before any element"))?;
+                let property = match rest {
+                    ["list", count, ty, name] => Property {
+                        name: name.to_string(),
+                        ty: Scalar::parse(ty)?,
+                        count: Some(Scalar::parse(count)?),
+                    },
+                    [ty, name] => Property {
+                        name: name.to_string(),
+                        ty: Scalar::parse(ty)?,
+                        count: None,
+                    },
+                    _ => return Err(format_error(format!("invalid property {:?}", line))),
+                };
+                element.properties.push(property);
+            }
+            [] | ["comment", ..] | ["obj_info", ..] => {}
+            _ => return Err(format_error(format!("invalid header line {:?}", line))),
+        }
+    }
+    let format = format.ok_or_else(|| format_error("missing format"))?;
+    Ok((format, elements, body))
+}
+
+/// 按顺序读取数据部分的标量
+enum Body<'a> {
+    Ascii(std::str::SplitAsciiWhitespace<'a>),
+    Binary { data: &'a [u8], big_endian: bool },
+}
+
+impl Body<'_> {
+    fn scalar(&mut self, ty: Scalar) -> Result<f64, MeshError> {
+        match self {
+            Body::Ascii(tokens) => {
+                let token = tokens
+                    .next()
+                    .ok_or_else(|| format_error("unexpected end of data"))?;
+                token
+                    .parse()
+                    .map_err(|_| format_error(format!("invalid number {:?}", token)))
+            }
+            Body::Binary { data, big_endian } => {
+                if data.len() < ty.size() {
+                    return Err(format_error("unexpected end of data"));
+                }
+                let (bytes, rest) = data.split_at(ty.size());
+                *data = rest;
+                let mut buf = [0u8; 8];
+                buf[..bytes.len()].copy_from_slice(bytes);
+                if *big_endian {
+                    buf[..bytes.len()].reverse();
+                }
+                Ok(match ty {
+                    Scalar::I8 => buf[0] as i8 as f64,
+                    Scalar::U8 => buf[0] as f64,
+                    Scalar::I16 => i16::from_le_bytes([buf[0], buf[1]]) as f64,
+                    Scalar::U16 => u16::from_le_bytes([buf[0], buf[1]]) as f64,
+                    Scalar::I32 => i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+                    Scalar::U32 => u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+                    Scalar::F32 => f32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+                    Scalar::F64 => f64::from_le_bytes(buf),
+                })
+            }
+        }
+    }
+
+    /// 读取一个属性，标量属性返回只有一个元素的列表
+    fn property(&mut self, property: &Property) -> Result<Vec<f64>, MeshError> {
+        let Some(count) = property.count else {
+            return Ok(vec![self.scalar(property.ty)?]);
+        };
+        let n = self.scalar(count)?;
+        if !(n >= 0.0 && n.fract() == 0.0) {
+            return Err(format_error(format!("invalid list length {}", n)));
+        }
+        (0..n as usize).map(|_| self.scalar(property.ty)).collect()
+    }
+}
+
+fn read_vertices(reader: &mut Body, element: &Element, mesh: &mut Mesh) -> Result<(), MeshError> {
+    let find = |names: &[&str]| {
+        element
+            .properties
+            .iter()
+            .position(|p| p.count.is_none() && names.contains(&p.name.as_str()))
+    };
+    let position = [find(&["x"]), find(&["y"]), find(&["z"])];
+    let normal = [find(&["nx"]), find(&["ny"]), find(&["nz"])];
+    let color = [
+        find(&["red", "r"]),
+        find(&["green", "g"]),
+        find(&["blue", "b"]),
+    ];
+    let uv = [
+        find(&["u", "s", "texture_u", "texture_s"]),
+        find(&["v", "t", "texture_v", "texture_t"]),
+    ];
+    if position.iter().any(Option::is_none) {
+        return Err(format_error("vertex element without x, y, z"));
+    }
+    let has = |indices: &[Option<usize>]| indices.iter().all(Option::is_some);
+    let (has_normal, has_color, has_uv) = (has(&normal), has(&color), has(&uv));
+
+    let mut values = vec![0.0; element.properties.len()];
+    for _ in 0..element.count {
+        for (value, property) in values.iter_mut().zip(&element.properties) {
+            // 列表属性只需要跳过
+            *value = reader.property(property)?.first().copied().unwrap_or(0.0);
+        }
+        let get = |i: Option<usize>| values[i.unwrap()] as f32;
+        mesh.positions
+            .push(vec3(get(position[0]), get(position[1]), get(position[2])));
+        if has_normal {
+            mesh.normals
+                .push(vec3(get(normal[0]), get(normal[1]), get(normal[2])));
+        }
+        if has_color {
+            // 整数颜色范围是 [0, 255]，浮点颜色范围是 [0, 1]
+            let channel = |i: Option<usize>| {
+                let i = i.unwrap();
+                let value = if element.properties[i].ty.is_integer() {
+                    values[i]
+                } else {
+                    values[i] * 255.0
+                };
+                value.round().clamp(0.0, 255.0) as u8
+            };
+            mesh.colors.push(Color::new_rgb(
+                channel(color[0]),
+                channel(color[1]),
+                channel(color[2]),
+            ));
+        }
+        if has_uv {
+            mesh.tex_coords.push(vec2(get(uv[0]), get(uv[1])));
+        }
+    }
+    Ok(())
+}
+
+fn read_faces(reader: &mut Body, element: &Element, mesh: &mut Mesh) -> Result<(), MeshError> {
+    let list = element
+        .properties
+        .iter()
+        .position(|p| p.count.is_some() && (p.name == "vertex_indices" || p.name == "vertex_index"))
+        .ok_or_else(|| format_error("face element without vertex_indices"))?;
+    for _ in 0..element.count {
+        for (i, property) in element.properties.iter().enumerate() {
+            let values = reader.property(property)?;
+            if i != list {
+                continue;
+            }
+            let mut polygon = Vec::with_capacity(values.len());
+            for value in values {
+                if !(value >= 0.0 && value.fract() == 0.0 && value <= u32::MAX as f64) {
+                    return Err(format_error(format!("invalid vertex index {}", value)));
+                }
+                polygon.push(value as u32);
+            }
+            // 顶点元素在面元素之前时才能按位置切分凹多边形，否则按扇形切分
+            let points: Vec<Vec3> = polygon
+                .iter()
+                .map(|&i| {
+                    mesh.positions
+                        .get(i as usize)
+                        .copied()
+                        .unwrap_or_else(Vec3::zeros)
+                })
+                .collect();
+            for [a, b, c] in triangulate(&points) {
+                mesh.indices.push([polygon[a], polygon[b], polygon[c]]);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 把网格保存为 PLY 文件
+pub fn save(mesh: &Mesh, path: impl AsRef<Path>, format: PlyFormat) -> Result<(), MeshError> {
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    write(mesh, &mut file, format)?;
+    file.flush()?;
+    Ok(())
+}
+
+/// 按指定格式写出网格的顶点（位置、法线、颜色、纹理坐标）和三角形
+pub fn write(mesh: &Mesh, w: &mut impl Write, format: PlyFormat) -> std::io::Result<()> {
+    let n = mesh.positions.len();
+    let has_normals = mesh.normals.len() == n && n > 0;
+    let has_colors = mesh.colors.len() == n && n > 0;
+    let has_uv = mesh.tex_coords.len() == n && n > 0;
+
+    let format_name = match format {
+        PlyFormat::Ascii => "ascii",
+        PlyFormat::BinaryLittleEndian => "binary_little_endian",
+        PlyFormat::BinaryBigEndian => "binary_big_endian",
+    };
+    writeln!(w, "ply")?;
+    writeln!(w, "format {} 1.0", format_name)?;
+    writeln!(w, "element vertex {}", n)?;
+    for name in ["x", "y", "z"] {
+        writeln!(w, "property float {}", name)?;
+    }
+    if has_normals {
+        for name in ["nx", "ny", "nz"] {
+            writeln!(w, "property float {}", name)?;
+        }
+    }
+    if has_colors {
+        for name in ["red", "green", "blue"] {
+            writeln!(w, "property uchar {}", name)?;
+        }
+    }
+    if has_uv {
+        for name in ["s", "t"] {
+            writeln!(w, "property float {}", name)?;
+        }
+    }
+    writeln!(w, "element face {}", mesh.indices.len())?;
+    writeln!(w, "property list uchar uint vertex_indices")?;
+    writeln!(w, "end_header")?;
+
+    let mut floats = Vec::with_capacity(8);
+    for i in 0..n {
+        floats.clear();
+        floats.extend(mesh.positions[i].iter());
+        if has_normals {
+            floats.extend(mesh.normals[i].iter());
+        }
+        let uv = if has_uv {
+            Some(mesh.tex_coords[i])
+        } else {
+            None
+        };
+        let color = if has_colors {
+            Some(mesh.colors[i])
+        } else {
+            None
+        };
+        match format {
+            PlyFormat::Ascii => {
+                let mut line: Vec<String> = floats.iter().map(f32::to_string).collect();
+                if let Some(c) = color {
+                    line.extend([c.r, c.g, c.b].map(|x| x.to_string()));
+                }
+                if let Some(uv) = uv {
+                    line.extend([uv.x, uv.y].map(|x| x.to_string()));
+                }
+                writeln!(w, "{}", line.join(" "))?;
+            }
+            _ => {
+                let big_endian = format == PlyFormat::BinaryBigEndian;
+                for &x in &floats {
+                    write_f32(w, x, big_endian)?;
+                }
+                if let Some(c) = color {
+                    w.write_all(&[c.r, c.g, c.b])?;
+                }
+                if let Some(uv) = uv {
+                    write_f32(w, uv.x, big_endian)?;
+                    write_f32(w, uv.y, big_endian)?;
+                }
+            }
+        }
+    }
+
+    for &[a, b, c] in &mesh.indices {
+        match format {
+            PlyFormat::Ascii => writeln!(w, "3 {} {} {}", a, b, c)?,
+            _ => {
+                w.write_all(&[3])?;
+                for i in [a, b, c] {
+                    let bytes = if format == PlyFormat::BinaryBigEndian {
+                        i.to_be_bytes()
+                    } else {
+                        i.to_le_bytes()
+                    };
+                    w.write_all(&bytes)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_f32(w: &mut impl Write, x: f32, big_endian: bool) -> std::io::Result<()> {
+    let bytes = if big_endian {
+        x.to_be_bytes()
+    } else {
+        x.to_le_bytes()
+    };
+    w.write_all(&bytes)
+}
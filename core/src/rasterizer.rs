@@ -0,0 +1,1146 @@
+use crate::color::Color;
+use crate::edge::{self, EdgeTriangle};
+use crate::lighting::{Light, Lighting, Material, PbrMaterial, ShadingMode};
+use crate::scanline::{self, ScanEdge};
+use crate::shader::FragmentShader;
+use crate::shadow::{Shadow, ShadowMap};
+use crate::surface::SurfaceMap;
+use crate::texture::Texture;
+use crate::triangle::Triangle;
+use bitflags::bitflags;
+use nalgebra_glm::{vec4, Mat4, UVec2, Vec2, Vec3, Vec4};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// 分块光栅化时 tile 的边长（像素）
+const TILE_SIZE: u32 = 64;
+
+/// 视口四周的保护带宽度（像素），顶点超出保护带的三角形会被剔除。
+///
+/// 目前没有做裁剪，靠近相机平面的顶点透视除法后坐标会非常大，
+/// 直接光栅化这样的三角形要遍历巨大的包围盒。
+const GUARD_BAND: f32 = 8192.0;
+
+pub struct Rasterizer {
+    width: u32,
+    height: u32,
+    frame_buf: Vec<Color>,
+    /// 越大表示离视点越远，一般都是正数，反转深度时都是负数
+    depth_buf: Vec<f32>,
+    model: Mat4,
+    view: Mat4,
+    projection: Mat4,
+    /// 投影矩阵把近平面映射到 NDC 的 z = 1
+    reversed_z: bool,
+    pos_buf: HashMap<u32, Vec<Vec3>>,
+    ind_buf: HashMap<u32, Vec<Vec3>>,
+    col_buf: HashMap<u32, Vec<Color>>,
+    tex_buf: HashMap<u32, Vec<Vec2>>,
+    norm_buf: HashMap<u32, Vec<Vec3>>,
+    textures: HashMap<u32, Texture>,
+    bound_tex_coords: Option<TexBufId>,
+    bound_normals: Option<NormBufId>,
+    bound_texture: Option<TextureId>,
+    bound_surface_map: Option<(TextureId, SurfaceMap)>,
+    /// 世界空间的光源
+    lights: Vec<Light>,
+    ambient_light: Vec3,
+    material: Option<Material>,
+    pbr_material: Option<PbrMaterial>,
+    /// 由光源、材质和观察矩阵得到的观察空间光照，设置了材质时才有
+    lighting: Option<Lighting>,
+    shading_mode: ShadingMode,
+    /// 与 `lights` 一一对应的阴影贴图
+    shadow_maps: Vec<Option<Arc<ShadowMap>>>,
+    /// 正在渲染阴影贴图的光源
+    shadow_pass: Option<usize>,
+    /// 顶点阶段的结果，按位置缓冲区下标存放，跨 draw 复用内存
+    vertex_cache: Vec<Option<ShadedVertex>>,
+    raster_mode: RasterMode,
+    exercises: Exercises,
+    /// 光栅化线程数，为 1 或使用 `RasterMode::BoundingBox` 时逐个三角形串行光栅化
+    threads: usize,
+    /// 多线程时先收集本次 draw 的屏幕空间三角形
+    triangles: Vec<Triangle>,
+    /// 每个 tile 覆盖到的三角形下标，按提交顺序排列
+    tile_bins: Vec<Vec<u32>>,
+    stats: DrawStats,
+    next_id: u32,
+    clear_color: Color,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Primitive {
+    /// 三角形的线框
+    Line,
+    Triangle,
+}
+
+/// 三角形的覆盖测试方式
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum RasterMode {
+    /// 包围盒加 `inside_triangle` 测试，即作业中要实现的 `rasterize_triangle`，见 [`Exercises`]
+    BoundingBox,
+    /// 定点数增量边函数，采样像素中心并遵循 top-left 规则，公共边上的像素只绘制一次。
+    /// 纹理使用 mipmap 时按 2x2 像素块光栅化以求纹理坐标的导数，其他模式只能在原图上采样。
+    /// 光栅化器默认使用这种方式，不需要作业中的函数
+    #[default]
+    EdgeFunction,
+    /// 经典的扫描线算法（边表 + 活动边表），覆盖结果与 `EdgeFunction` 相同
+    Scanline,
+}
+
+/// 作业中需要实现的光栅化函数，`RasterMode::BoundingBox` 时调用。
+///
+/// 默认的实现会直接 panic，各个作业用 [`Rasterizer::set_exercises`] 换成自己写的函数，
+/// 再用 [`Rasterizer::set_raster_mode`] 切换到 `BoundingBox`
+#[derive(Debug, Clone, Copy)]
+pub struct Exercises {
+    /// 像素 `(x, y)` 的中心是否在屏幕空间的三角形 `v` 内
+    pub inside_triangle: fn(x: u32, y: u32, v: [Vec4; 3]) -> bool,
+    /// 遍历三角形的包围盒，把三角形内的像素写入帧缓冲
+    pub rasterize_triangle: fn(r: &mut Rasterizer, t: &Triangle),
+}
+
+impl Default for Exercises {
+    fn default() -> Self {
+        Self {
+            inside_triangle: |_, _, _| {
+                unimplemented!("inside_triangle, see Rasterizer::set_exercises")
+            },
+            rasterize_triangle: |_, _| {
+                unimplemented!("rasterize_triangle, see Rasterizer::set_exercises")
+            },
+        }
+    }
+}
+
+/// 一次 `draw` 中顶点阶段的结果，相邻三角形共享的顶点只计算一次
+#[derive(Debug, Clone, Copy)]
+struct ShadedVertex {
+    /// 屏幕空间坐标，w 为裁剪空间的 w
+    screen: Vec4,
+    view_pos: Vec3,
+    /// 观察空间法线，没有绑定法线时为零
+    normal: Vec3,
+    /// 顶点颜色，Gouraud 着色时是顶点处的光照结果
+    color: Color,
+}
+
+/// 最近一次 `draw` 的三角形统计，被剔除的三角形不会进入光栅化
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct DrawStats {
+    /// 索引缓冲中提交的三角形数
+    pub submitted: usize,
+    /// 实际送去光栅化的三角形数
+    pub rasterized: usize,
+    /// 索引不是非负整数，或超出了位置/颜色缓冲的范围
+    pub invalid_indices: usize,
+    /// 变换后的顶点含有 NaN 或无穷大
+    pub non_finite: usize,
+    /// 屏幕空间面积为零
+    pub degenerate: usize,
+    /// 顶点超出了视口外的保护带
+    pub out_of_range: usize,
+    /// 经过顶点阶段（变换、法线变换和 Gouraud 光照）的顶点数，共享的索引只计算一次
+    pub vertices: usize,
+}
+
+bitflags! {
+    pub struct Buffers: u32 {
+        const COLOR = 0b00000001;
+        const DEPTH = 0b00000010;
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct PosBufId(u32);
+
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct IndBufId(u32);
+
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct ColBufId(u32);
+
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct TexBufId(u32);
+
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct NormBufId(u32);
+
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct TextureId(u32);
+
+impl Rasterizer {
+    pub fn new(width: u32, height: u32) -> Self {
+        let frame_buf = vec![Color::BLACK; (width * height) as usize];
+        let depth_buf = vec![f32::MAX; (width * height) as usize];
+        let model = Mat4::identity();
+        let view = Mat4::identity();
+        let projection = Mat4::identity();
+        let pos_buf = HashMap::new();
+        let ind_buf = HashMap::new();
+        let col_buf = HashMap::new();
+        let next_id = 0;
+        Self {
+            width,
+            height,
+            frame_buf,
+            depth_buf,
+            model,
+            view,
+            projection,
+            reversed_z: false,
+            pos_buf,
+            ind_buf,
+            col_buf,
+            tex_buf: HashMap::new(),
+            norm_buf: HashMap::new(),
+            textures: HashMap::new(),
+            bound_tex_coords: None,
+            bound_normals: None,
+            bound_texture: None,
+            bound_surface_map: None,
+            lights: Vec::new(),
+            ambient_light: Vec3::new(10.0, 10.0, 10.0),
+            material: None,
+            pbr_material: None,
+            lighting: None,
+            shading_mode: ShadingMode::default(),
+            shadow_maps: Vec::new(),
+            shadow_pass: None,
+            vertex_cache: Vec::new(),
+            raster_mode: RasterMode::default(),
+            exercises: Exercises::default(),
+            threads: 1,
+            triangles: Vec::new(),
+            tile_bins: Vec::new(),
+            stats: DrawStats::default(),
+            next_id,
+            clear_color: Color::BLACK,
+        }
+    }
+
+    pub fn load_positions(&mut self, positions: Vec<Vec3>) -> PosBufId {
+        let id = self.get_next_id();
+        self.pos_buf.insert(id, positions);
+        PosBufId(id)
+    }
+
+    pub fn load_indices(&mut self, indices: Vec<Vec3>) -> IndBufId {
+        let id = self.get_next_id();
+        self.ind_buf.insert(id, indices);
+        IndBufId(id)
+    }
+
+    pub fn load_colors(&mut self, colors: Vec<Color>) -> ColBufId {
+        let id = self.get_next_id();
+        self.col_buf.insert(id, colors);
+        ColBufId(id)
+    }
+
+    pub fn load_tex_coords(&mut self, tex_coords: Vec<Vec2>) -> TexBufId {
+        let id = self.get_next_id();
+        self.tex_buf.insert(id, tex_coords);
+        TexBufId(id)
+    }
+
+    pub fn load_normals(&mut self, normals: Vec<Vec3>) -> NormBufId {
+        let id = self.get_next_id();
+        self.norm_buf.insert(id, normals);
+        NormBufId(id)
+    }
+
+    pub fn load_texture(&mut self, texture: Texture) -> TextureId {
+        let id = self.get_next_id();
+        self.textures.insert(id, texture);
+        TextureId(id)
+    }
+
+    /// 修改已加载纹理的过滤、环绕方式等
+    pub fn texture_mut(&mut self, texture: TextureId) -> Option<&mut Texture> {
+        self.textures.get_mut(&texture.0)
+    }
+
+    /// 绑定之后 `draw` 会为每个顶点读取纹理坐标，`None` 表示解除绑定
+    pub fn bind_tex_coords(&mut self, tex_coords: Option<TexBufId>) {
+        self.bound_tex_coords = tex_coords;
+    }
+
+    pub fn bound_tex_coords(&self) -> Option<TexBufId> {
+        self.bound_tex_coords
+    }
+
+    /// 绑定之后 `draw` 会为每个顶点读取模型空间的法线，`None` 表示解除绑定
+    pub fn bind_normals(&mut self, normals: Option<NormBufId>) {
+        self.bound_normals = normals;
+    }
+
+    pub fn bound_normals(&self) -> Option<NormBufId> {
+        self.bound_normals
+    }
+
+    /// 绑定之后片元颜色取纹理在插值纹理坐标处的采样结果，而不是顶点颜色，
+    /// 需要同时用 `bind_tex_coords` 绑定纹理坐标。`None` 表示解除绑定
+    pub fn bind_texture(&mut self, texture: Option<TextureId>) {
+        self.bound_texture = texture;
+    }
+
+    pub fn bound_texture(&self) -> Option<TextureId> {
+        self.bound_texture
+    }
+
+    /// 绑定法线贴图或高度图，逐像素（`Phong`）计算光照时用它扰动法线和着色点。
+    /// 切线由三角形顶点的坐标和纹理坐标求出，需要同时绑定法线和纹理坐标。`None` 表示解除绑定
+    pub fn bind_surface_map(&mut self, map: Option<(TextureId, SurfaceMap)>) {
+        self.bound_surface_map = map;
+    }
+
+    /// 设置世界空间中的光源，只有用 `set_material` 设置了材质时才计算光照
+    pub fn set_lights(&mut self, lights: Vec<Light>) {
+        self.lights = lights;
+        self.update_lighting();
+    }
+
+    pub fn lights(&self) -> &[Light] {
+        &self.lights
+    }
+
+    /// 环境光强度，与材质的 `ka` 相乘，默认与作业 3 框架相同为 10
+    pub fn set_ambient_light(&mut self, intensity: Vec3) {
+        self.ambient_light = intensity;
+        self.update_lighting();
+    }
+
+    pub fn ambient_light(&self) -> Vec3 {
+        self.ambient_light
+    }
+
+    /// 设置之后按 Blinn-Phong 模型逐像素计算光照，需要用 `bind_normals` 绑定法线，
+    /// 没有法线的三角形不计算光照。`None` 表示不使用光照，直接输出顶点颜色或纹理颜色
+    pub fn set_material(&mut self, material: Option<Material>) {
+        self.material = material;
+        self.update_lighting();
+    }
+
+    pub fn material(&self) -> Option<Material> {
+        self.material
+    }
+
+    /// 设置之后用 Cook-Torrance 模型在线性空间中计算光照，代替 `set_material` 设置的 Blinn-Phong 材质。
+    /// 顶点颜色和纹理颜色被当作 sRGB 颜色转换到线性空间，与基础颜色相乘。`None` 表示不使用 PBR 材质
+    pub fn set_pbr_material(&mut self, material: Option<PbrMaterial>) {
+        self.pbr_material = material;
+        self.update_lighting();
+    }
+
+    pub fn pbr_material(&self) -> Option<PbrMaterial> {
+        self.pbr_material
+    }
+
+    /// 设置光照的计算方式，对之后的 draw 生效，默认逐像素计算（`Phong`）
+    pub fn set_shading_mode(&mut self, mode: ShadingMode) {
+        self.shading_mode = mode;
+    }
+
+    pub fn shading_mode(&self) -> ShadingMode {
+        self.shading_mode
+    }
+
+    /// 为第 `light` 个光源设置阴影贴图，需要先用 `begin_shadow_pass` 渲染深度。`None` 表示不投射阴影
+    pub fn set_shadow_map(&mut self, light: usize, map: Option<ShadowMap>) {
+        if self.shadow_maps.len() <= light {
+            self.shadow_maps.resize(light + 1, None);
+        }
+        self.shadow_maps[light] = map.map(Arc::new);
+        self.update_lighting();
+    }
+
+    pub fn shadow_map(&self, light: usize) -> Option<&ShadowMap> {
+        self.shadow_maps.get(light)?.as_deref()
+    }
+
+    /// 开始渲染第 `light` 个光源的阴影贴图。
+    ///
+    /// 之后的 `draw` 按模型矩阵和光源的投影只把深度写入阴影贴图，不修改帧缓冲，
+    /// 直到调用 `end_shadow_pass`。光源没有设置阴影贴图时这些 `draw` 什么也不做
+    pub fn begin_shadow_pass(&mut self, light: usize) {
+        self.shadow_pass = Some(light);
+        // 先释放光照中对阴影贴图的引用，避免写入时复制整张深度图
+        self.update_lighting();
+        if let (Some(l), Some(Some(map))) =
+            (self.lights.get(light), self.shadow_maps.get_mut(light))
+        {
+            Arc::make_mut(map).look_from(l);
+        }
+    }
+
+    /// 结束阴影贴图的渲染，之后的 `draw` 恢复正常绘制并使用阴影
+    pub fn end_shadow_pass(&mut self) {
+        self.shadow_pass = None;
+        self.update_lighting();
+    }
+
+    /// 光源、阴影或观察矩阵改变后重新把光源变换到观察空间
+    fn update_lighting(&mut self) {
+        let view_inverse = self.view.try_inverse().unwrap_or_else(Mat4::identity);
+        let shadows = self
+            .shadow_maps
+            .iter()
+            .map(|map| {
+                let map = map.as_ref().filter(|_| self.shadow_pass.is_none())?;
+                Some(Shadow {
+                    map: map.clone(),
+                    matrix: map.light_matrix() * view_inverse,
+                })
+            })
+            .collect();
+        if self.material.is_none() && self.pbr_material.is_none() {
+            self.lighting = None;
+            return;
+        }
+        self.lighting = Some(Lighting {
+            material: self.material.unwrap_or_default(),
+            lights: self
+                .lights
+                .iter()
+                .map(|l| l.transform(&self.view))
+                .collect(),
+            ambient: self.ambient_light,
+            shadows,
+            pbr: self.pbr_material,
+        });
+    }
+
+    /// 当前 draw 状态下的片元着色器
+    pub fn shader(&self) -> FragmentShader<'_> {
+        Self::fragment_shader(
+            &self.textures,
+            self.bound_texture,
+            self.bound_surface_map,
+            self.lighting.as_ref(),
+            self.shading_mode,
+        )
+    }
+
+    fn fragment_shader<'a>(
+        textures: &'a HashMap<u32, Texture>,
+        texture: Option<TextureId>,
+        surface: Option<(TextureId, SurfaceMap)>,
+        lighting: Option<&'a Lighting>,
+        shading: ShadingMode,
+    ) -> FragmentShader<'a> {
+        FragmentShader {
+            texture: texture.and_then(|id| textures.get(&id.0)),
+            surface: surface.and_then(|(id, map)| Some((textures.get(&id.0)?, map))),
+            lighting,
+            shading,
+        }
+    }
+
+    /// 计算点相对于三角形顶点的重心坐标。
+    ///
+    /// # 参数
+    ///
+    /// * `x` - 像素的 x 坐标，在像素中心 `x + 0.5` 处采样。
+    /// * `y` - 像素的 y 坐标，在像素中心 `y + 0.5` 处采样。
+    /// * `v` - 三角形顶点的 `Vec4` 数组，表示三个顶点。
+    ///
+    /// # 返回值
+    ///
+    /// 返回一个元组 `(alpha, beta, gamma)`，代表点的重心坐标。
+    pub fn compute_barycentric2d(x: u32, y: u32, v: [Vec4; 3]) -> (f32, f32, f32) {
+        let x = x as f32 + 0.5;
+        let y = y as f32 + 0.5;
+
+        let x0 = v[0].x;
+        let y0 = v[0].y;
+        let x1 = v[1].x;
+        let y1 = v[1].y;
+        let x2 = v[2].x;
+        let y2 = v[2].y;
+
+        // 使用三角形的顶点坐标和点 (x, y) 的坐标来计算重心坐标 alpha, beta, gamma。
+        // alpha = ((y1 - y2) * (x - x2) + (x2 - x1) * (y - y2)) / 分母
+        // beta = ((y2 - y0) * (x - x2) + (x0 - x2) * (y - y2)) / 分母
+        // gamma = 1 - alpha - beta
+        // 其中，分母 = (y1 - y2) * (x0 - x2) + (x2 - x1) * (y0 - y2)，为常数项，用于归一化。
+
+        let denom = (y1 - y2) * (x0 - x2) + (x2 - x1) * (y0 - y2);
+        let alpha = ((y1 - y2) * (x - x2) + (x2 - x1) * (y - y2)) / denom;
+        let beta = ((y2 - y0) * (x - x2) + (x0 - x2) * (y - y2)) / denom;
+        let gamma = 1.0 - alpha - beta;
+
+        (alpha, beta, gamma)
+    }
+
+    pub fn set_model(&mut self, model: Mat4) {
+        self.model = model;
+    }
+
+    pub fn model(&self) -> Mat4 {
+        self.model
+    }
+
+    pub fn set_view(&mut self, view: Mat4) {
+        self.view = view;
+        self.update_lighting();
+    }
+
+    pub fn set_projection(&mut self, projection: Mat4) {
+        self.projection = projection;
+    }
+
+    /// 投影矩阵是否使用反转的深度（近平面 z = 1，远平面 z = 0），见 [`crate::camera::Projection::matrix`]。
+    ///
+    /// 反转时深度取负，深度测试仍然是越小越近
+    pub fn set_reversed_z(&mut self, reversed_z: bool) {
+        self.reversed_z = reversed_z;
+    }
+
+    pub fn reversed_z(&self) -> bool {
+        self.reversed_z
+    }
+
+    /// 设置光栅化使用的线程数。
+    ///
+    /// 大于 1 时三角形会先按 64x64 的 tile 分箱，再由多个线程并行光栅化各个 tile，
+    /// 每个像素上三角形的绘制顺序不变，输出与单线程完全一致。
+    /// 只对内置的 `EdgeFunction` 和 `Scanline` 有效，`BoundingBox` 调用作业中的
+    /// `rasterize_triangle`，总是串行光栅化。
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    pub fn set_raster_mode(&mut self, mode: RasterMode) {
+        self.raster_mode = mode;
+    }
+
+    pub fn raster_mode(&self) -> RasterMode {
+        self.raster_mode
+    }
+
+    pub fn set_exercises(&mut self, exercises: Exercises) {
+        self.exercises = exercises;
+    }
+
+    pub fn exercises(&self) -> Exercises {
+        self.exercises
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn stats(&self) -> DrawStats {
+        self.stats
+    }
+
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: Color, depth: f32) {
+        if x < self.width && y < self.height {
+            let index = self.get_index(x, y);
+            if depth < self.depth_buf[index] {
+                self.frame_buf[index] = color;
+                self.depth_buf[index] = depth;
+            }
+        }
+    }
+
+    pub fn clear(&mut self, buffers: Buffers) {
+        if buffers.contains(Buffers::COLOR) {
+            self.frame_buf.fill(self.clear_color);
+        }
+        if buffers.contains(Buffers::DEPTH) {
+            self.depth_buf.fill(f32::MAX);
+        }
+    }
+
+    fn vec3_to_vec4(v: Vec3) -> Vec4 {
+        vec4(v.x, v.y, v.z, 1.0)
+    }
+
+    pub fn draw(
+        &mut self,
+        pos_buffer: PosBufId,
+        ind_buffer: IndBufId,
+        col_buffer: ColBufId,
+        primitive: Primitive,
+    ) {
+        if let Some(light) = self.shadow_pass {
+            self.draw_shadow(light, pos_buffer, ind_buffer);
+            return;
+        }
+
+        // 暂时把缓冲区从 self 中取出，这样光栅化时可以借用它们而不必整份复制
+        let pos_buf = std::mem::take(&mut self.pos_buf);
+        let ind_buf = std::mem::take(&mut self.ind_buf);
+        let col_buf = std::mem::take(&mut self.col_buf);
+        let tex_buf = std::mem::take(&mut self.tex_buf);
+        let norm_buf = std::mem::take(&mut self.norm_buf);
+        let mut vertex_cache = std::mem::take(&mut self.vertex_cache);
+        let mut triangles = std::mem::take(&mut self.triangles);
+
+        let pos = &pos_buf[&pos_buffer.0];
+        let ind = &ind_buf[&ind_buffer.0];
+        let col = &col_buf[&col_buffer.0];
+        let uv = self.bound_tex_coords.map(|id| &tex_buf[&id.0][..]);
+        let normals = self.bound_normals.map(|id| &norm_buf[&id.0][..]);
+        let vertex_count = pos
+            .len()
+            .min(col.len())
+            .min(uv.map_or(usize::MAX, <[_]>::len))
+            .min(normals.map_or(usize::MAX, <[_]>::len));
+
+        let model_view = self.view * self.model;
+        let mvp = self.projection * model_view;
+        // 法线用模型观察矩阵的逆转置变换，非均匀缩放时仍与表面垂直
+        let normal_matrix = nalgebra_glm::inverse_transpose(model_view);
+        // Gouraud 着色在顶点处计算光照，结果作为顶点颜色插值；有纹理时只计算光照颜色，再与纹理相乘
+        let vertex_lighting = self
+            .lighting
+            .clone()
+            .filter(|_| self.shading_mode == ShadingMode::Gouraud);
+        let textured = self.bound_texture.is_some();
+        let tiled = self.threads > 1 && self.raster_mode != RasterMode::BoundingBox;
+        let mut stats = DrawStats::default();
+        match primitive {
+            Primitive::Triangle if self.width > 0 && self.height > 0 => {
+                // 顶点变换后的结果按索引缓存，共享顶点在一次 draw 中只变换一次
+                vertex_cache.clear();
+                vertex_cache.resize(pos.len(), None);
+                triangles.clear();
+
+                for i in ind {
+                    stats.submitted += 1;
+                    let Some(indices) = Self::vertex_indices(i, vertex_count) else {
+                        stats.invalid_indices += 1;
+                        continue;
+                    };
+
+                    let mut t = Triangle::new();
+                    for (k, index) in indices.into_iter().enumerate() {
+                        let vertex = *vertex_cache[index].get_or_insert_with(|| {
+                            stats.vertices += 1;
+                            let view_pos = (model_view * Self::vec3_to_vec4(pos[index])).xyz();
+                            let normal = normals.map_or(Vec3::zeros(), |normals| {
+                                (normal_matrix * normals[index].push(0.0)).xyz()
+                            });
+                            let color = match &vertex_lighting {
+                                Some(lighting) => {
+                                    let base = if textured { Color::WHITE } else { col[index] };
+                                    lighting.shade(view_pos, normal, base)
+                                }
+                                None => col[index],
+                            };
+                            ShadedVertex {
+                                screen: self.transform_vertex(&mvp, pos[index]),
+                                view_pos,
+                                normal,
+                                color,
+                            }
+                        });
+                        t.set_vertex(k, vertex.screen.xyz());
+                        t.set_w(k, vertex.screen.w);
+                        t.set_view_pos(k, vertex.view_pos);
+                        t.set_normal(k, vertex.normal);
+                        t.set_color(k, vertex.color);
+                        if let Some(uv) = uv {
+                            t.set_tex_coords(k, uv[index].x, uv[index].y);
+                        }
+                    }
+
+                    // 在光栅化之前剔除无法产生有效像素的三角形，
+                    // 否则 NaN 会进入包围盒计算，零面积会让重心坐标除以零
+                    if !t.is_finite() {
+                        stats.non_finite += 1;
+                        continue;
+                    }
+                    if t.is_degenerate() {
+                        stats.degenerate += 1;
+                        continue;
+                    }
+                    if !self.in_guard_band(&t) {
+                        stats.out_of_range += 1;
+                        continue;
+                    }
+                    stats.rasterized += 1;
+
+                    if tiled {
+                        triangles.push(t);
+                    } else {
+                        match self.raster_mode {
+                            RasterMode::BoundingBox => {
+                                (self.exercises.rasterize_triangle)(self, &t)
+                            }
+                            mode => {
+                                let shader = Self::fragment_shader(
+                                    &self.textures,
+                                    self.bound_texture,
+                                    self.bound_surface_map,
+                                    self.lighting.as_ref(),
+                                    self.shading_mode,
+                                );
+                                let mut target = RenderTarget::full(
+                                    self.width,
+                                    self.height,
+                                    &mut self.frame_buf,
+                                    &mut self.depth_buf,
+                                );
+                                if mode == RasterMode::EdgeFunction {
+                                    Self::rasterize_triangle_edge(&t, &shader, &mut target);
+                                } else {
+                                    Self::rasterize_triangle_scanline(&t, &shader, &mut target);
+                                }
+                            }
+                        }
+                    }
+                }
+
+                if tiled {
+                    self.rasterize_tiled(&triangles);
+                }
+            }
+            Primitive::Triangle => {}
+            Primitive::Line => {
+                // 线框：画出每个三角形的三条边，边的颜色取起点的颜色
+                for i in ind {
+                    stats.submitted += 1;
+                    let Some(indices) = Self::vertex_indices(i, vertex_count) else {
+                        stats.invalid_indices += 1;
+                        continue;
+                    };
+                    let mut t = Triangle::new();
+                    for (k, &index) in indices.iter().enumerate() {
+                        t.set_vertex(k, self.transform_vertex(&mvp, pos[index]).xyz());
+                        t.set_color(k, col[index]);
+                    }
+                    if !t.is_finite() {
+                        stats.non_finite += 1;
+                        continue;
+                    }
+                    if !self.in_guard_band(&t) {
+                        stats.out_of_range += 1;
+                        continue;
+                    }
+                    stats.rasterized += 1;
+                    self.rasterize_wireframe(&t);
+                }
+            }
+        }
+
+        self.pos_buf = pos_buf;
+        self.ind_buf = ind_buf;
+        self.col_buf = col_buf;
+        self.tex_buf = tex_buf;
+        self.norm_buf = norm_buf;
+        self.vertex_cache = vertex_cache;
+        self.triangles = triangles;
+        self.stats = stats;
+    }
+
+    /// 阴影贴图渲染时的 draw，只变换顶点位置并写入深度
+    fn draw_shadow(&mut self, light: usize, pos_buffer: PosBufId, ind_buffer: IndBufId) {
+        let Some(Some(map)) = self.shadow_maps.get_mut(light) else {
+            return;
+        };
+        let map = Arc::make_mut(map);
+        let pos = &self.pos_buf[&pos_buffer.0];
+        let mvp = map.light_matrix() * self.model;
+        for i in &self.ind_buf[&ind_buffer.0] {
+            let Some(indices) = Self::vertex_indices(i, pos.len()) else {
+                continue;
+            };
+            map.draw_triangle(indices.map(|k| mvp * Self::vec3_to_vec4(pos[k])));
+        }
+    }
+
+    fn in_guard_band(&self, t: &Triangle) -> bool {
+        let (width, height) = (self.width as f32, self.height as f32);
+        t.v.iter().all(|v| {
+            (-GUARD_BAND..=width + GUARD_BAND).contains(&v.x)
+                && (-GUARD_BAND..=height + GUARD_BAND).contains(&v.y)
+        })
+    }
+
+    /// 把索引缓冲中的浮点索引转换为下标，任何一个不是 `[0, len)` 内的整数时返回 `None`。
+    fn vertex_indices(i: &Vec3, len: usize) -> Option<[usize; 3]> {
+        let mut indices = [0; 3];
+        for (k, &index) in i.iter().enumerate() {
+            // NaN 与任何数比较都为 false
+            if !(index >= 0.0 && index.fract() == 0.0 && (index as usize) < len) {
+                return None;
+            }
+            indices[k] = index as usize;
+        }
+        Some(indices)
+    }
+
+    /// 把模型空间的顶点经过 MVP 变换、透视除法和视口变换转换到屏幕空间。
+    ///
+    /// 返回值的 `w` 分量保留裁剪空间的 `w`，用于透视校正插值。
+    fn transform_vertex(&self, mvp: &Mat4, position: Vec3) -> Vec4 {
+        let f1 = (50.0 - 0.1) / 2.0;
+        let f2 = (50.0 + 0.1) / 2.0;
+
+        let mut vert = mvp * Self::vec3_to_vec4(position);
+        vert.x /= vert.w;
+        vert.y /= vert.w;
+        vert.z /= vert.w;
+
+        vert.x = (vert.x + 1.0) * 0.5 * self.width as f32;
+        vert.y = (vert.y + 1.0) * 0.5 * self.height as f32;
+        vert.z = vert.z * f1 + f2;
+        if self.reversed_z {
+            vert.z = -vert.z;
+        }
+        vert
+    }
+
+    pub fn framebuffer(&self) -> &[Color] {
+        &self.frame_buf
+    }
+
+    pub fn framebuffer_u32(&self) -> &[u32] {
+        bytemuck::cast_slice(&self.frame_buf)
+    }
+
+    pub fn framebuffer_u8(&self) -> &[u8] {
+        bytemuck::cast_slice(&self.frame_buf)
+    }
+
+    // bresenhams line algorithm
+    pub fn draw_line(&mut self, begin: Vec3, end: Vec3, color: Color) {
+        let mut x0 = begin.x as i32;
+        let mut y0 = begin.y as i32;
+        let mut x1 = end.x as i32;
+        let mut y1 = end.y as i32;
+        let mut steep = false;
+        if (x0 - x1).abs() < (y0 - y1).abs() {
+            std::mem::swap(&mut x0, &mut y0);
+            std::mem::swap(&mut x1, &mut y1);
+            steep = true;
+        }
+        if x0 > x1 {
+            std::mem::swap(&mut x0, &mut x1);
+            std::mem::swap(&mut y0, &mut y1);
+        }
+        let dx = x1 - x0;
+        let dy = y1 - y0;
+        let derror2 = dy.abs() * 2;
+        let mut error2 = 0;
+        let mut y = y0;
+        for x in x0..x1 {
+            if steep {
+                self.set_pixel(y as u32, x as u32, color, 0.0);
+            } else {
+                self.set_pixel(x as u32, y as u32, color, 0.0);
+            }
+            error2 += derror2;
+            if error2 > dx {
+                y += if y1 > y0 { 1 } else { -1 };
+                error2 -= dx * 2;
+            }
+        }
+    }
+
+    fn rasterize_wireframe(&mut self, t: &Triangle) {
+        for k in 0..3 {
+            self.draw_line(t.v[k], t.v[(k + 1) % 3], t.color[k]);
+        }
+    }
+
+    /// 把三角形分箱到 tile 中，再多线程并行光栅化。
+    ///
+    /// 帧缓冲按 tile 行切分成互不重叠的条带，每个线程每次领取一个条带，
+    /// 依次处理条带内的各个 tile，tile 内按提交顺序绘制其分到的三角形。
+    fn rasterize_tiled(&mut self, triangles: &[Triangle]) {
+        let tiles_x = self.width.div_ceil(TILE_SIZE) as usize;
+        let tiles_y = self.height.div_ceil(TILE_SIZE) as usize;
+
+        let mut bins = std::mem::take(&mut self.tile_bins);
+        bins.resize_with(tiles_x * tiles_y, Vec::new);
+        bins.iter_mut().for_each(Vec::clear);
+
+        for (index, t) in triangles.iter().enumerate() {
+            let Some((min, max)) = self.pixel_bounds(&t.to_vector4()) else {
+                continue;
+            };
+            // tile 行按帧缓冲内存中的行划分，帧缓冲的 y 轴是翻转的
+            let (row_min, row_max) = (self.height - 1 - max.y, self.height - 1 - min.y);
+            for ty in (row_min / TILE_SIZE)..=(row_max / TILE_SIZE) {
+                for tx in (min.x / TILE_SIZE)..=(max.x / TILE_SIZE) {
+                    bins[ty as usize * tiles_x + tx as usize].push(index as u32);
+                }
+            }
+        }
+
+        let mut frame_buf = std::mem::take(&mut self.frame_buf);
+        let mut depth_buf = std::mem::take(&mut self.depth_buf);
+        let band_len = (TILE_SIZE * self.width) as usize;
+        let bands = Mutex::new(
+            frame_buf
+                .chunks_mut(band_len)
+                .zip(depth_buf.chunks_mut(band_len))
+                .enumerate(),
+        );
+
+        let this = &*self;
+        std::thread::scope(|scope| {
+            for _ in 0..this.threads.min(tiles_y) {
+                scope.spawn(|| loop {
+                    let Some((band, (color, depth))) = bands.lock().unwrap().next() else {
+                        break;
+                    };
+                    let band_bins = &bins[band * tiles_x..(band + 1) * tiles_x];
+                    this.rasterize_band(triangles, band_bins, band, color, depth);
+                });
+            }
+        });
+
+        self.frame_buf = frame_buf;
+        self.depth_buf = depth_buf;
+        self.tile_bins = bins;
+    }
+
+    /// 光栅化一个 tile 行条带，`color` 和 `depth` 是该条带在帧缓冲中对应的切片。
+    fn rasterize_band(
+        &self,
+        triangles: &[Triangle],
+        bins: &[Vec<u32>],
+        band: usize,
+        color: &mut [Color],
+        depth: &mut [f32],
+    ) {
+        let row_begin = band as u32 * TILE_SIZE;
+        let rows = (color.len() / self.width as usize) as u32;
+        let y_max = self.height - 1 - row_begin;
+        let mut target = RenderTarget {
+            width: self.width,
+            height: self.height,
+            row_begin,
+            min: UVec2::new(0, y_max + 1 - rows),
+            max: UVec2::new(0, y_max),
+            color,
+            depth,
+        };
+        let shader = self.shader();
+
+        for (tx, bin) in bins.iter().enumerate() {
+            target.min.x = tx as u32 * TILE_SIZE;
+            target.max.x = (target.min.x + TILE_SIZE).min(self.width) - 1;
+
+            for &index in bin {
+                let t = &triangles[index as usize];
+                if self.raster_mode == RasterMode::EdgeFunction {
+                    Self::rasterize_triangle_edge(t, &shader, &mut target);
+                } else {
+                    Self::rasterize_triangle_scanline(t, &shader, &mut target);
+                }
+            }
+        }
+    }
+
+    /// 用定点数边函数光栅化三角形，退化（面积为零）的三角形不产生任何像素。
+    /// 需要纹理坐标的导数时改为逐个 2x2 像素块光栅化。
+    fn rasterize_triangle_edge(t: &Triangle, shader: &FragmentShader, target: &mut RenderTarget) {
+        let v = t.to_vector4();
+        let Some(edge) = EdgeTriangle::new(&v) else {
+            return;
+        };
+        let (min, max) = (target.min, target.max);
+        if shader.needs_derivatives() {
+            edge.for_each_quad(min.x, min.y, max.x, max.y, |x, y, quad, mask| {
+                let colors = shader.shade_quad(t, &quad, mask);
+                for i in (0..4).filter(|&i| mask[i]) {
+                    let (alpha, beta, gamma) = quad[i];
+                    let z = Self::interpolate_depth(alpha, beta, gamma, &v);
+                    target.set_pixel(x + (i & 1) as u32, y + (i >> 1) as u32, colors[i], z);
+                }
+            });
+            return;
+        }
+        edge.for_each_pixel(min.x, min.y, max.x, max.y, |x, y, (alpha, beta, gamma)| {
+            let z = Self::interpolate_depth(alpha, beta, gamma, &v);
+            target.set_pixel(x, y, shader.shade(t, alpha, beta, gamma), z);
+        });
+    }
+
+    /// 用扫描线算法光栅化三角形，以重心坐标作为沿边和扫描线插值的属性。
+    fn rasterize_triangle_scanline(
+        t: &Triangle,
+        shader: &FragmentShader,
+        target: &mut RenderTarget,
+    ) {
+        let v = t.to_vector4();
+        let barycentric = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        let mut edges = [0, 1, 2].map(|i| {
+            let j = (i + 1) % 3;
+            ScanEdge::new(
+                (v[i].x, v[i].y),
+                barycentric[i],
+                (v[j].x, v[j].y),
+                barycentric[j],
+            )
+        });
+        let (min, max) = (target.min, target.max);
+        scanline::fill(
+            &mut edges,
+            min.x,
+            min.y,
+            max.x,
+            max.y,
+            |x, y, [alpha, beta, gamma]| {
+                let z = Self::interpolate_depth(alpha, beta, gamma, &v);
+                target.set_pixel(x, y, shader.shade(t, alpha, beta, gamma), z);
+            },
+        );
+    }
+
+    /// 用扫描线算法填充屏幕空间中的任意多边形，凸多边形和凹多边形都可以。
+    ///
+    /// 按奇偶规则判断内外，深度和颜色沿边和扫描线线性插值，并做深度测试。
+    /// 顶点数少于 3、颜色数与顶点数不一致或含有无法表示的坐标时不绘制。
+    pub fn fill_polygon(&mut self, vertices: &[Vec3], colors: &[Color]) {
+        let representable =
+            |v: &Vec3| edge::representable(v.x) && edge::representable(v.y) && v.z.is_finite();
+        if vertices.len() < 3
+            || vertices.len() != colors.len()
+            || !vertices.iter().all(representable)
+            || self.width == 0
+            || self.height == 0
+        {
+            return;
+        }
+
+        let attrs = |i: usize| {
+            let c = colors[i];
+            [vertices[i].z, c.r as f32, c.g as f32, c.b as f32]
+        };
+        let mut edges: Vec<_> = (0..vertices.len())
+            .map(|i| {
+                let j = (i + 1) % vertices.len();
+                ScanEdge::new(
+                    (vertices[i].x, vertices[i].y),
+                    attrs(i),
+                    (vertices[j].x, vertices[j].y),
+                    attrs(j),
+                )
+            })
+            .collect();
+
+        let mut target = RenderTarget::full(
+            self.width,
+            self.height,
+            &mut self.frame_buf,
+            &mut self.depth_buf,
+        );
+        let (min, max) = (target.min, target.max);
+        scanline::fill(
+            &mut edges,
+            min.x,
+            min.y,
+            max.x,
+            max.y,
+            |x, y, [z, r, g, b]| {
+                let channel = |c: f32| c.round().clamp(0.0, 255.0) as u8;
+                target.set_pixel(x, y, Color::new_rgb(channel(r), channel(g), channel(b)), z);
+            },
+        );
+    }
+
+    /// 三角形在屏幕上覆盖的像素范围（闭区间），完全在屏幕外时返回 `None`。
+    fn pixel_bounds(&self, v: &[Vec4; 3]) -> Option<(UVec2, UVec2)> {
+        let min_x = v[0].x.min(v[1].x).min(v[2].x).floor();
+        let min_y = v[0].y.min(v[1].y).min(v[2].y).floor();
+        let max_x = v[0].x.max(v[1].x).max(v[2].x).ceil();
+        let max_y = v[0].y.max(v[1].y).max(v[2].y).ceil();
+        if max_x < 0.0 || max_y < 0.0 || min_x >= self.width as f32 || min_y >= self.height as f32 {
+            return None;
+        }
+        Some((
+            UVec2::new(min_x.max(0.0) as u32, min_y.max(0.0) as u32),
+            UVec2::new(
+                (max_x as u32).min(self.width - 1),
+                (max_y as u32).min(self.height - 1),
+            ),
+        ))
+    }
+
+    /// 透视校正插值得到像素的深度值。
+    fn interpolate_depth(alpha: f32, beta: f32, gamma: f32, v: &[Vec4; 3]) -> f32 {
+        let w_reciprocal = 1.0 / (alpha / v[0].w + beta / v[1].w + gamma / v[2].w);
+        let z_interpolated =
+            alpha * v[0].z / v[0].w + beta * v[1].z / v[1].w + gamma * v[2].z / v[2].w;
+        z_interpolated * w_reciprocal
+    }
+
+    #[allow(dead_code)]
+    fn get_index(&self, x: u32, y: u32) -> usize {
+        ((self.height - 1 - y) * self.width + x) as usize
+    }
+
+    fn get_next_id(&mut self) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    pub fn save_framebuffer_to_png(&self, file_path: &str) -> image::ImageResult<()> {
+        let width = self.width;
+        let height = self.height;
+
+        let buffer = self.framebuffer_u8();
+        image::save_buffer(
+            file_path,
+            buffer,
+            width,
+            height,
+            image::ExtendedColorType::Rgba8,
+        )?;
+        Ok(())
+    }
+}
+
+/// 帧缓冲上的一块可写区域，坐标为屏幕坐标，`min`/`max` 为闭区间。
+///
+/// `color`/`depth` 从帧缓冲内存的第 `row_begin` 行开始，分块光栅化时每个线程各持有一块。
+struct RenderTarget<'a> {
+    width: u32,
+    height: u32,
+    row_begin: u32,
+    min: UVec2,
+    max: UVec2,
+    color: &'a mut [Color],
+    depth: &'a mut [f32],
+}
+
+impl<'a> RenderTarget<'a> {
+    fn full(width: u32, height: u32, color: &'a mut [Color], depth: &'a mut [f32]) -> Self {
+        Self {
+            width,
+            height,
+            row_begin: 0,
+            min: UVec2::new(0, 0),
+            max: UVec2::new(width - 1, height - 1),
+            color,
+            depth,
+        }
+    }
+
+    fn set_pixel(&mut self, x: u32, y: u32, color: Color, depth: f32) {
+        // flip y
+        let index = ((self.height - 1 - y - self.row_begin) * self.width + x) as usize;
+        if depth < self.depth[index] {
+            self.color[index] = color;
+            self.depth[index] = depth;
+        }
+    }
+}
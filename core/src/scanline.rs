@@ -0,0 +1,148 @@
+use crate::edge::{snap, SUBPIXEL_HALF, SUBPIXEL_ONE};
+
+/// 扫描线算法中多边形的一条边，端点已吸附到亚像素定点网格，并按 y 从小到大排列。
+///
+/// 每条边携带 `N` 个顶点属性（深度、颜色、重心坐标等），沿边和沿扫描线都做线性插值。
+/// 覆盖规则与 [`crate::edge::EdgeTriangle`] 一致：采样像素中心，
+/// 扫描线包含边的上端点、不含下端点，区间包含左交点、不含右交点，即 top-left 规则。
+#[derive(Debug, Clone, Copy)]
+pub struct ScanEdge<const N: usize> {
+    x0: i64,
+    y0: i64,
+    dx: i64,
+    dy: i64,
+    /// 与这条边相交的第一条和最后一条扫描线（像素行），水平边的范围为空
+    first_row: i64,
+    last_row: i64,
+    attrs0: [f32; N],
+    attrs1: [f32; N],
+    /// 当前扫描线交点的 x 坐标乘以 dy，逐行增量更新
+    numer: i64,
+    /// 当前扫描线上第一个中心位于交点右侧（含）的像素
+    pixel: i64,
+    /// 当前交点的 x 坐标（像素）
+    x: f32,
+    /// 当前交点处插值得到的属性
+    attrs: [f32; N],
+}
+
+impl<const N: usize> ScanEdge<N> {
+    /// 由屏幕空间的两个端点及其属性建立一条边，坐标需要已经在可定点化的范围内。
+    pub fn new(from: (f32, f32), from_attrs: [f32; N], to: (f32, f32), to_attrs: [f32; N]) -> Self {
+        let (mut p0, mut p1) = ((snap(from.0), snap(from.1)), (snap(to.0), snap(to.1)));
+        let (mut attrs0, mut attrs1) = (from_attrs, to_attrs);
+        if p0.1 > p1.1 {
+            std::mem::swap(&mut p0, &mut p1);
+            std::mem::swap(&mut attrs0, &mut attrs1);
+        }
+        Self {
+            x0: p0.0,
+            y0: p0.1,
+            dx: p1.0 - p0.0,
+            dy: p1.1 - p0.1,
+            first_row: (p0.1 - SUBPIXEL_HALF).div_euclid(SUBPIXEL_ONE) + 1,
+            last_row: (p1.1 - SUBPIXEL_HALF).div_euclid(SUBPIXEL_ONE),
+            attrs0,
+            attrs1,
+            numer: 0,
+            pixel: 0,
+            x: 0.0,
+            attrs: attrs0,
+        }
+    }
+
+    fn row_center(row: i64) -> i64 {
+        row * SUBPIXEL_ONE + SUBPIXEL_HALF
+    }
+
+    /// 边在第 `row` 行加入活动边表
+    fn start(&mut self, row: i64) {
+        self.numer = self.x0 * self.dy + (Self::row_center(row) - self.y0) * self.dx;
+    }
+
+    /// 移动到下一条扫描线
+    fn advance(&mut self) {
+        self.numer += SUBPIXEL_ONE * self.dx;
+    }
+
+    /// 计算当前扫描线上的交点和插值属性
+    fn intersect(&mut self, row: i64) {
+        let denom = SUBPIXEL_ONE * self.dy;
+        self.pixel = -(SUBPIXEL_HALF * self.dy - self.numer).div_euclid(denom);
+        self.x = (self.numer as f64 / denom as f64) as f32;
+        let t = (Self::row_center(row) - self.y0) as f32 / self.dy as f32;
+        for i in 0..N {
+            self.attrs[i] = self.attrs0[i] + t * (self.attrs1[i] - self.attrs0[i]);
+        }
+    }
+}
+
+/// 用边表和活动边表对 `[x_min, x_max] x [y_min, y_max]` 内的多边形做扫描线填充。
+///
+/// `edges` 同时充当边表和活动边表：按起始扫描线排序后，
+/// 前段是已经结束的边，中段是活动边，后段是还未到达的边，整个过程不分配内存。
+/// 同一扫描线上的交点按 x 排序后两两配对成区间，即奇偶填充规则，因此凹多边形也能正确填充。
+/// 回调参数为像素坐标和该像素中心处的插值属性。
+pub fn fill<const N: usize>(
+    edges: &mut [ScanEdge<N>],
+    x_min: u32,
+    y_min: u32,
+    x_max: u32,
+    y_max: u32,
+    mut f: impl FnMut(u32, u32, [f32; N]),
+) {
+    let Some(first) = edges.iter().map(|e| e.first_row).min() else {
+        return;
+    };
+    let last = edges.iter().map(|e| e.last_row).max().unwrap_or(first);
+    edges.sort_unstable_by_key(|e| e.first_row);
+
+    let (mut done, mut next) = (0, 0);
+    for row in first.max(y_min as i64)..=last.min(y_max as i64) {
+        for edge in &mut edges[done..next] {
+            edge.advance();
+        }
+        while next < edges.len() && edges[next].first_row <= row {
+            edges[next].start(row);
+            next += 1;
+        }
+        let mut i = done;
+        while i < next {
+            if edges[i].last_row < row {
+                edges.swap(i, done);
+                done += 1;
+            }
+            i += 1;
+        }
+
+        let active = &mut edges[done..next];
+        for edge in active.iter_mut() {
+            edge.intersect(row);
+        }
+        active.sort_unstable_by(|a, b| a.pixel.cmp(&b.pixel).then(a.x.total_cmp(&b.x)));
+
+        for span in active.chunks_exact(2) {
+            let (left, right) = (&span[0], &span[1]);
+            let begin = left.pixel.max(x_min as i64);
+            let end = right.pixel.min(x_max as i64 + 1);
+            if begin >= end {
+                continue;
+            }
+            // 沿扫描线对属性做增量插值
+            let inv_width = 1.0 / (right.x - left.x);
+            let mut step = [0.0; N];
+            let mut attrs = left.attrs;
+            let offset = begin as f32 + 0.5 - left.x;
+            for i in 0..N {
+                step[i] = (right.attrs[i] - left.attrs[i]) * inv_width;
+                attrs[i] += step[i] * offset;
+            }
+            for x in begin..end {
+                f(x as u32, row as u32, attrs);
+                for i in 0..N {
+                    attrs[i] += step[i];
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,463 @@
+use crate::camera::Projection;
+use crate::color::{linear_to_srgb, Color};
+use crate::mesh::{to_color, Group, Material, Mesh, MeshBuffers, MeshError};
+use crate::rasterizer::{Primitive, Rasterizer, TextureId};
+use crate::texture::{Filter, Texture, WrapMode};
+use gltf::mesh::Mode;
+use gltf::texture::{MinFilter, WrappingMode};
+use nalgebra_glm::{vec2, vec3, Mat4, Vec3};
+use std::path::Path;
+
+/// glTF 2.0 描述的场景：网格、材质、贴图、相机和节点层级
+#[derive(Debug, Default, Clone)]
+pub struct Scene {
+    /// 每个 glTF 网格对应一个 [`Mesh`]，其中每个图元是一个分组。
+    ///
+    /// 各网格的 `materials` 都是 `Scene::materials` 的副本，分组的材质下标即 glTF 材质下标。
+    pub meshes: Vec<Mesh>,
+    pub materials: Vec<Material>,
+    /// 每个材质的基础颜色贴图在 `textures` 中的下标
+    pub material_textures: Vec<Option<usize>>,
+    /// 与 glTF 的 texture 一一对应，已按采样器设置好过滤和环绕方式
+    pub textures: Vec<Texture>,
+    pub cameras: Vec<Camera>,
+    pub nodes: Vec<Node>,
+    /// 默认场景的根节点
+    pub roots: Vec<usize>,
+    /// 读取时忽略的内容，例如不支持的图元类型，由调用者决定是否提示
+    pub warnings: Vec<String>,
+}
+
+/// 场景中的节点，变换相对于父节点
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub name: String,
+    pub transform: Mat4,
+    pub mesh: Option<usize>,
+    pub camera: Option<usize>,
+    pub children: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Camera {
+    pub name: String,
+    pub projection: Projection,
+    /// glTF 中没有指定宽高比的透视相机使用视口的宽高比，`projection` 中的宽高比只是占位
+    pub viewport_aspect: bool,
+}
+
+impl Camera {
+    /// 投影矩阵，`aspect` 是视口的宽高比
+    pub fn projection_matrix(&self, aspect: f32) -> Mat4 {
+        let mut projection = self.projection;
+        if self.viewport_aspect {
+            projection.set_aspect(aspect);
+        }
+        projection.matrix(false)
+    }
+}
+
+/// 读取 `.gltf` 或 `.glb` 文件，外部的缓冲区和图片相对于文件所在目录查找
+pub fn load(path: impl AsRef<Path>) -> Result<Scene, MeshError> {
+    let (document, buffers, images) = gltf::import(path).map_err(to_mesh_error)?;
+    Ok(convert(&document, &buffers, &images))
+}
+
+/// 解析内存中的 glTF 或 GLB 数据，只能引用内嵌的缓冲区和图片
+pub fn read(data: &[u8]) -> Result<Scene, MeshError> {
+    let (document, buffers, images) = gltf::import_slice(data).map_err(to_mesh_error)?;
+    Ok(convert(&document, &buffers, &images))
+}
+
+fn to_mesh_error(e: gltf::Error) -> MeshError {
+    match e {
+        gltf::Error::Io(e) => MeshError::Io(e),
+        e => MeshError::Format(e.to_string()),
+    }
+}
+
+fn convert(
+    document: &gltf::Document,
+    buffers: &[gltf::buffer::Data],
+    images: &[gltf::image::Data],
+) -> Scene {
+    let mut warnings = vec![];
+    let materials: Vec<Material> = document.materials().map(convert_material).collect();
+    let material_textures = document
+        .materials()
+        .map(|m| {
+            let info = m.pbr_metallic_roughness().base_color_texture()?;
+            if info.tex_coord() != 0 {
+                warnings.push(format!(
+                    "material {:?}: only TEXCOORD_0 is supported, ignoring base color texture",
+                    m.name().unwrap_or_default()
+                ));
+                return None;
+            }
+            Some(info.texture().index())
+        })
+        .collect();
+    let textures = document
+        .textures()
+        .map(|t| convert_texture(&t, &images[t.source().index()]))
+        .collect();
+    let meshes = document
+        .meshes()
+        .map(|m| convert_mesh(&m, buffers, &materials, &mut warnings))
+        .collect();
+    let cameras = document
+        .cameras()
+        .map(|c| {
+            let (projection, viewport_aspect) = match c.projection() {
+                gltf::camera::Projection::Perspective(p) => (
+                    Projection::Perspective {
+                        fov_y: p.yfov(),
+                        aspect: p.aspect_ratio().unwrap_or(1.0),
+                        near: p.znear(),
+                        far: p.zfar(),
+                    },
+                    p.aspect_ratio().is_none(),
+                ),
+                // 正交相机的可见范围是 [-xmag, xmag] x [-ymag, ymag]
+                gltf::camera::Projection::Orthographic(o) => (
+                    Projection::Orthographic {
+                        height: 2.0 * o.ymag(),
+                        aspect: o.xmag() / o.ymag(),
+                        near: o.znear(),
+                        far: o.zfar(),
+                    },
+                    false,
+                ),
+            };
+            Camera {
+                name: c.name().unwrap_or_default().to_string(),
+                projection,
+                viewport_aspect,
+            }
+        })
+        .collect();
+    let nodes: Vec<Node> = document
+        .nodes()
+        .map(|n| Node {
+            name: n.name().unwrap_or_default().to_string(),
+            transform: Mat4::from_column_slice(n.transform().matrix().as_flattened()),
+            mesh: n.mesh().map(|m| m.index()),
+            camera: n.camera().map(|c| c.index()),
+            children: n.children().map(|c| c.index()).collect(),
+        })
+        .collect();
+
+    // 没有场景时把所有不是子节点的节点作为根节点
+    let roots = match document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+    {
+        Some(scene) => scene.nodes().map(|n| n.index()).collect(),
+        None => {
+            let mut is_child = vec![false; nodes.len()];
+            for &child in nodes.iter().flat_map(|n| &n.children) {
+                is_child[child] = true;
+            }
+            (0..nodes.len()).filter(|&i| !is_child[i]).collect()
+        }
+    };
+
+    Scene {
+        meshes,
+        materials,
+        material_textures,
+        textures,
+        cameras,
+        nodes,
+        roots,
+        warnings,
+    }
+}
+
+fn convert_material(material: gltf::Material) -> Material {
+    let pbr = material.pbr_metallic_roughness();
+    let [r, g, b, a] = pbr.base_color_factor();
+    Material {
+        name: material.name().unwrap_or_default().to_string(),
+        diffuse: linear_to_srgb(vec3(r, g, b)),
+        opacity: a,
+        ..Default::default()
+    }
+}
+
+fn convert_texture(texture: &gltf::Texture, image: &gltf::image::Data) -> Texture {
+    use gltf::image::Format;
+    let (channels, bytes) = match image.format {
+        Format::R8 => (1, 1),
+        Format::R8G8 => (2, 1),
+        Format::R8G8B8 => (3, 1),
+        Format::R8G8B8A8 => (4, 1),
+        Format::R16 => (1, 2),
+        Format::R16G16 => (2, 2),
+        Format::R16G16B16 => (3, 2),
+        Format::R16G16B16A16 => (4, 2),
+        Format::R32G32B32FLOAT => (3, 4),
+        Format::R32G32B32A32FLOAT => (4, 4),
+    };
+    let channel = |c: &[u8]| match bytes {
+        1 => c[0],
+        // 按本机字节序存储，16 位取高 8 位
+        2 => (u16::from_ne_bytes([c[0], c[1]]) >> 8) as u8,
+        _ => (f32::from_ne_bytes([c[0], c[1], c[2], c[3]]).clamp(0.0, 1.0) * 255.0).round() as u8,
+    };
+    let data = image
+        .pixels
+        .chunks_exact(channels * bytes)
+        .map(|pixel| {
+            let mut c = pixel.chunks_exact(bytes).map(channel);
+            match channels {
+                // 单通道视为灰度
+                1 => {
+                    let l = c.next().unwrap();
+                    Color::new_rgb(l, l, l)
+                }
+                2 => {
+                    let (l, a) = (c.next().unwrap(), c.next().unwrap());
+                    Color::new_rgba(l, l, l, a)
+                }
+                _ => Color::new_rgba(
+                    c.next().unwrap(),
+                    c.next().unwrap(),
+                    c.next().unwrap(),
+                    c.next().unwrap_or(255),
+                ),
+            }
+        })
+        .collect();
+
+    let mut result = Texture::new(image.width, image.height, data);
+    let sampler = texture.sampler();
+    result.set_wrap_mode(match sampler.wrap_s() {
+        WrappingMode::ClampToEdge => WrapMode::ClampToEdge,
+        WrappingMode::MirroredRepeat => WrapMode::MirroredRepeat,
+        WrappingMode::Repeat => WrapMode::Repeat,
+    });
+    let filter = match sampler.min_filter() {
+        Some(MinFilter::Nearest) => Filter::Nearest,
+        Some(MinFilter::Linear) => Filter::Bilinear,
+        // 其他方式都会用到 mipmap，没有指定时也使用三线性过滤
+        _ => Filter::Trilinear,
+    };
+    if filter.uses_mipmaps() {
+        result.generate_mipmaps();
+    }
+    result.set_filter(filter);
+    result
+}
+
+fn convert_mesh(
+    mesh: &gltf::Mesh,
+    buffers: &[gltf::buffer::Data],
+    materials: &[Material],
+    warnings: &mut Vec<String>,
+) -> Mesh {
+    let mut result = Mesh {
+        materials: materials.to_vec(),
+        ..Default::default()
+    };
+    let (mut has_normals, mut has_tex_coords, mut has_colors) = (false, false, false);
+    for primitive in mesh.primitives() {
+        let reader = primitive.reader(|b| Some(&buffers[b.index()]));
+        let Some(positions) = reader.read_positions() else {
+            continue;
+        };
+        let base = result.positions.len() as u32;
+        result.positions.extend(positions.map(Vec3::from));
+        let count = result.positions.len();
+
+        // 缺少的属性先用默认值补齐，所有图元都没有时再清空
+        match reader.read_normals() {
+            Some(normals) => {
+                has_normals = true;
+                result.normals.extend(normals.map(Vec3::from));
+            }
+            None => result.normals.resize(count, Vec3::zeros()),
+        }
+        match reader.read_tex_coords(0) {
+            Some(tex_coords) => {
+                has_tex_coords = true;
+                // glTF 的 v = 0 在图片顶部
+                result
+                    .tex_coords
+                    .extend(tex_coords.into_f32().map(|[u, v]| vec2(u, 1.0 - v)));
+            }
+            None => result.tex_coords.resize(count, vec2(0.0, 0.0)),
+        }
+        match reader.read_colors(0) {
+            Some(colors) => {
+                has_colors = true;
+                result.colors.extend(
+                    colors
+                        .into_rgb_f32()
+                        .map(|c| to_color(linear_to_srgb(Vec3::from(c)))),
+                );
+            }
+            None => result.colors.resize(count, Color::WHITE),
+        }
+
+        let indices: Vec<u32> = match reader.read_indices() {
+            Some(indices) => indices.into_u32().collect(),
+            None => (0..count as u32 - base).collect(),
+        };
+        let start = result.indices.len();
+        let triangles = assemble(primitive.mode(), &indices).unwrap_or_else(|| {
+            warnings.push(format!(
+                "mesh {:?}: ignoring {:?} primitive",
+                mesh.name().unwrap_or_default(),
+                primitive.mode()
+            ));
+            vec![]
+        });
+        for [a, b, c] in triangles {
+            if [a, b, c].iter().any(|&i| base + i >= count as u32) {
+                continue;
+            }
+            result.indices.push([base + a, base + b, base + c]);
+        }
+        result.groups.push(Group {
+            name: mesh.name().unwrap_or_default().to_string(),
+            material: primitive.material().index(),
+            triangles: start..result.indices.len(),
+        });
+    }
+
+    if !has_tex_coords {
+        result.tex_coords.clear();
+    }
+    if !has_colors {
+        result.colors.clear();
+    }
+    // 只有部分图元有法线时，glTF 要求缺少法线的图元使用平面法线，这里统一重新计算
+    if !has_normals {
+        result.normals.clear();
+    } else if result.normals.iter().any(|n| *n == Vec3::zeros()) {
+        result.compute_normals();
+    }
+    result
+}
+
+/// 把图元的索引组装为三角形，点和线图元返回 `None`
+fn assemble(mode: Mode, indices: &[u32]) -> Option<Vec<[u32; 3]>> {
+    let triangles = match mode {
+        Mode::Triangles => indices
+            .chunks_exact(3)
+            .map(|t| [t[0], t[1], t[2]])
+            .collect(),
+        // 奇数三角形交换前两个顶点，保持环绕方向一致
+        Mode::TriangleStrip => indices
+            .windows(3)
+            .enumerate()
+            .map(|(i, t)| {
+                if i % 2 == 0 {
+                    [t[0], t[1], t[2]]
+                } else {
+                    [t[1], t[0], t[2]]
+                }
+            })
+            .collect(),
+        Mode::TriangleFan => (1..indices.len().saturating_sub(1))
+            .map(|i| [indices[0], indices[i], indices[i + 1]])
+            .collect(),
+        _ => return None,
+    };
+    Some(triangles)
+}
+
+impl Scene {
+    /// 深度优先遍历默认场景，返回每个节点的下标和世界变换
+    pub fn world_transforms(&self) -> Vec<(usize, Mat4)> {
+        let mut result = vec![];
+        let mut stack: Vec<(usize, Mat4)> = self
+            .roots
+            .iter()
+            .rev()
+            .map(|&i| (i, Mat4::identity()))
+            .collect();
+        while let Some((i, parent)) = stack.pop() {
+            let node = &self.nodes[i];
+            let world = parent * node.transform;
+            result.push((i, world));
+            stack.extend(node.children.iter().rev().map(|&c| (c, world)));
+        }
+        result
+    }
+
+    /// 场景中第一个相机的视图矩阵和投影矩阵，`aspect` 是视口的宽高比
+    pub fn camera(&self, aspect: f32) -> Option<(Mat4, Mat4)> {
+        self.world_transforms().into_iter().find_map(|(i, world)| {
+            let camera = &self.cameras[self.nodes[i].camera?];
+            Some((
+                nalgebra_glm::inverse(&world),
+                camera.projection_matrix(aspect),
+            ))
+        })
+    }
+}
+
+/// 场景上传到 [`Rasterizer`] 之后的缓冲区和贴图
+#[derive(Debug, Clone)]
+pub struct SceneBuffers {
+    /// 与 `Scene::meshes` 一一对应
+    pub meshes: Vec<MeshBuffers>,
+    /// 与 `Scene::textures` 一一对应
+    pub textures: Vec<TextureId>,
+}
+
+impl Rasterizer {
+    /// 上传场景中所有网格和贴图
+    pub fn load_scene(&mut self, scene: &Scene) -> SceneBuffers {
+        SceneBuffers {
+            meshes: scene.meshes.iter().map(|m| self.load_mesh(m)).collect(),
+            textures: scene
+                .textures
+                .iter()
+                .map(|t| self.load_texture(t.clone()))
+                .collect(),
+        }
+    }
+
+    /// 按节点层级绘制整个场景，每个节点的世界变换作为模型矩阵。
+    ///
+    /// 视图和投影矩阵需要事先设置，例如使用 [`Scene::camera`]。绘制之后恢复原来的模型矩阵和绑定状态。
+    pub fn draw_scene(&mut self, scene: &Scene, buffers: &SceneBuffers) {
+        let model = self.model();
+        let (normals, tex_coords, texture) = (
+            self.bound_normals(),
+            self.bound_tex_coords(),
+            self.bound_texture(),
+        );
+        for (i, world) in scene.world_transforms() {
+            let Some(mesh) = scene.nodes[i].mesh else {
+                continue;
+            };
+            let (mesh, mesh_buffers) = (&scene.meshes[mesh], &buffers.meshes[mesh]);
+            self.set_model(world);
+            self.bind_normals(mesh_buffers.normals);
+            self.bind_tex_coords(mesh_buffers.tex_coords);
+            for (group, &indices) in mesh.groups.iter().zip(&mesh_buffers.groups) {
+                // 没有纹理坐标时无法采样贴图
+                let texture = group
+                    .material
+                    .and_then(|m| scene.material_textures[m])
+                    .filter(|_| mesh_buffers.tex_coords.is_some())
+                    .map(|t| buffers.textures[t]);
+                self.bind_texture(texture);
+                self.draw(
+                    mesh_buffers.positions,
+                    indices,
+                    mesh_buffers.colors,
+                    Primitive::Triangle,
+                );
+            }
+        }
+        self.set_model(model);
+        self.bind_normals(normals);
+        self.bind_tex_coords(tex_coords);
+        self.bind_texture(texture);
+    }
+}
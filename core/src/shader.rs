@@ -0,0 +1,123 @@
+use crate::color::Color;
+use crate::lighting::{Lighting, ShadingMode};
+use crate::surface::SurfaceMap;
+use crate::texture::Texture;
+use crate::triangle::Triangle;
+use nalgebra_glm::{Vec2, Vec3};
+
+/// 计算片元颜色所需的 draw 状态。
+///
+/// 只持有只读引用，分块光栅化时可以在线程间共享。
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FragmentShader<'a> {
+    /// 绑定的纹理，设置后片元颜色取纹理在插值纹理坐标处的采样结果
+    pub texture: Option<&'a Texture>,
+    /// 设置后按 Blinn-Phong 模型对纹理或顶点颜色计算逐像素光照
+    pub lighting: Option<&'a Lighting>,
+    /// 法线贴图或高度图，只在逐像素计算光照时使用
+    pub surface: Option<(&'a Texture, SurfaceMap)>,
+    /// 设置了光照时的着色方式。`Gouraud` 的顶点光照在 draw 时已经写入三角形的顶点颜色
+    pub shading: ShadingMode,
+}
+
+impl FragmentShader<'_> {
+    /// 是否需要按 2x2 像素块光栅化，以求出纹理坐标的屏幕空间导数
+    pub fn needs_derivatives(&self) -> bool {
+        self.texture
+            .is_some_and(|texture| texture.filter().uses_mipmaps())
+    }
+
+    /// 由像素的屏幕空间重心坐标计算片元颜色
+    pub fn shade(&self, t: &Triangle, alpha: f32, beta: f32, gamma: f32) -> Color {
+        let base = match self.texture {
+            Some(texture) => {
+                let (alpha, beta, gamma) = t.perspective_correct(alpha, beta, gamma);
+                let uv = t.tex_coords_by_barycentric(alpha, beta, gamma);
+                texture.sample(uv.x, uv.y)
+            }
+            None => t.color_by_barycentric(alpha, beta, gamma),
+        };
+        self.light(t, (alpha, beta, gamma), base)
+    }
+
+    /// 在透视校正后的观察空间位置和法线处计算光照，没有设置光照时直接返回 `base`
+    fn light(&self, t: &Triangle, (alpha, beta, gamma): (f32, f32, f32), base: Color) -> Color {
+        let Some(lighting) = self.lighting else {
+            return base;
+        };
+        match self.shading {
+            ShadingMode::Flat => {
+                let [a, b, c] = t.view_pos;
+                let centroid = (a + b + c) / 3.0;
+                let mut n = (b - a).cross(&(c - a));
+                // 面法线的朝向取决于顶点顺序，按顶点法线翻转，没有顶点法线时朝向观察点
+                let reference = t.normal[0] + t.normal[1] + t.normal[2];
+                let reference = if reference == Vec3::zeros() {
+                    -centroid
+                } else {
+                    reference
+                };
+                if n.dot(&reference) < 0.0 {
+                    n = -n;
+                }
+                lighting.shade(centroid, n, base)
+            }
+            ShadingMode::Gouraud if self.texture.is_some() => {
+                modulate(base, t.color_by_barycentric(alpha, beta, gamma))
+            }
+            ShadingMode::Gouraud => base,
+            ShadingMode::Phong => {
+                let (alpha, beta, gamma) = t.perspective_correct(alpha, beta, gamma);
+                let mut p = t.view_pos_by_barycentric(alpha, beta, gamma);
+                let mut n = t.normal_by_barycentric(alpha, beta, gamma);
+                if let Some((texture, map)) = self.surface {
+                    if let Some(frame) = t.tangent_frame() {
+                        let uv = t.tex_coords_by_barycentric(alpha, beta, gamma);
+                        (p, n) = map.apply(texture, uv, p, n, frame);
+                    }
+                }
+                lighting.shade(p, n, base)
+            }
+        }
+    }
+
+    /// 对一个 2x2 像素块着色，`quad` 和 `mask` 的顺序与 [`crate::edge::EdgeTriangle::for_each_quad`] 一致。
+    ///
+    /// 纹理坐标的导数由块内相邻像素做差得到，未覆盖的像素参与求导但不着色，返回黑色。
+    pub fn shade_quad(
+        &self,
+        t: &Triangle,
+        quad: &[(f32, f32, f32); 4],
+        mask: [bool; 4],
+    ) -> [Color; 4] {
+        let Some(texture) = self.texture else {
+            return [0, 1, 2, 3].map(|i| {
+                let (alpha, beta, gamma) = quad[i];
+                if mask[i] {
+                    self.light(t, quad[i], t.color_by_barycentric(alpha, beta, gamma))
+                } else {
+                    Color::BLACK
+                }
+            });
+        };
+        let uv: [Vec2; 4] = quad.map(|(alpha, beta, gamma)| {
+            let (alpha, beta, gamma) = t.perspective_correct(alpha, beta, gamma);
+            t.tex_coords_by_barycentric(alpha, beta, gamma)
+        });
+        let ddx = uv[1] - uv[0];
+        let ddy = uv[2] - uv[0];
+        [0, 1, 2, 3].map(|i| {
+            if mask[i] {
+                self.light(t, quad[i], texture.sample_grad(uv[i].x, uv[i].y, ddx, ddy))
+            } else {
+                Color::BLACK
+            }
+        })
+    }
+}
+
+/// 逐通道相乘，alpha 取第一个颜色的
+fn modulate(a: Color, b: Color) -> Color {
+    let mul = |x: u8, y: u8| ((x as u32 * y as u32 + 127) / 255) as u8;
+    Color::new_rgba(mul(a.r, b.r), mul(a.g, b.g), mul(a.b, b.b), a.a)
+}
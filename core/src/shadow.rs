@@ -0,0 +1,188 @@
+use crate::edge::EdgeTriangle;
+use crate::lighting::Light;
+use crate::transform;
+use nalgebra_glm::{vec3, vec4, Mat4, Vec3, Vec4};
+use std::sync::Arc;
+
+/// 从光源看过去的深度图，用于方向光和聚光灯的阴影。
+///
+/// 先用 [`crate::rasterizer::Rasterizer::begin_shadow_pass`] 把场景的深度渲染进来，
+/// 再在正常绘制时把片元变换到光源空间与之比较。点光源需要六个方向的深度图，暂不支持。
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShadowMap {
+    /// 深度图的边长（纹素）
+    resolution: u32,
+    /// 光源裁剪空间的深度映射到 [0, 1]，按行从下到上存放
+    depth: Vec<f32>,
+    /// 世界空间到光源裁剪空间
+    light_matrix: Mat4,
+    /// 需要投射和接收阴影的场景范围，世界空间中的包围球
+    pub center: Vec3,
+    pub radius: f32,
+    /// 常数深度偏移（深度映射到 [0, 1]），避免表面被自己遮挡（shadow acne）。
+    /// 比较时还会按表面的倾斜程度和 PCF 半径增加偏移
+    pub bias: f32,
+    /// 百分比渐近过滤（PCF）的半径（纹素），在 `(2r + 1)^2` 个纹素上比较后取平均，0 时阴影边缘是硬的
+    pub pcf_radius: u32,
+}
+
+impl ShadowMap {
+    /// 覆盖以 `center` 为中心、`radius` 为半径的场景的深度图
+    pub fn new(resolution: u32, center: Vec3, radius: f32) -> Self {
+        Self {
+            resolution,
+            depth: vec![f32::MAX; (resolution * resolution) as usize],
+            light_matrix: Mat4::identity(),
+            center,
+            radius,
+            bias: 0.002,
+            pcf_radius: 1,
+        }
+    }
+
+    pub fn resolution(&self) -> u32 {
+        self.resolution
+    }
+
+    /// 每个纹素的深度，没有被覆盖的纹素为 `f32::MAX`
+    pub fn depth(&self) -> &[f32] {
+        &self.depth
+    }
+
+    /// 世界空间到光源裁剪空间的变换
+    pub fn light_matrix(&self) -> Mat4 {
+        self.light_matrix
+    }
+
+    /// 按光源的位置和方向重新计算光源空间并清空深度。
+    ///
+    /// 方向光使用包住整个包围球的正交投影，聚光灯使用视角为外锥角两倍的透视投影。
+    /// 点光源返回 `false`，深度图保持为空，不产生阴影
+    pub fn look_from(&mut self, light: &Light) -> bool {
+        self.depth.fill(f32::MAX);
+        let radius = self.radius.max(f32::EPSILON);
+        let (view, projection) = match *light {
+            Light::Directional { direction, .. } => {
+                let Some(d) = direction.try_normalize(f32::EPSILON) else {
+                    return false;
+                };
+                let eye = self.center - d * (2.0 * radius);
+                (
+                    transform::look_at(eye, self.center, up_for(d)),
+                    transform::orthographic(-radius, radius, -radius, radius, radius, 3.0 * radius),
+                )
+            }
+            Light::Spot {
+                position,
+                direction,
+                outer_angle,
+                ..
+            } => {
+                let Some(d) = direction.try_normalize(f32::EPSILON) else {
+                    return false;
+                };
+                let far = (self.center - position).norm() + radius;
+                let near = (far - 2.0 * radius).max(far * 1e-3);
+                let fov = (2.0 * outer_angle).clamp(1f32.to_radians(), 179f32.to_radians());
+                (
+                    transform::look_at(position, position + d, up_for(d)),
+                    transform::perspective(fov, 1.0, near, far),
+                )
+            }
+            Light::Point { .. } => return false,
+        };
+        self.light_matrix = projection * view;
+        true
+    }
+
+    /// 把光源裁剪空间中的三角形写入深度图，有顶点在光源后面的三角形被丢弃
+    pub(crate) fn draw_triangle(&mut self, clip: [Vec4; 3]) {
+        if self.resolution == 0 || clip.iter().any(|v| v.w <= 0.0) {
+            return;
+        }
+        let size = self.resolution as f32;
+        let v = clip.map(|c| {
+            let ndc = c.xyz() / c.w;
+            vec4(
+                (ndc.x + 1.0) * 0.5 * size,
+                (ndc.y + 1.0) * 0.5 * size,
+                (ndc.z + 1.0) * 0.5,
+                1.0,
+            )
+        });
+        let Some(edge) = EdgeTriangle::new(&v) else {
+            return;
+        };
+        let max = self.resolution - 1;
+        edge.for_each_pixel(0, 0, max, max, |x, y, (alpha, beta, gamma)| {
+            // 投影后的深度在屏幕空间中是线性的，不需要透视校正
+            let z = alpha * v[0].z + beta * v[1].z + gamma * v[2].z;
+            let index = (y * self.resolution + x) as usize;
+            if z < self.depth[index] {
+                self.depth[index] = z;
+            }
+        });
+    }
+
+    /// 世界空间中的点 `p` 没有被遮挡的比例，`cos` 是法线与光线夹角的余弦，用于调整深度偏移
+    pub fn visibility(&self, p: Vec3, cos: f32) -> f32 {
+        self.lookup(self.light_matrix * p.push(1.0), cos)
+    }
+
+    fn lookup(&self, clip: Vec4, cos: f32) -> f32 {
+        if clip.w <= 0.0 || self.resolution == 0 {
+            return 1.0;
+        }
+        let ndc = clip.xyz() / clip.w;
+        let z = (ndc.z + 1.0) * 0.5;
+        if z > 1.0 {
+            return 1.0;
+        }
+        // 正交投影中一个纹素的宽度约等于 1 / resolution 的深度，倾斜的表面上相邻纹素的深度差
+        // 按 tan 增大，PCF 比较的纹素离采样点越远，深度差越大
+        let cos = cos.clamp(0.1, 1.0);
+        let tan = (1.0 - cos * cos).sqrt() / cos;
+        let bias = self.bias + tan * (self.pcf_radius + 1) as f32 / self.resolution as f32;
+
+        let size = self.resolution as f32;
+        let x = ((ndc.x + 1.0) * 0.5 * size).floor() as i64;
+        let y = ((ndc.y + 1.0) * 0.5 * size).floor() as i64;
+        let r = self.pcf_radius as i64;
+        let mut lit = 0;
+        for sy in y - r..=y + r {
+            for sx in x - r..=x + r {
+                let inside = (0..self.resolution as i64).contains(&sx)
+                    && (0..self.resolution as i64).contains(&sy);
+                // 深度图以外的区域不产生阴影
+                if !inside || z - bias <= self.depth[(sy * self.resolution as i64 + sx) as usize] {
+                    lit += 1;
+                }
+            }
+        }
+        lit as f32 / ((2 * r + 1) * (2 * r + 1)) as f32
+    }
+}
+
+/// 与光线方向不平行的上方向
+fn up_for(direction: Vec3) -> Vec3 {
+    if direction.y.abs() > 0.99 {
+        vec3(0.0, 0.0, 1.0)
+    } else {
+        vec3(0.0, 1.0, 0.0)
+    }
+}
+
+/// 在观察空间中使用的阴影贴图
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shadow {
+    pub map: Arc<ShadowMap>,
+    /// 观察空间到光源裁剪空间
+    pub matrix: Mat4,
+}
+
+impl Shadow {
+    /// 观察空间中的点 `p` 没有被遮挡的比例，见 [`ShadowMap::visibility`]
+    pub fn visibility(&self, p: Vec3, cos: f32) -> f32 {
+        self.map.lookup(self.matrix * p.push(1.0), cos)
+    }
+}
@@ -0,0 +1,164 @@
+use crate::mesh::{Mesh, MeshError};
+use nalgebra_glm::{vec3, Vec3};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+/// STL 文件的数据格式
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum StlFormat {
+    Ascii,
+    #[default]
+    Binary,
+}
+
+/// 二进制 STL 的文件头长度，之后是 4 字节的三角形数量
+const HEADER_LEN: usize = 80;
+/// 每个三角形占 50 字节：法线、三个顶点和 2 字节属性
+const TRIANGLE_LEN: usize = 50;
+
+fn format_error(message: impl Into<String>) -> MeshError {
+    MeshError::Format(message.into())
+}
+
+/// 读取 STL 文件
+pub fn load(path: impl AsRef<Path>) -> Result<Mesh, MeshError> {
+    read(&std::fs::read(path)?)
+}
+
+/// 解析 ASCII 或二进制的 STL 数据。
+///
+/// STL 中每个三角形单独存储顶点，位置完全相同的顶点会合并为一个，
+/// 文件中的面法线不会被读取，需要时用 [`Mesh::compute_normals`] 计算。
+pub fn read(data: &[u8]) -> Result<Mesh, MeshError> {
+    // ASCII 文件也以 "solid" 开头，按长度是否吻合判断是否为二进制
+    if data.len() >= HEADER_LEN + 4 {
+        let count = u32::from_le_bytes(data[HEADER_LEN..HEADER_LEN + 4].try_into().unwrap());
+        if data.len() == HEADER_LEN + 4 + count as usize * TRIANGLE_LEN {
+            return Ok(read_binary(&data[HEADER_LEN + 4..]));
+        }
+    }
+    if data.trim_ascii_start().starts_with(b"solid") {
+        let text = std::str::from_utf8(data).map_err(|_| format_error("invalid ASCII STL"))?;
+        return read_ascii(text);
+    }
+    Err(format_error("not an STL file"))
+}
+
+/// 按位置合并顶点
+#[derive(Default)]
+struct Builder {
+    mesh: Mesh,
+    vertices: HashMap<[u32; 3], u32>,
+}
+
+impl Builder {
+    fn vertex(&mut self, p: Vec3) -> u32 {
+        // -0.0 与 0.0 视为同一个位置
+        let key = [p.x, p.y, p.z].map(|x| (x + 0.0).to_bits());
+        *self.vertices.entry(key).or_insert_with(|| {
+            self.mesh.positions.push(p);
+            self.mesh.positions.len() as u32 - 1
+        })
+    }
+
+    fn triangle(&mut self, [a, b, c]: [Vec3; 3]) {
+        let triangle = [self.vertex(a), self.vertex(b), self.vertex(c)];
+        self.mesh.indices.push(triangle);
+    }
+}
+
+fn read_binary(data: &[u8]) -> Mesh {
+    let mut builder = Builder::default();
+    for triangle in data.chunks_exact(TRIANGLE_LEN) {
+        let float = |i: usize| f32::from_le_bytes(triangle[i * 4..i * 4 + 4].try_into().unwrap());
+        // 跳过开头的法线
+        let vertex = |i: usize| vec3(float(3 + i * 3), float(4 + i * 3), float(5 + i * 3));
+        builder.triangle([vertex(0), vertex(1), vertex(2)]);
+    }
+    builder.mesh
+}
+
+fn read_ascii(text: &str) -> Result<Mesh, MeshError> {
+    let mut builder = Builder::default();
+    let mut vertices = Vec::with_capacity(3);
+    for (number, line) in text.lines().enumerate() {
+        let error = |message: &str| format_error(format!("line {}: {}", number + 1, message));
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("vertex") => {
+                let mut coord = || -> Result<f32, MeshError> {
+                    tokens
+                        .next()
+                        .and_then(|t| t.parse().ok())
+                        .ok_or_else(|| error("invalid vertex"))
+                };
+                vertices.push(vec3(coord()?, coord()?, coord()?));
+            }
+            Some("outer") => vertices.clear(),
+            Some("endloop") => {
+                let triangle: [Vec3; 3] = vertices
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| error("facet must have exactly 3 vertices"))?;
+                builder.triangle(triangle);
+            }
+            _ => {}
+        }
+    }
+    Ok(builder.mesh)
+}
+
+/// 把网格保存为 STL 文件
+pub fn save(mesh: &Mesh, path: impl AsRef<Path>, format: StlFormat) -> Result<(), MeshError> {
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    write(mesh, &mut file, format)?;
+    file.flush()?;
+    Ok(())
+}
+
+/// 按指定格式写出网格的三角形，面法线由顶点位置计算。
+///
+/// STL 只保存位置，顶点颜色、法线和纹理坐标都会丢失。
+pub fn write(mesh: &Mesh, w: &mut impl Write, format: StlFormat) -> std::io::Result<()> {
+    let triangles = mesh.indices.iter().map(|&[a, b, c]| {
+        let [a, b, c] = [a, b, c].map(|i| mesh.positions[i as usize]);
+        let normal = (b - a)
+            .cross(&(c - a))
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vec3::zeros);
+        (normal, [a, b, c])
+    });
+
+    match format {
+        StlFormat::Ascii => {
+            writeln!(w, "solid mesh")?;
+            for (n, vertices) in triangles {
+                writeln!(w, "  facet normal {} {} {}", n.x, n.y, n.z)?;
+                writeln!(w, "    outer loop")?;
+                for v in vertices {
+                    writeln!(w, "      vertex {} {} {}", v.x, v.y, v.z)?;
+                }
+                writeln!(w, "    endloop")?;
+                writeln!(w, "  endfacet")?;
+            }
+            writeln!(w, "endsolid mesh")?;
+        }
+        StlFormat::Binary => {
+            // 文件头不能以 "solid" 开头，否则可能被误认为 ASCII 格式
+            let mut header = [0u8; HEADER_LEN];
+            header[..12].copy_from_slice(b"binary mesh\0");
+            w.write_all(&header)?;
+            w.write_all(&(mesh.indices.len() as u32).to_le_bytes())?;
+            for (n, vertices) in triangles {
+                for v in std::iter::once(n).chain(vertices) {
+                    for x in v.iter() {
+                        w.write_all(&x.to_le_bytes())?;
+                    }
+                }
+                w.write_all(&[0, 0])?;
+            }
+        }
+    }
+    Ok(())
+}
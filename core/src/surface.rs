@@ -0,0 +1,97 @@
+use crate::texture::Texture;
+use nalgebra_glm::{vec3, Mat3, Vec2, Vec3};
+
+/// 表面细节贴图的用法，与作业 3 中的 normal、bump 和 displacement 着色器对应
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SurfaceMap {
+    /// 切线空间法线贴图，RGB 从 [0, 1] 映射到 [-1, 1]，绿色通道沿 v 增大的方向
+    Normal,
+    /// 高度图凹凸贴图，只扰动法线。高度取 RGB 的平均值，范围 [0, 1]
+    Bump {
+        /// 相邻纹素的高度差到切线空间法线偏移的比例
+        strength: f32,
+    },
+    /// 高度图位移，着色点沿法线移动 `depth * h`，法线与凹凸贴图相同。
+    ///
+    /// 只改变计算光照的位置，不改变三角形的几何形状和深度
+    Displacement { strength: f32, depth: f32 },
+}
+
+impl SurfaceMap {
+    /// `tbn` 的三列是正交化之后观察空间的切线、副切线和法线
+    fn perturb(&self, texture: &Texture, uv: Vec2, p: Vec3, tbn: &Mat3) -> (Vec3, Vec3) {
+        let n = tbn.column(2).into_owned();
+        match *self {
+            SurfaceMap::Normal => {
+                let c = texture.sample(uv.x, uv.y);
+                let local =
+                    vec3(c.r as f32, c.g as f32, c.b as f32) / 255.0 * 2.0 - vec3(1.0, 1.0, 1.0);
+                (p, tbn * local)
+            }
+            SurfaceMap::Bump { strength } => (p, tbn * height_gradient(texture, uv, strength)),
+            SurfaceMap::Displacement { strength, depth } => (
+                p + n * (depth * height(texture, uv)),
+                tbn * height_gradient(texture, uv, strength),
+            ),
+        }
+    }
+
+    /// 用贴图扰动观察空间中的着色点 `p` 和法线 `n`，返回新的着色点和法线。
+    ///
+    /// `tangent` 和 `bitangent` 是纹理坐标 u、v 增大的方向，不需要与法线正交；
+    /// 切线退化（纹理坐标没有展开）时不做扰动
+    pub fn apply(
+        &self,
+        texture: &Texture,
+        uv: Vec2,
+        p: Vec3,
+        n: Vec3,
+        (tangent, bitangent): (Vec3, Vec3),
+    ) -> (Vec3, Vec3) {
+        let Some(tbn) = orthonormal_frame(n, tangent, bitangent) else {
+            return (p, n);
+        };
+        self.perturb(texture, uv, p, &tbn)
+    }
+}
+
+/// 高度图在 `uv` 处的高度，范围 [0, 1]
+fn height(texture: &Texture, uv: Vec2) -> f32 {
+    let c = texture.sample(uv.x, uv.y);
+    (c.r as f32 + c.g as f32 + c.b as f32) / (3.0 * 255.0)
+}
+
+/// 由相邻纹素的高度差得到切线空间中的法线 `(-dU, -dV, 1)`
+fn height_gradient(texture: &Texture, uv: Vec2, strength: f32) -> Vec3 {
+    let (du, dv) = (1.0 / texture.width() as f32, 1.0 / texture.height() as f32);
+    let h = height(texture, uv);
+    let d_u = strength * (height(texture, uv + Vec2::new(du, 0.0)) - h);
+    let d_v = strength * (height(texture, uv + Vec2::new(0.0, dv)) - h);
+    vec3(-d_u, -d_v, 1.0)
+}
+
+/// 用 Gram-Schmidt 正交化得到列为 T、B、N 的矩阵，B 的方向保持与 `bitangent` 一致
+fn orthonormal_frame(n: Vec3, tangent: Vec3, bitangent: Vec3) -> Option<Mat3> {
+    let n = n.try_normalize(f32::EPSILON)?;
+    let t = (tangent - n * n.dot(&tangent)).try_normalize(f32::EPSILON)?;
+    let mut b = n.cross(&t);
+    if b.dot(&bitangent) < 0.0 {
+        b = -b;
+    }
+    Some(Mat3::from_columns(&[t, b, n]))
+}
+
+/// 由三个顶点的坐标和纹理坐标求三角形的切线和副切线，即坐标对 u、v 的偏导数。
+///
+/// 纹理坐标退化（三个点共线）时返回 `None`
+pub fn tangent_frame(positions: [Vec3; 3], tex_coords: [Vec2; 3]) -> Option<(Vec3, Vec3)> {
+    let (e1, e2) = (positions[1] - positions[0], positions[2] - positions[0]);
+    let (d1, d2) = (tex_coords[1] - tex_coords[0], tex_coords[2] - tex_coords[0]);
+    let det = d1.x * d2.y - d2.x * d1.y;
+    if det.abs() <= f32::EPSILON * (d1.norm_squared() + d2.norm_squared()) {
+        return None;
+    }
+    let tangent = (e1 * d2.y - e2 * d1.y) / det;
+    let bitangent = (e2 * d1.x - e1 * d2.x) / det;
+    Some((tangent, bitangent))
+}
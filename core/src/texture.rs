@@ -0,0 +1,384 @@
+use crate::color::Color;
+use nalgebra_glm::{vec2, Vec2};
+use std::path::Path;
+
+/// 纹理过滤方式
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum Filter {
+    /// 取最近的纹素
+    Nearest,
+    /// 对周围 4 个纹素做双线性插值
+    #[default]
+    Bilinear,
+    /// 由纹理坐标的导数选择 mipmap 层级，在相邻两层上分别双线性采样后再线性插值
+    Trilinear,
+    /// 椭圆加权平均（EWA）各向异性过滤，沿像素足迹的长轴在较精细的层级上多次采样
+    Anisotropic,
+}
+
+impl Filter {
+    /// 是否需要纹理坐标的屏幕空间导数来选择 mipmap 层级
+    pub fn uses_mipmaps(self) -> bool {
+        matches!(self, Filter::Trilinear | Filter::Anisotropic)
+    }
+}
+
+/// 纹理坐标超出 [0, 1] 时的处理方式
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum WrapMode {
+    /// 重复平铺
+    #[default]
+    Repeat,
+    /// 取边缘纹素
+    ClampToEdge,
+    /// 镜像平铺
+    MirroredRepeat,
+}
+
+impl WrapMode {
+    /// 把纹素坐标映射到 `[0, size)` 内
+    fn apply(self, i: i64, size: i64) -> i64 {
+        match self {
+            WrapMode::Repeat => i.rem_euclid(size),
+            WrapMode::ClampToEdge => i.clamp(0, size - 1),
+            WrapMode::MirroredRepeat => {
+                let i = i.rem_euclid(2 * size);
+                if i < size {
+                    i
+                } else {
+                    2 * size - 1 - i
+                }
+            }
+        }
+    }
+}
+
+/// mipmap 中的一层，纹素按图像的行从上到下存放
+#[derive(Debug, Clone)]
+struct Level {
+    width: u32,
+    height: u32,
+    data: Vec<Color>,
+}
+
+impl Level {
+    fn texel(&self, wrap_mode: WrapMode, x: i64, y: i64) -> Color {
+        if self.data.is_empty() {
+            return Color::BLACK;
+        }
+        let x = wrap_mode.apply(x, self.width as i64);
+        let y = wrap_mode.apply(y, self.height as i64);
+        self.data[(y * self.width as i64 + x) as usize]
+    }
+
+    /// 纹理坐标对应的纹素坐标，纹素 `(i, j)` 的中心位于 `(i + 0.5, j + 0.5)`
+    fn position(&self, u: f32, v: f32) -> (f32, f32) {
+        (u * self.width as f32, (1.0 - v) * self.height as f32)
+    }
+
+    fn bilinear(&self, wrap_mode: WrapMode, u: f32, v: f32) -> [f32; 4] {
+        let (x, y) = self.position(u, v);
+        let (x, y) = (x - 0.5, y - 0.5);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (s, t) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let (x1, y1) = (x0.saturating_add(1), y0.saturating_add(1));
+
+        let texel = |x, y| to_rgba(self.texel(wrap_mode, x, y));
+        let top = lerp(texel(x0, y0), texel(x1, y0), s);
+        let bottom = lerp(texel(x0, y1), texel(x1, y1), s);
+        lerp(top, bottom, t)
+    }
+
+    /// 以 `(u, v)` 为中心、由 `major`、`minor` 两个轴（本层纹素单位）张成的椭圆内做高斯加权平均
+    fn ewa(&self, wrap_mode: WrapMode, u: f32, v: f32, major: Vec2, minor: Vec2) -> [f32; 4] {
+        let (x, y) = self.position(u, v);
+        let (x, y) = (x - 0.5, y - 0.5);
+
+        // 椭圆方程 A * x^2 + B * x * y + C * y^2 = 1，两个轴各加一个纹素避免椭圆漏掉纹素中心
+        let a = major.y * major.y + minor.y * minor.y + 1.0;
+        let b = -2.0 * (major.x * major.y + minor.x * minor.y);
+        let c = major.x * major.x + minor.x * minor.x + 1.0;
+        let inv_f = 1.0 / (a * c - b * b * 0.25);
+        let (a, b, c) = (a * inv_f, b * inv_f, c * inv_f);
+
+        // 椭圆的包围盒
+        let det = 4.0 * a * c - b * b;
+        let half_width = 2.0 * (det * c).sqrt() / det;
+        let half_height = 2.0 * (det * a).sqrt() / det;
+        let x0 = (x - half_width).ceil() as i64;
+        let x1 = (x + half_width).floor() as i64;
+        let y0 = (y - half_height).ceil() as i64;
+        let y1 = (y + half_height).floor() as i64;
+
+        let mut sum = [0.0; 4];
+        let mut weight_sum = 0.0;
+        for j in y0..=y1 {
+            let dy = j as f32 - y;
+            for i in x0..=x1 {
+                let dx = i as f32 - x;
+                let r2 = a * dx * dx + b * dx * dy + c * dy * dy;
+                if r2 < 1.0 {
+                    let weight = (-2.0 * r2).exp() - (-2.0f32).exp();
+                    let texel = to_rgba(self.texel(wrap_mode, i, j));
+                    for k in 0..4 {
+                        sum[k] += texel[k] * weight;
+                    }
+                    weight_sum += weight;
+                }
+            }
+        }
+        if weight_sum <= 0.0 {
+            return self.bilinear(wrap_mode, u, v);
+        }
+        sum.map(|x| x / weight_sum)
+    }
+
+    /// 用盒式滤波缩小一半生成下一层，奇数尺寸时相邻的源纹素范围会有重叠
+    fn downsample(&self) -> Self {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        let range = |i: u32, from: u32, to: u32| {
+            let begin = i * from / to;
+            let end = ((i + 1) * from).div_ceil(to);
+            begin..end
+        };
+
+        let mut data = Vec::with_capacity((width * height) as usize);
+        for j in 0..height {
+            for i in 0..width {
+                let mut sum = [0.0; 4];
+                let mut count = 0.0;
+                for y in range(j, self.height, height) {
+                    for x in range(i, self.width, width) {
+                        let texel = to_rgba(self.data[(y * self.width + x) as usize]);
+                        for k in 0..4 {
+                            sum[k] += texel[k];
+                        }
+                        count += 1.0;
+                    }
+                }
+                data.push(from_rgba(sum.map(|x| x / count)));
+            }
+        }
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+}
+
+/// RGBA8 纹理，纹素按图像的行从上到下存放。
+///
+/// 纹理坐标的原点在图像左下角，`u` 向右、`v` 向上。
+/// 调用 [`Texture::generate_mipmaps`] 之后三线性和各向异性过滤才会用到较粗的层级。
+#[derive(Debug, Clone)]
+pub struct Texture {
+    /// levels[0] 是原图，之后每层的宽高减半，直到 1x1
+    levels: Vec<Level>,
+    filter: Filter,
+    wrap_mode: WrapMode,
+    max_anisotropy: f32,
+}
+
+impl Texture {
+    pub fn new(width: u32, height: u32, data: Vec<Color>) -> Self {
+        assert_eq!(
+            data.len(),
+            (width * height) as usize,
+            "texture data does not match its size"
+        );
+        Self {
+            levels: vec![Level {
+                width,
+                height,
+                data,
+            }],
+            filter: Filter::default(),
+            wrap_mode: WrapMode::default(),
+            max_anisotropy: 16.0,
+        }
+    }
+
+    /// 用 `image` 读取 PNG、JPEG 等格式的图片
+    pub fn load(file_path: impl AsRef<Path>) -> image::ImageResult<Self> {
+        let image = image::open(file_path)?.to_rgba8();
+        let (width, height) = image.dimensions();
+        let data = bytemuck::cast_slice(image.as_raw()).to_vec();
+        Ok(Self::new(width, height, data))
+    }
+
+    pub fn width(&self) -> u32 {
+        self.levels[0].width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.levels[0].height
+    }
+
+    pub fn data(&self) -> &[Color] {
+        &self.levels[0].data
+    }
+
+    pub fn filter(&self) -> Filter {
+        self.filter
+    }
+
+    pub fn set_filter(&mut self, filter: Filter) {
+        self.filter = filter;
+    }
+
+    pub fn wrap_mode(&self) -> WrapMode {
+        self.wrap_mode
+    }
+
+    pub fn set_wrap_mode(&mut self, wrap_mode: WrapMode) {
+        self.wrap_mode = wrap_mode;
+    }
+
+    pub fn max_anisotropy(&self) -> f32 {
+        self.max_anisotropy
+    }
+
+    /// 各向异性过滤时像素足迹长轴与短轴之比的上限，更细长的足迹会被加宽，以限制采样次数
+    pub fn set_max_anisotropy(&mut self, max_anisotropy: f32) {
+        self.max_anisotropy = max_anisotropy.max(1.0);
+    }
+
+    /// 由原图生成完整的 mipmap 链，原图修改后需要重新生成
+    pub fn generate_mipmaps(&mut self) {
+        self.levels.truncate(1);
+        if self.levels[0].data.is_empty() {
+            return;
+        }
+        loop {
+            let last = &self.levels[self.levels.len() - 1];
+            if last.width == 1 && last.height == 1 {
+                break;
+            }
+            let next = last.downsample();
+            self.levels.push(next);
+        }
+    }
+
+    /// mipmap 的层数，没有生成 mipmap 时为 1
+    pub fn mip_levels(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// 第 `level` 层的宽、高和纹素
+    pub fn mip_level(&self, level: usize) -> Option<(u32, u32, &[Color])> {
+        self.levels
+            .get(level)
+            .map(|l| (l.width, l.height, &l.data[..]))
+    }
+
+    /// 读取一个纹素，`x`、`y` 为图像坐标（第 0 行在最上方），越界时按环绕方式处理
+    pub fn texel(&self, x: i64, y: i64) -> Color {
+        self.levels[0].texel(self.wrap_mode, x, y)
+    }
+
+    /// 按纹理的过滤和环绕方式在 `(u, v)` 处采样。
+    ///
+    /// 没有导数时无法选择 mipmap 层级，三线性和各向异性过滤都退化为在原图上双线性采样。
+    pub fn sample(&self, u: f32, v: f32) -> Color {
+        let base = &self.levels[0];
+        match self.filter {
+            Filter::Nearest => {
+                let (x, y) = base.position(u, v);
+                base.texel(self.wrap_mode, x.floor() as i64, y.floor() as i64)
+            }
+            _ => from_rgba(base.bilinear(self.wrap_mode, u, v)),
+        }
+    }
+
+    /// 带纹理坐标屏幕空间导数的采样，`ddx`、`ddy` 是相邻像素之间纹理坐标的差。
+    ///
+    /// 最近点和双线性过滤忽略导数，只在原图上采样。
+    pub fn sample_grad(&self, u: f32, v: f32, ddx: Vec2, ddy: Vec2) -> Color {
+        // 换算成原图的纹素单位，图像的 y 轴向下
+        let size = vec2(self.width() as f32, -(self.height() as f32));
+        let (dx, dy) = (ddx.component_mul(&size), ddy.component_mul(&size));
+        if !(dx.x.is_finite() && dx.y.is_finite() && dy.x.is_finite() && dy.y.is_finite()) {
+            return self.sample(u, v);
+        }
+        match self.filter {
+            Filter::Nearest | Filter::Bilinear => self.sample(u, v),
+            Filter::Trilinear => {
+                let lod = dx.norm().max(dy.norm()).log2();
+                from_rgba(self.trilinear(u, v, lod))
+            }
+            Filter::Anisotropic => from_rgba(self.anisotropic(u, v, dx, dy)),
+        }
+    }
+
+    /// 在 `lod` 两侧的两层上双线性采样后线性插值
+    fn trilinear(&self, u: f32, v: f32, lod: f32) -> [f32; 4] {
+        let last = self.levels.len() - 1;
+        let lod = lod.clamp(0.0, last as f32);
+        let level = lod.floor() as usize;
+        let fine = self.levels[level].bilinear(self.wrap_mode, u, v);
+        if level == last {
+            return fine;
+        }
+        let coarse = self.levels[level + 1].bilinear(self.wrap_mode, u, v);
+        lerp(fine, coarse, lod - level as f32)
+    }
+
+    fn anisotropic(&self, u: f32, v: f32, dx: Vec2, dy: Vec2) -> [f32; 4] {
+        let (major, mut minor) = if dx.norm_squared() < dy.norm_squared() {
+            (dy, dx)
+        } else {
+            (dx, dy)
+        };
+        let major_length = major.norm();
+        let mut minor_length = minor.norm();
+
+        // 限制足迹的离心率，加宽短轴会让选中的层级变粗
+        if minor_length * self.max_anisotropy < major_length && minor_length > 0.0 {
+            let scale = major_length / (minor_length * self.max_anisotropy);
+            minor *= scale;
+            minor_length *= scale;
+        }
+        if minor_length == 0.0 {
+            return self.levels[0].bilinear(self.wrap_mode, u, v);
+        }
+
+        // 短轴决定层级，使短轴在选中的层上约为一个纹素
+        let last = self.levels.len() - 1;
+        let lod = minor_length.log2().max(0.0);
+        if lod >= last as f32 {
+            return self.levels[last].bilinear(self.wrap_mode, u, v);
+        }
+        let level = lod.floor() as usize;
+        let ewa = |level: usize| {
+            let l = &self.levels[level];
+            let scale = vec2(
+                l.width as f32 / self.width() as f32,
+                l.height as f32 / self.height() as f32,
+            );
+            l.ewa(
+                self.wrap_mode,
+                u,
+                v,
+                major.component_mul(&scale),
+                minor.component_mul(&scale),
+            )
+        };
+        lerp(ewa(level), ewa(level + 1), lod - level as f32)
+    }
+}
+
+fn to_rgba(c: Color) -> [f32; 4] {
+    [c.r as f32, c.g as f32, c.b as f32, c.a as f32]
+}
+
+fn from_rgba(c: [f32; 4]) -> Color {
+    let channel = |x: f32| x.round().clamp(0.0, 255.0) as u8;
+    Color::new_rgba(channel(c[0]), channel(c[1]), channel(c[2]), channel(c[3]))
+}
+
+fn lerp(a: [f32; 4], b: [f32; 4], t: f32) -> [f32; 4] {
+    [0, 1, 2, 3].map(|i| a[i] + (b[i] - a[i]) * t)
+}
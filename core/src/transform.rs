@@ -0,0 +1,297 @@
+//! 模型变换、观察变换和投影变换，角度都以弧度为单位，矩阵作用于列向量。
+
+use nalgebra_glm::{vec3, vec4, Mat3, Mat4, Quat, Vec3};
+
+/// 平移
+pub fn translation(t: Vec3) -> Mat4 {
+    #[rustfmt::skip]
+    let m = Mat4::new(
+        1.0, 0.0, 0.0, t.x,
+        0.0, 1.0, 0.0, t.y,
+        0.0, 0.0, 1.0, t.z,
+        0.0, 0.0, 0.0, 1.0,
+    );
+    m
+}
+
+/// 沿三个坐标轴缩放
+pub fn scaling(s: Vec3) -> Mat4 {
+    #[rustfmt::skip]
+    let m = Mat4::new(
+        s.x, 0.0, 0.0, 0.0,
+        0.0, s.y, 0.0, 0.0,
+        0.0, 0.0, s.z, 0.0,
+        0.0, 0.0, 0.0, 1.0,
+    );
+    m
+}
+
+/// 绕 x 轴旋转
+pub fn rotation_x(angle: f32) -> Mat4 {
+    let (s, c) = angle.sin_cos();
+    #[rustfmt::skip]
+    let m = Mat4::new(
+        1.0, 0.0, 0.0, 0.0,
+        0.0, c,   -s,  0.0,
+        0.0, s,   c,   0.0,
+        0.0, 0.0, 0.0, 1.0,
+    );
+    m
+}
+
+/// 绕 y 轴旋转
+pub fn rotation_y(angle: f32) -> Mat4 {
+    let (s, c) = angle.sin_cos();
+    #[rustfmt::skip]
+    let m = Mat4::new(
+        c,   0.0, s,   0.0,
+        0.0, 1.0, 0.0, 0.0,
+        -s,  0.0, c,   0.0,
+        0.0, 0.0, 0.0, 1.0,
+    );
+    m
+}
+
+/// 绕 z 轴旋转
+///
+/// ```text
+/// Rz(θ) = | cosθ -sinθ  0 0 |
+///         | sinθ  cosθ  0 0 |
+///         |  0      0   1 0 |
+///         |  0      0   0 1 |
+/// ```
+pub fn rotation_z(angle: f32) -> Mat4 {
+    let (s, c) = angle.sin_cos();
+    #[rustfmt::skip]
+    let m = Mat4::new(
+        c,   -s,  0.0, 0.0,
+        s,   c,   0.0, 0.0,
+        0.0, 0.0, 1.0, 0.0,
+        0.0, 0.0, 0.0, 1.0,
+    );
+    m
+}
+
+/// 绕过原点的任意轴 `axis` 旋转 `angle`，`axis` 不需要是单位向量
+///
+/// # 罗德里格斯旋转公式
+///
+/// 给定单位旋转轴 v = (v_x, v_y, v_z) 和旋转角度 θ，
+/// 旋转矩阵 R 可以通过罗德里格斯公式计算得到：
+///
+/// R = I + sin(θ)K + (1 - cos(θ))K^2
+///
+/// 其中，I 是单位矩阵，K 是根据旋转轴 v 构造的斜对称矩阵：
+///
+/// ```text
+/// K = |  0   -v_z  v_y |
+///     | v_z   0   -v_x |
+///     |-v_y  v_x   0   |
+/// ```
+pub fn rotation(angle: f32, axis: Vec3) -> Mat4 {
+    let v = axis.normalize();
+    let (s, c) = angle.sin_cos();
+    #[rustfmt::skip]
+    let k = Mat3::new(
+        0.0,  -v.z, v.y,
+        v.z,  0.0,  -v.x,
+        -v.y, v.x,  0.0,
+    );
+    let r = Mat3::identity() + k * s + k * k * (1.0 - c);
+    r.to_homogeneous()
+}
+
+/// 欧拉角旋转，依次绕 x、y、z 轴旋转 `angles.x`、`angles.y`、`angles.z`（绕固定的世界坐标轴），
+/// 即 `Rz * Ry * Rx`
+pub fn euler_xyz(angles: Vec3) -> Mat4 {
+    rotation_z(angles.z) * rotation_y(angles.y) * rotation_x(angles.x)
+}
+
+/// 单位四元数 `q` 表示的旋转
+pub fn rotation_from_quat(q: &Quat) -> Mat4 {
+    let (w, x, y, z) = (q.w, q.i, q.j, q.k);
+    #[rustfmt::skip]
+    let m = Mat4::new(
+        1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y - w * z),       2.0 * (x * z + w * y),       0.0,
+        2.0 * (x * y + w * z),       1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z - w * x),       0.0,
+        2.0 * (x * z - w * y),       2.0 * (y * z + w * x),       1.0 - 2.0 * (x * x + y * y), 0.0,
+        0.0,                         0.0,                         0.0,                         1.0,
+    );
+    m
+}
+
+/// 绕 `axis` 旋转 `angle` 的单位四元数
+pub fn quat_from_axis_angle(angle: f32, axis: Vec3) -> Quat {
+    let v = axis.normalize();
+    let (s, c) = (angle / 2.0).sin_cos();
+    Quat::new(c, v.x * s, v.y * s, v.z * s)
+}
+
+/// 与 [`euler_xyz`] 相同的旋转
+pub fn quat_from_euler_xyz(angles: Vec3) -> Quat {
+    quat_from_axis_angle(angles.z, Vec3::z())
+        * quat_from_axis_angle(angles.y, Vec3::y())
+        * quat_from_axis_angle(angles.x, Vec3::x())
+}
+
+/// 切变，`x' = x + xy * y + xz * z`，其余两个坐标同理
+pub fn shear(xy: f32, xz: f32, yx: f32, yz: f32, zx: f32, zy: f32) -> Mat4 {
+    #[rustfmt::skip]
+    let m = Mat4::new(
+        1.0, xy,  xz,  0.0,
+        yx,  1.0, yz,  0.0,
+        zx,  zy,  1.0, 0.0,
+        0.0, 0.0, 0.0, 1.0,
+    );
+    m
+}
+
+/// 观察矩阵：相机位于 `eye`，看向 `target`，`up` 大致为屏幕上方
+pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Mat4 {
+    // 相机坐标系的三个轴，相机看向 -z
+    let z = (eye - target).normalize();
+    let x = up.cross(&z).normalize();
+    let y = z.cross(&x);
+    #[rustfmt::skip]
+    let view = Mat4::new(
+        x.x, x.y, x.z, -x.dot(&eye),
+        y.x, y.y, y.z, -y.dot(&eye),
+        z.x, z.y, z.z, -z.dot(&eye),
+        0.0, 0.0, 0.0, 1.0,
+    );
+    view
+}
+
+/// 透视投影，`fov_y` 是竖直方向的视角，把 `[-near, -far]` 映射到 NDC 的 `[-1, 1]`
+pub fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> Mat4 {
+    let tan_half_fov = (fov_y / 2.0).tan();
+    let a = 1.0 / (tan_half_fov * aspect); // 影响x轴上的缩放
+    let b = 1.0 / tan_half_fov; // 影响y轴上的缩放
+    let c = -(far + near) / (far - near); // 计算z轴上的深度缩放和位移
+    let d = -2.0 * far * near / (far - near); // 远近裁剪面之间的关系
+    #[rustfmt::skip]
+    let m = Mat4::new(
+        a,   0.0, 0.0,  0.0,
+        0.0, b,   0.0,  0.0,
+        0.0, 0.0, c,    d,
+        0.0, 0.0, -1.0, 0.0,
+    );
+    m
+}
+
+/// 远平面在无穷远处的透视投影，把 `-near` 映射到 NDC 的 -1，无穷远处趋近于 1
+pub fn infinite_perspective(fov_y: f32, aspect: f32, near: f32) -> Mat4 {
+    let f = 1.0 / (fov_y / 2.0).tan();
+    #[rustfmt::skip]
+    let m = Mat4::new(
+        f / aspect, 0.0, 0.0,  0.0,
+        0.0,        f,   0.0,  0.0,
+        0.0,        0.0, -1.0, -2.0 * near,
+        0.0,        0.0, -1.0, 0.0,
+    );
+    m
+}
+
+/// 正交投影，把观察空间中 `[left, right] x [bottom, top] x [-near, -far]` 的长方体映射到 NDC 的 `[-1, 1]^3`
+pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Mat4 {
+    #[rustfmt::skip]
+    let m = Mat4::new(
+        2.0 / (right - left), 0.0,                  0.0,                 -(right + left) / (right - left),
+        0.0,                  2.0 / (top - bottom), 0.0,                 -(top + bottom) / (top - bottom),
+        0.0,                  0.0,                  -2.0 / (far - near), -(far + near) / (far - near),
+        0.0,                  0.0,                  0.0,                 1.0,
+    );
+    m
+}
+
+/// 把投影矩阵改为反转的深度：原来映射到 NDC 的 z = -1 的近平面映射到 1，z = 1 的远平面映射到 0，
+/// 即 `z' = (1 - z) / 2`
+pub fn reverse_depth(projection: &Mat4) -> Mat4 {
+    #[rustfmt::skip]
+    let remap = Mat4::new(
+        1.0, 0.0, 0.0,  0.0,
+        0.0, 1.0, 0.0,  0.0,
+        0.0, 0.0, -0.5, 0.5,
+        0.0, 0.0, 0.0,  1.0,
+    );
+    remap * projection
+}
+
+/// 先缩放、再旋转、最后平移的变换
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub translation: Vec3,
+    /// 单位四元数
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            translation: Vec3::zeros(),
+            rotation: Quat::identity(),
+            scale: vec3(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+impl Transform {
+    pub fn new(translation: Vec3, rotation: Quat, scale: Vec3) -> Self {
+        Self {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    /// `T * R * S`
+    pub fn matrix(&self) -> Mat4 {
+        translation(self.translation) * rotation_from_quat(&self.rotation) * scaling(self.scale)
+    }
+
+    /// 逆变换 `S^-1 * R^T * T^-1`，不需要求一般矩阵的逆。
+    ///
+    /// 非均匀缩放时逆变换不能再写成先缩放后旋转的形式，所以返回矩阵
+    pub fn inverse_matrix(&self) -> Mat4 {
+        let r = rotation_from_quat(&self.rotation).transpose();
+        scaling(self.scale.map(|s| 1.0 / s)) * r * translation(-self.translation)
+    }
+
+    /// 把仿射矩阵分解为平移、旋转和缩放。
+    ///
+    /// 矩阵含有切变、投影或者退化时返回 `None`；镜像变换的负号放在 x 方向的缩放上
+    pub fn from_matrix(m: &Mat4) -> Option<Self> {
+        const EPSILON: f32 = 1e-4;
+        let last_row = vec4(m[(3, 0)], m[(3, 1)], m[(3, 2)], m[(3, 3)]);
+        if (last_row - vec4(0.0, 0.0, 0.0, 1.0)).abs().max() > EPSILON {
+            return None;
+        }
+        let linear: Mat3 = m.fixed_view::<3, 3>(0, 0).into();
+        let mut scale = vec3(
+            linear.column(0).norm(),
+            linear.column(1).norm(),
+            linear.column(2).norm(),
+        );
+        if scale.min() < EPSILON {
+            return None;
+        }
+        if linear.determinant() < 0.0 {
+            scale.x = -scale.x;
+        }
+        let r = Mat3::from_columns(&[
+            linear.column(0) / scale.x,
+            linear.column(1) / scale.y,
+            linear.column(2) / scale.z,
+        ]);
+        // 各列两两正交才是旋转，否则有切变
+        if (r.transpose() * r - Mat3::identity()).abs().max() > EPSILON {
+            return None;
+        }
+        Some(Self {
+            translation: m.fixed_view::<3, 1>(0, 3).into(),
+            rotation: nalgebra_glm::mat3_to_quat(&r),
+            scale,
+        })
+    }
+}
@@ -0,0 +1,128 @@
+use crate::color::Color;
+use nalgebra_glm::{Vec2, Vec3, Vec4};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Triangle {
+    /// the original coordinates of the triangle, v0, v1, v2 in
+    // counterclockwise order
+    pub v: [Vec3; 3],
+    /// color at each vertex
+    pub color: [Color; 3],
+    /// texture u,v
+    pub tex_coords: [Vec2; 3],
+    /// normal vector for each vertex, in view space
+    pub normal: [Vec3; 3],
+    /// view-space position of each vertex, used for lighting
+    pub view_pos: [Vec3; 3],
+    /// clip-space w of each vertex, used for perspective-correct interpolation
+    pub w: [f32; 3],
+}
+
+impl Default for Triangle {
+    fn default() -> Self {
+        Self {
+            v: [Vec3::zeros(); 3],
+            color: [Color::BLACK; 3],
+            tex_coords: [Vec2::zeros(); 3],
+            normal: [Vec3::zeros(); 3],
+            view_pos: [Vec3::zeros(); 3],
+            w: [1.0; 3],
+        }
+    }
+}
+
+impl Triangle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn a(&self) -> Vec3 {
+        self.v[0]
+    }
+
+    pub fn b(&self) -> Vec3 {
+        self.v[1]
+    }
+    pub fn c(&self) -> Vec3 {
+        self.v[2]
+    }
+
+    pub fn set_vertex(&mut self, index: usize, vertex: Vec3) {
+        self.v[index] = vertex;
+    }
+
+    pub fn set_normal(&mut self, index: usize, normal: Vec3) {
+        self.normal[index] = normal;
+    }
+
+    pub fn set_view_pos(&mut self, index: usize, view_pos: Vec3) {
+        self.view_pos[index] = view_pos;
+    }
+
+    pub fn set_tex_coords(&mut self, index: usize, s: f32, t: f32) {
+        self.tex_coords[index] = Vec2::new(s, t);
+    }
+
+    pub fn set_color(&mut self, index: usize, color: Color) {
+        self.color[index] = color;
+    }
+
+    pub fn set_w(&mut self, index: usize, w: f32) {
+        self.w[index] = w;
+    }
+
+    pub fn color(&self) -> Color {
+        self.color[0]
+    }
+
+    pub fn color_by_barycentric(&self, alpha: f32, beta: f32, gamma: f32) -> Color {
+        self.color[0] * alpha + self.color[1] * beta + self.color[2] * gamma
+    }
+
+    /// 把屏幕空间的重心坐标校正为透视正确的重心坐标
+    pub fn perspective_correct(&self, alpha: f32, beta: f32, gamma: f32) -> (f32, f32, f32) {
+        let (a, b, c) = (alpha / self.w[0], beta / self.w[1], gamma / self.w[2]);
+        let sum = a + b + c;
+        (a / sum, b / sum, c / sum)
+    }
+
+    /// 按（已做透视校正的）重心坐标插值纹理坐标
+    pub fn tex_coords_by_barycentric(&self, alpha: f32, beta: f32, gamma: f32) -> Vec2 {
+        self.tex_coords[0] * alpha + self.tex_coords[1] * beta + self.tex_coords[2] * gamma
+    }
+
+    /// 按（已做透视校正的）重心坐标插值法线，结果不是单位向量
+    pub fn normal_by_barycentric(&self, alpha: f32, beta: f32, gamma: f32) -> Vec3 {
+        self.normal[0] * alpha + self.normal[1] * beta + self.normal[2] * gamma
+    }
+
+    /// 按（已做透视校正的）重心坐标插值观察空间坐标
+    pub fn view_pos_by_barycentric(&self, alpha: f32, beta: f32, gamma: f32) -> Vec3 {
+        self.view_pos[0] * alpha + self.view_pos[1] * beta + self.view_pos[2] * gamma
+    }
+
+    /// 观察空间中的切线和副切线，见 [`crate::surface::tangent_frame`]
+    pub fn tangent_frame(&self) -> Option<(Vec3, Vec3)> {
+        crate::surface::tangent_frame(self.view_pos, self.tex_coords)
+    }
+
+    /// 三个顶点的坐标是否都是有限值（不含 NaN 和无穷大）
+    pub fn is_finite(&self) -> bool {
+        self.v.iter().all(|v| v.iter().all(|c| c.is_finite()))
+    }
+
+    /// 三角形在 xy 平面上的投影面积是否为零
+    pub fn is_degenerate(&self) -> bool {
+        let e1 = self.v[1] - self.v[0];
+        let e2 = self.v[2] - self.v[0];
+        e1.x * e2.y - e1.y * e2.x == 0.0
+    }
+
+    pub fn to_vector4(&self) -> [Vec4; 3] {
+        [
+            Vec4::new(self.v[0].x, self.v[0].y, self.v[0].z, 1.0),
+            Vec4::new(self.v[1].x, self.v[1].y, self.v[1].z, 1.0),
+            Vec4::new(self.v[2].x, self.v[2].y, self.v[2].z, 1.0),
+        ]
+    }
+}
@@ -0,0 +1,176 @@
+use games101_core::animation::{self, sequence_path, AnimationFormat, AnimationWriter};
+use games101_core::cli::RenderArgs;
+use games101_core::color::Color;
+use games101_core::rasterizer::Rasterizer;
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+use image::AnimationDecoder;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("games101-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// 第 `i` 帧：左半边是红色，右半边的亮度随帧数变化
+fn frame(i: u32) -> Vec<Color> {
+    (0..4 * 3)
+        .map(|p| {
+            if p % 4 < 2 {
+                Color::RED
+            } else {
+                Color::new_rgb(0, 0, 60 * i as u8)
+            }
+        })
+        .collect()
+}
+
+/// 共 `n` 帧时按 `t` 取 [`frame`]
+fn frames(n: u32) -> impl FnMut(f32) -> Vec<Color> {
+    move |t| frame((t * n as f32).round() as u32)
+}
+
+fn decoded_frames(frames: image::Frames) -> Vec<image::Frame> {
+    frames.collect_frames().unwrap()
+}
+
+#[test]
+fn format_and_sequence_names() {
+    assert_eq!(
+        AnimationFormat::from_path(Path::new("a.GIF")),
+        AnimationFormat::Gif
+    );
+    assert_eq!(
+        AnimationFormat::from_path(Path::new("a.apng")),
+        AnimationFormat::Apng
+    );
+    assert_eq!(
+        AnimationFormat::from_path(Path::new("a.png")),
+        AnimationFormat::PngSequence
+    );
+    assert_eq!(
+        sequence_path(Path::new("out/turntable.png"), 3),
+        Path::new("out/turntable_0003.png")
+    );
+    assert_eq!(
+        sequence_path(Path::new("frame"), 12),
+        Path::new("frame_0012.png")
+    );
+}
+
+#[test]
+fn png_sequence() {
+    let dir = temp_dir("sequence");
+    animation::render(dir.join("seq.png"), 4, 3, 3, 30, frames(3)).unwrap();
+    // render 的 t 从 0 开始，每帧增加 1 / 3
+    let mut ts = Vec::new();
+    animation::render(dir.join("t.png"), 4, 3, 3, 30, |t| {
+        ts.push(t);
+        frame(0)
+    })
+    .unwrap();
+    assert_eq!(ts, vec![0.0, 1.0 / 3.0, 2.0 / 3.0]);
+
+    for i in 0..3 {
+        let image = image::open(dir.join(format!("seq_{:04}.png", i)))
+            .unwrap()
+            .to_rgba8();
+        assert_eq!(image.dimensions(), (4, 3));
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(3, 2).0, [0, 0, 60 * i as u8, 255]);
+    }
+    assert!(!dir.join("seq_0003.png").exists());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn apng_round_trip() {
+    let dir = temp_dir("apng");
+    let path = dir.join("anim.apng");
+    animation::render(&path, 4, 3, 4, 20, frames(4)).unwrap();
+
+    let file = BufReader::new(std::fs::File::open(&path).unwrap());
+    let decoder = PngDecoder::new(file).unwrap();
+    assert!(decoder.is_apng().unwrap());
+    let frames = decoded_frames(decoder.apng().unwrap().into_frames());
+    assert_eq!(frames.len(), 4);
+    for (i, f) in frames.iter().enumerate() {
+        // APNG 是无损的
+        let expected: Vec<u8> = bytemuck::cast_slice(&frame(i as u32)).to_vec();
+        assert_eq!(f.buffer().as_raw(), &expected);
+        // 分子分母的约分方式取决于解码器，只比较时长
+        assert_eq!(Duration::from(f.delay()), Duration::from_millis(50));
+    }
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn gif_round_trip() {
+    let dir = temp_dir("gif");
+    let path = dir.join("anim.gif");
+    animation::render(&path, 4, 3, 3, 10, frames(3)).unwrap();
+
+    let file = BufReader::new(std::fs::File::open(&path).unwrap());
+    let frames = decoded_frames(GifDecoder::new(file).unwrap().into_frames());
+    assert_eq!(frames.len(), 3);
+    for (i, f) in frames.iter().enumerate() {
+        // 调色板量化后颜色可能有少许误差
+        let pixel = f.buffer().get_pixel(3, 0).0;
+        assert!(pixel[2].abs_diff(60 * i as u8) <= 8, "{:?}", pixel);
+        assert!(f.buffer().get_pixel(0, 2).0[0] >= 248);
+        assert_eq!(Duration::from(f.delay()), Duration::from_millis(100));
+    }
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn gif_write_errors_are_reported() {
+    // 写入 /dev/full 总是失败，小动画在 finish 之前都留在缓冲区中
+    let dir = temp_dir("gif-full");
+    let path = dir.join("full.gif");
+    std::os::unix::fs::symlink("/dev/full", &path).unwrap();
+    let mut writer = AnimationWriter::create(&path, 4, 3, 2, 10).unwrap();
+    writer.write_frame(&frame(0)).unwrap();
+    writer.write_frame(&frame(1)).unwrap();
+    assert!(matches!(
+        writer.finish(),
+        Err(image::ImageError::IoError(_))
+    ));
+    assert!(animation::render(&path, 4, 3, 2, 10, frames(2)).is_err());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn frame_count_and_turntable() {
+    let dir = temp_dir("turntable");
+    // 帧数与声明的不同时报错
+    let mut writer = AnimationWriter::create(dir.join("a.apng"), 4, 3, 1, 30).unwrap();
+    assert!(writer.write_frame(&frame(0)[1..]).is_err());
+    writer.write_frame(&frame(0)).unwrap();
+    assert!(writer.write_frame(&frame(1)).is_err());
+    writer.finish().unwrap();
+    let writer = AnimationWriter::create(dir.join("b.apng"), 4, 3, 2, 30).unwrap();
+    assert!(writer.finish().is_err());
+
+    // 命令行的 --frames 从 --angle 开始转一整圈
+    let args = RenderArgs::parse(
+        "-a 10 --frames 4 --size 4x3"
+            .split_whitespace()
+            .map(String::from),
+    )
+    .unwrap();
+    let mut r = Rasterizer::new(4, 3);
+    let mut angles = Vec::new();
+    let path = dir.join("turntable.png");
+    args.render_to_file(&mut r, path.to_str().unwrap(), |_, frame| {
+        angles.push(frame.angle)
+    })
+    .unwrap();
+    assert_eq!(angles, vec![10.0, 100.0, 190.0, 280.0]);
+    assert!(dir.join("turntable_0003.png").exists());
+    std::fs::remove_dir_all(dir).unwrap();
+}
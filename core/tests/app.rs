@@ -0,0 +1,112 @@
+use games101_core::app::{App, Geometry, Transforms};
+use games101_core::cli::RenderArgs;
+use games101_core::color::Color;
+use games101_core::rasterizer::{Buffers, Primitive, RasterMode, Rasterizer};
+use games101_core::transform;
+use nalgebra_glm::{vec3, Mat4, Vec3};
+use std::path::PathBuf;
+
+fn model(angle: f32) -> Mat4 {
+    transform::rotation_z(angle.to_radians())
+}
+
+fn projection(fov: f32, aspect: f32, near: f32, far: f32) -> Mat4 {
+    transform::perspective(fov.to_radians(), aspect, near, far)
+}
+
+const APP: App = App {
+    program: "test",
+    title: "Test",
+    transforms: Transforms { model, projection },
+    exercises: None,
+};
+
+fn triangle(primitive: Primitive) -> Geometry {
+    Geometry {
+        positions: vec![
+            vec3(2.0, 0.0, -2.0),
+            vec3(0.0, 2.0, -2.0),
+            vec3(-2.0, 0.0, -2.0),
+        ],
+        indices: vec![vec3(0.0, 1.0, 2.0)],
+        colors: vec![Color::RED, Color::GREEN, Color::BLUE],
+        primitive,
+    }
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("games101-app-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn load(path: &PathBuf) -> Vec<u8> {
+    image::open(path).unwrap().to_rgba8().into_raw()
+}
+
+#[test]
+fn model_matrix_uses_exercise_for_z_axis() {
+    let mut args = RenderArgs {
+        angle: 30.0,
+        ..RenderArgs::default()
+    };
+    // 用一个明显不同的函数代替作业中的模型矩阵，确认绕 z 轴时调用的是它
+    let app = App {
+        transforms: Transforms {
+            model: |_| transform::scaling(Vec3::repeat(2.0)),
+            projection,
+        },
+        ..APP
+    };
+    assert_eq!(
+        app.model_matrix(&args),
+        transform::scaling(Vec3::repeat(2.0))
+    );
+    args.axis = vec3(0.0, 2.0, 0.0);
+    let expected = transform::rotation_y(30f32.to_radians());
+    assert!((app.model_matrix(&args) - expected).abs().max() < 1e-6);
+}
+
+#[test]
+fn renders_like_the_pipeline() {
+    let dir = temp_dir("render");
+    let args = RenderArgs::parse(
+        "-s 64x48 -a 20 --mode edge"
+            .split_whitespace()
+            .map(String::from),
+    )
+    .unwrap();
+    let path = dir.join("triangle.png");
+    APP.render_to_file(
+        &args,
+        &triangle(Primitive::Triangle),
+        path.to_str().unwrap(),
+    )
+    .unwrap();
+
+    let mut r = Rasterizer::new(64, 48);
+    r.set_raster_mode(RasterMode::EdgeFunction);
+    let geometry = triangle(Primitive::Triangle);
+    let pos = r.load_positions(geometry.positions);
+    let ind = r.load_indices(geometry.indices);
+    let col = r.load_colors(geometry.colors);
+    r.set_model(model(20.0));
+    r.set_view(transform::translation(vec3(0.0, 0.0, -5.0)));
+    r.set_projection(projection(45.0, 64.0 / 48.0, 0.1, 50.0));
+    r.clear(Buffers::all());
+    r.draw(pos, ind, col, Primitive::Triangle);
+    assert_eq!(load(&path), r.framebuffer_u8());
+
+    // 线框只画边，中心是背景色
+    let wireframe = dir.join("wireframe.png");
+    APP.render_to_file(
+        &args,
+        &triangle(Primitive::Line),
+        wireframe.to_str().unwrap(),
+    )
+    .unwrap();
+    let pixels = load(&wireframe);
+    assert!(pixels.chunks(4).any(|p| p != [0, 0, 0, 255]));
+    assert_ne!(pixels, load(&path));
+    std::fs::remove_dir_all(dir).unwrap();
+}
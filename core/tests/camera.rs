@@ -0,0 +1,153 @@
+use games101_core::camera::{Camera, Projection};
+use games101_core::color::Color;
+use games101_core::rasterizer::{Buffers, Primitive, RasterMode, Rasterizer};
+use games101_core::transform::look_at;
+use nalgebra_glm::{vec3, Mat4, Vec3};
+
+fn assert_close(a: &Mat4, b: &Mat4) {
+    assert!((a - b).abs().max() < 1e-5, "{} {}", a, b);
+}
+
+/// 观察空间中 `z`（负数）处的点投影后的 NDC 深度
+fn ndc_z(projection: &Mat4, z: f32) -> f32 {
+    let clip = projection * vec3(0.0, 0.0, z).push(1.0);
+    clip.z / clip.w
+}
+
+fn perspective(far: Option<f32>) -> Projection {
+    Projection::Perspective {
+        fov_y: 60f32.to_radians(),
+        aspect: 1.5,
+        near: 0.5,
+        far,
+    }
+}
+
+#[test]
+fn look_at_matches_glm() {
+    let eye = vec3(3.0, 2.0, -4.0);
+    let target = vec3(0.5, 0.0, 1.0);
+    let up = vec3(0.0, 1.0, 0.0);
+    let view = look_at(eye, target, up);
+    assert_close(&view, &nalgebra_glm::look_at_rh(&eye, &target, &up));
+    // 相机在原点，看向 -z
+    let t = view * target.push(1.0);
+    assert!(t.x.abs() < 1e-5 && t.y.abs() < 1e-5 && t.z < 0.0);
+    let e = view * eye.push(1.0);
+    assert!(e.xyz().norm() < 1e-5);
+
+    let camera = Camera::look_at(eye, target, up);
+    assert_close(&camera.view_matrix(), &view);
+    assert!((camera.forward() - (target - eye).normalize()).norm() < 1e-6);
+    // 默认相机与作业框架相同
+    assert_close(
+        &Camera::default().view_matrix(),
+        &nalgebra_glm::translation(&vec3(0.0, 0.0, -5.0)),
+    );
+}
+
+#[test]
+fn perspective_matches_glm() {
+    let p = perspective(Some(20.0)).matrix(false);
+    assert_close(
+        &p,
+        &nalgebra_glm::perspective_rh_no(1.5, 60f32.to_radians(), 0.5, 20.0),
+    );
+    assert!((ndc_z(&p, -0.5) + 1.0).abs() < 1e-5);
+    assert!((ndc_z(&p, -20.0) - 1.0).abs() < 1e-5);
+
+    let infinite = perspective(None).matrix(false);
+    assert_close(
+        &infinite,
+        &nalgebra_glm::infinite_perspective_rh_no(1.5, 60f32.to_radians(), 0.5),
+    );
+    assert!((ndc_z(&infinite, -0.5) + 1.0).abs() < 1e-5);
+    assert!(ndc_z(&infinite, -1e6) < 1.0 && ndc_z(&infinite, -1e6) > 0.999);
+}
+
+#[test]
+fn orthographic_matches_glm() {
+    let mut ortho = Projection::Orthographic {
+        height: 4.0,
+        aspect: 1.0,
+        near: 0.1,
+        far: 10.0,
+    };
+    ortho.set_aspect(2.0);
+    assert_close(
+        &ortho.matrix(false),
+        &nalgebra_glm::ortho_rh_no(-4.0, 4.0, -2.0, 2.0, 0.1, 10.0),
+    );
+    let reversed = ortho.matrix(true);
+    assert!((ndc_z(&reversed, -0.1) - 1.0).abs() < 1e-5);
+    assert!(ndc_z(&reversed, -10.0).abs() < 1e-5);
+}
+
+#[test]
+fn reversed_z_maps_near_to_one() {
+    let p = perspective(Some(20.0)).matrix(true);
+    assert!((ndc_z(&p, -0.5) - 1.0).abs() < 1e-5);
+    assert!(ndc_z(&p, -20.0).abs() < 1e-5);
+    // 越远深度越小
+    assert!(ndc_z(&p, -2.0) > ndc_z(&p, -3.0));
+
+    let infinite = perspective(None).matrix(true);
+    assert!((ndc_z(&infinite, -0.5) - 1.0).abs() < 1e-5);
+    assert!(ndc_z(&infinite, -1e6) > 0.0 && ndc_z(&infinite, -1e6) < 1e-6);
+    // x、y 与普通的透视投影相同
+    let normal = perspective(None).matrix(false);
+    assert_eq!(
+        infinite.fixed_view::<2, 4>(0, 0),
+        normal.fixed_view::<2, 4>(0, 0)
+    );
+}
+
+/// 先画远处的红色三角形再画近处的绿色三角形，然后反过来，中心都应该是绿色
+fn draw_overlapping(camera: &Camera) {
+    let mut r = Rasterizer::new(16, 16);
+    r.set_raster_mode(RasterMode::EdgeFunction);
+    camera.apply(&mut r);
+    assert_eq!(r.reversed_z(), camera.reversed_z);
+    let triangle = |z: f32| vec![vec3(-1.0, -1.0, z), vec3(1.0, -1.0, z), vec3(0.0, 1.0, z)];
+    let far = r.load_positions(triangle(-1.0));
+    let near = r.load_positions(triangle(1.0));
+    let ind = r.load_indices(vec![vec3(0.0, 1.0, 2.0)]);
+    let red = r.load_colors(vec![Color::RED; 3]);
+    let green = r.load_colors(vec![Color::GREEN; 3]);
+    for order in [[(far, red), (near, green)], [(near, green), (far, red)]] {
+        r.clear(Buffers::all());
+        for (pos, col) in order {
+            r.draw(pos, ind, col, Primitive::Triangle);
+        }
+        let c = r.framebuffer()[8 * 16 + 8];
+        assert!(c.g > 250 && c.r == 0, "{:?}", c);
+    }
+}
+
+#[test]
+fn camera_drives_rasterizer_depth_test() {
+    let eye = vec3(0.0, 0.0, 5.0);
+    let up = vec3(0.0, 1.0, 0.0);
+    for far in [Some(50.0), None] {
+        for reversed in [false, true] {
+            let camera = Camera::look_at(eye, Vec3::zeros(), up)
+                .with_projection(Projection::Perspective {
+                    fov_y: 45f32.to_radians(),
+                    aspect: 1.0,
+                    near: 0.1,
+                    far,
+                })
+                .with_reversed_z(reversed);
+            draw_overlapping(&camera);
+        }
+    }
+    let ortho = Camera::look_at(eye, Vec3::zeros(), up)
+        .with_projection(Projection::Orthographic {
+            height: 4.0,
+            aspect: 1.0,
+            near: 0.1,
+            far: 10.0,
+        })
+        .with_reversed_z(true);
+    draw_overlapping(&ortho);
+}
@@ -0,0 +1,138 @@
+use games101_core::cli::{usage, ArgsError, RenderArgs};
+use games101_core::color::Color;
+use games101_core::rasterizer::{RasterMode, Rasterizer};
+use nalgebra_glm::vec3;
+
+fn parse(args: &str) -> Result<RenderArgs, ArgsError> {
+    RenderArgs::parse(args.split_whitespace().map(String::from))
+}
+
+#[test]
+fn parses_all_options() {
+    assert_eq!(parse("").unwrap(), RenderArgs::default());
+    let args = parse(
+        "-o out.png --size 320x200 -a 30 --axis 1,1,0 -e 0,1,8 --fov 60 --near 0.5 --far 100 --msaa 4 --mode scanline --frames 12 --fps 24",
+    )
+    .unwrap();
+    assert_eq!(args.output.as_deref(), Some("out.png"));
+    assert_eq!((args.width, args.height), (320, 200));
+    assert_eq!(args.angle, 30.0);
+    assert_eq!(args.axis, vec3(1.0, 1.0, 0.0));
+    assert_eq!(args.eye, vec3(0.0, 1.0, 8.0));
+    assert_eq!((args.fov, args.near, args.far), (60.0, 0.5, 100.0));
+    assert_eq!(args.mode, RasterMode::Scanline);
+    assert_eq!((args.frames, args.fps), (12, 24));
+    assert_eq!(args.aspect(), 1.6);
+    // 4 倍采样时每个方向放大 2 倍
+    assert_eq!(args.framebuffer_size(), (640, 400));
+}
+
+#[test]
+fn camera_looks_at_origin() {
+    let args = RenderArgs::default();
+    let camera = args.camera();
+    // 默认相机与作业框架相同，只沿 z 轴平移
+    assert!(
+        (camera.view_matrix() - nalgebra_glm::translation(&-args.eye))
+            .abs()
+            .max()
+            < 1e-6
+    );
+    let projection = nalgebra_glm::perspective_rh_no(1.0, 45f32.to_radians(), 0.1, 50.0);
+    assert!((camera.projection_matrix() - projection).abs().max() < 1e-6);
+
+    // 偏离 z 轴时仍然看向原点，在 y 轴上和原点处也不会产生 NaN
+    for eye in ["3,2,5", "0,5,0", "0,-5,0", "0,0,0"] {
+        let camera = parse(&format!("-e {}", eye)).unwrap().camera();
+        let view = camera.view_matrix();
+        assert!(view.iter().all(|v| v.is_finite()), "{}", eye);
+        if eye != "0,0,0" {
+            let origin = view * vec3(0.0, 0.0, 0.0).push(1.0);
+            assert!(origin.x.abs() < 1e-5 && origin.y.abs() < 1e-5 && origin.z < 0.0);
+        }
+    }
+}
+
+#[test]
+fn legacy_render_flag() {
+    let args = parse("-r 20 image.png").unwrap();
+    assert_eq!(
+        (args.angle, args.output.as_deref()),
+        (20.0, Some("image.png"))
+    );
+    // 没有文件名时默认为 output.png，后面还能跟其他参数
+    let args = parse("-r -45 --mode edge").unwrap();
+    assert_eq!(
+        (args.angle, args.output.as_deref()),
+        (-45.0, Some("output.png"))
+    );
+    assert_eq!(args.mode, RasterMode::EdgeFunction);
+}
+
+#[test]
+fn rejects_bad_input() {
+    assert_eq!(parse("foo"), Err(ArgsError::Unknown("foo".to_string())));
+    assert_eq!(parse("-a"), Err(ArgsError::MissingValue("--angle")));
+    let invalid = |option, value: &str| {
+        Err(ArgsError::InvalidValue {
+            option,
+            value: value.to_string(),
+        })
+    };
+    assert_eq!(parse("-a ten"), invalid("--angle", "ten"));
+    assert_eq!(parse("--size 0x10"), invalid("--size", "0x10"));
+    assert_eq!(parse("--msaa 8"), invalid("--msaa", "8"));
+    assert_eq!(parse("--msaa 4294967295"), invalid("--msaa", "4294967295"));
+    assert_eq!(
+        parse("--size 4000x3000 --msaa 25"),
+        invalid("--size/--msaa", "4000x3000 with 25 samples")
+    );
+    assert_eq!(
+        parse("--size 4294967295x1"),
+        invalid("--size/--msaa", "4294967295x1 with 1 samples")
+    );
+    assert!(parse("--size 4096x4096 --msaa 16").is_ok());
+    assert_eq!(parse("--axis 0,0,0"), invalid("--axis", "0,0,0"));
+    assert_eq!(parse("-e 1,2"), invalid("--eye", "1,2"));
+    assert_eq!(parse("--mode fast"), invalid("--mode", "fast"));
+    assert_eq!(parse("--frames 0"), invalid("--frames", "0"));
+    assert_eq!(parse("--near 10 --far 5"), invalid("--near/--far", "10..5"));
+    let message = parse("--fov 180").unwrap_err().to_string();
+    assert_eq!(message, "invalid value '180' for --fov");
+}
+
+#[test]
+fn help() {
+    assert_eq!(parse("-o a.png --help"), Err(ArgsError::Help));
+    let text = usage("assignment1");
+    assert!(text.starts_with("Usage: assignment1 [options]"));
+    for option in [
+        "--output", "--size", "--axis", "--eye", "--msaa", "--mode", "--frames", "--fps",
+    ] {
+        assert!(text.contains(option), "{}", option);
+    }
+}
+
+#[test]
+fn resolve_averages_samples() {
+    let args = parse("--size 2x1 --msaa 4").unwrap();
+    let (w, h) = args.framebuffer_size();
+    let mut r = Rasterizer::new(w, h);
+    // 左边的像素有一半采样是白色，右边的像素全是红色
+    r.set_pixel(0, 0, Color::WHITE, 0.0);
+    r.set_pixel(1, 1, Color::WHITE, 0.0);
+    for (x, y) in [(2, 0), (3, 0), (2, 1), (3, 1)] {
+        r.set_pixel(x, y, Color::RED, 0.0);
+    }
+    assert_eq!(
+        args.resolve(&r),
+        vec![Color::new_rgb(128, 128, 128), Color::RED]
+    );
+
+    let path = std::env::temp_dir().join(format!("games101-cli-{}.png", std::process::id()));
+    args.save(&r, path.to_str().unwrap()).unwrap();
+    let image = image::open(&path).unwrap().to_rgba8();
+    assert_eq!(image.dimensions(), (2, 1));
+    assert_eq!(image.get_pixel(1, 0).0, [255, 0, 0, 255]);
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,186 @@
+use games101_core::camera::{Camera, Projection};
+use games101_core::controls::{window_to_ndc, FlyControls, OrbitControls};
+use nalgebra_glm::{vec2, vec3, Vec3};
+
+/// 世界空间中的点在相机中的 NDC 坐标
+fn project(camera: &Camera, p: Vec3) -> Vec3 {
+    let clip = camera.projection_matrix() * camera.view_matrix() * p.push(1.0);
+    clip.xyz() / clip.w
+}
+
+fn close(a: Vec3, b: Vec3) -> bool {
+    (a - b).norm() < 1e-4
+}
+
+#[test]
+fn orbit_round_trips_camera() {
+    let camera = Camera::look_at(
+        vec3(1.0, 2.0, 3.0),
+        vec3(0.5, 0.0, -1.0),
+        vec3(0.0, 1.0, 0.0),
+    );
+    let orbit = OrbitControls::from_camera(&camera);
+    let mut copy = Camera::default();
+    orbit.apply(&mut copy);
+    assert!(close(copy.position, camera.position), "{}", copy.position);
+    assert!(close(copy.target, camera.target));
+    assert!((copy.view_matrix() - camera.view_matrix()).abs().max() < 1e-4);
+
+    assert_eq!(window_to_ndc(0.0, 0.0, 100, 50), vec2(-1.0, 1.0));
+    assert_eq!(window_to_ndc(50.0, 25.0, 100, 50), vec2(0.0, 0.0));
+}
+
+#[test]
+fn orbit_survives_degenerate_cameras() {
+    let up = vec3(0.0, 1.0, 0.0);
+    let p = vec3(1.0, 2.0, 3.0);
+    // 相机与目标重合，以及上方向与视线平行
+    for camera in [
+        Camera::look_at(p, p, up),
+        Camera::look_at(vec3(0.0, 5.0, 0.0), Vec3::zeros(), up),
+        Camera::look_at(p, Vec3::zeros(), Vec3::zeros()),
+    ] {
+        let mut orbit = OrbitControls::from_camera(&camera);
+        assert!(
+            orbit.rotation.coords.iter().all(|c| c.is_finite()),
+            "{:?}",
+            camera
+        );
+        assert!(orbit.distance >= orbit.min_distance);
+        orbit.rotate(vec2(0.0, 0.0), vec2(0.3, 0.1));
+        let mut copy = camera;
+        orbit.apply(&mut copy);
+        assert!(
+            copy.view_matrix().iter().all(|v| v.is_finite()),
+            "{:?}",
+            camera
+        );
+        assert_eq!(copy.target, camera.target);
+    }
+    // 相机与目标重合时从 +z 方向看
+    let mut copy = Camera::default();
+    OrbitControls::from_camera(&Camera::look_at(p, p, up)).apply(&mut copy);
+    assert!(close(copy.position, p + vec3(0.0, 0.0, 0.01)));
+}
+
+#[test]
+fn arcball_drag_turns_model_with_mouse() {
+    let mut camera = Camera::default();
+    let mut orbit = OrbitControls::from_camera(&camera);
+    // 原地不动
+    orbit.rotate(vec2(0.3, 0.2), vec2(0.3, 0.2));
+    assert_eq!(orbit, OrbitControls::from_camera(&camera));
+
+    // 向右拖动，模型朝向相机的一面转到右边，上方的点不动
+    orbit.rotate(vec2(0.0, 0.0), vec2(0.5, 0.0));
+    orbit.apply(&mut camera);
+    assert!(project(&camera, vec3(0.0, 0.0, 1.0)).x > 0.1);
+    assert!(project(&camera, vec3(0.0, 1.0, 0.0)).x.abs() < 1e-4);
+    // 距离和目标不变
+    assert!(((camera.position - camera.target).norm() - 5.0).abs() < 1e-4);
+    assert_eq!(camera.target, Vec3::zeros());
+
+    // 向上拖动，前面的点转到上方
+    let mut camera = Camera::default();
+    let mut orbit = OrbitControls::from_camera(&camera);
+    orbit.rotate(vec2(0.0, -0.3), vec2(0.0, 0.3));
+    orbit.apply(&mut camera);
+    assert!(project(&camera, vec3(0.0, 0.0, 1.0)).y > 0.1);
+    // 往回拖动回到原处
+    orbit.rotate(vec2(0.0, 0.3), vec2(0.0, -0.3));
+    orbit.apply(&mut camera);
+    assert!(
+        close(camera.position, vec3(0.0, 0.0, 5.0)),
+        "{}",
+        camera.position
+    );
+}
+
+#[test]
+fn pan_follows_mouse_and_zoom_clamps() {
+    for projection in [
+        Projection::default(),
+        Projection::Orthographic {
+            height: 3.0,
+            aspect: 2.0,
+            near: 0.1,
+            far: 50.0,
+        },
+    ] {
+        let mut camera = Camera::look_at(vec3(2.0, 3.0, 4.0), Vec3::zeros(), vec3(0.0, 1.0, 0.0))
+            .with_projection(projection);
+        let mut orbit = OrbitControls::from_camera(&camera);
+        let before = project(&camera, Vec3::zeros());
+        orbit.pan(vec2(0.2, -0.1), &camera.projection);
+        orbit.apply(&mut camera);
+        // 原来在目标处的点跟着鼠标移动
+        let after = project(&camera, Vec3::zeros());
+        assert!(
+            (after.xy() - before.xy() - vec2(0.2, -0.1)).norm() < 1e-4,
+            "{}",
+            after
+        );
+    }
+
+    let mut orbit = OrbitControls::from_camera(&Camera::default());
+    orbit.zoom(1.0);
+    assert!((orbit.distance - 4.5).abs() < 1e-5);
+    orbit.zoom(-2.0);
+    assert!(orbit.distance > 5.0);
+    orbit.zoom(1000.0);
+    assert_eq!(orbit.distance, orbit.min_distance);
+    orbit.zoom(-1000.0);
+    assert_eq!(orbit.distance, orbit.max_distance);
+}
+
+#[test]
+fn fly_movement_is_frame_rate_independent() {
+    let start = Camera::default();
+    let fly = FlyControls::from_camera(&start);
+    assert!(close(fly.forward(), vec3(0.0, 0.0, -1.0)));
+
+    let mut slow = start;
+    fly.update(&mut slow, vec3(0.0, 0.0, 1.0), 0.5);
+    let mut fast = start;
+    for _ in 0..10 {
+        fly.update(&mut fast, vec3(0.0, 0.0, 1.0), 0.05);
+    }
+    assert!(close(slow.position, fast.position));
+    assert!(
+        close(slow.position, vec3(0.0, 0.0, 4.0)),
+        "{}",
+        slow.position
+    );
+
+    // 斜着走的速度不变
+    let mut diagonal = start;
+    fly.update(&mut diagonal, vec3(1.0, 0.0, 1.0), 1.0);
+    assert!(((diagonal.position - start.position).norm() - fly.speed).abs() < 1e-4);
+    assert!(diagonal.position.x > 0.0 && diagonal.position.z < 5.0);
+}
+
+#[test]
+fn fly_look_turns_and_clamps_pitch() {
+    let mut camera = Camera::default();
+    let mut fly = FlyControls::from_camera(&camera);
+    // 向右移动整个屏幕宽度，转过 sensitivity 角度
+    fly.look(vec2(2.0, 0.0));
+    assert!(
+        close(fly.forward(), vec3(1.0, 0.0, 0.0)),
+        "{}",
+        fly.forward()
+    );
+    fly.update(&mut camera, Vec3::zeros(), 0.016);
+    assert!(close(camera.forward(), vec3(1.0, 0.0, 0.0)));
+    assert_eq!(camera.position, vec3(0.0, 0.0, 5.0));
+
+    // 不会翻过头顶
+    fly.look(vec2(0.0, 100.0));
+    assert!(fly.pitch < 90f32.to_radians());
+    fly.update(&mut camera, vec3(0.0, 0.0, 1.0), 1.0);
+    assert!(camera.position.y > 1.9);
+    assert!(camera.view_matrix().iter().all(|x| x.is_finite()));
+    // 从相机恢复的角度一致
+    let copy = FlyControls::from_camera(&camera);
+    assert!((copy.yaw - fly.yaw).abs() < 1e-3 && (copy.pitch - fly.pitch).abs() < 1e-3);
+}
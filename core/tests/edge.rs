@@ -0,0 +1,100 @@
+use games101_core::edge::EdgeTriangle;
+use nalgebra_glm::{vec2, vec4, Vec2, Vec4};
+
+const SIZE: usize = 64;
+
+/// 每个像素被覆盖的次数，`quads` 为真时按 2x2 像素块遍历
+fn coverage(triangles: &[[Vec2; 3]], quads: bool) -> Vec<u32> {
+    let mut counts = vec![0; SIZE * SIZE];
+    let max = SIZE as u32 - 1;
+    for t in triangles {
+        let v: [Vec4; 3] = t.map(|p| vec4(p.x, p.y, 0.0, 1.0));
+        let edge = EdgeTriangle::new(&v).unwrap();
+        if quads {
+            edge.for_each_quad(0, 0, max, max, |x, y, _, mask| {
+                for i in (0..4).filter(|&i| mask[i]) {
+                    let (px, py) = (x as usize + (i & 1), y as usize + (i >> 1));
+                    counts[py * SIZE + px] += 1;
+                }
+            });
+        } else {
+            edge.for_each_pixel(0, 0, max, max, |x, y, _| {
+                counts[y as usize * SIZE + x as usize] += 1;
+            });
+        }
+    }
+    counts
+}
+
+/// 检查没有像素被覆盖两次，并且凸多边形 `outline`（逆时针）内部的像素都恰好被覆盖一次。
+/// 离外轮廓不到 0.01 像素的像素中心是否覆盖取决于 top-left 规则，不检查
+fn assert_covered_once(outline: &[Vec2], counts: &[u32], label: &str) {
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let count = counts[y * SIZE + x];
+            assert!(
+                count <= 1,
+                "{}: pixel ({}, {}) covered {} times",
+                label,
+                x,
+                y,
+                count
+            );
+            let p = vec2(x as f32 + 0.5, y as f32 + 0.5);
+            let inside = (0..outline.len()).all(|i| {
+                let (a, b) = (outline[i], outline[(i + 1) % outline.len()]);
+                let (e, d) = (b - a, p - a);
+                (e.x * d.y - e.y * d.x) / e.norm() > 0.01
+            });
+            if inside {
+                assert_eq!(count, 1, "{}: gap at pixel ({}, {})", label, x, y);
+            }
+        }
+    }
+}
+
+fn rotate(v: Vec2, degrees: f32) -> Vec2 {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    vec2(v.x * cos - v.y * sin, v.x * sin + v.y * cos)
+}
+
+#[test]
+fn rotated_quad_diagonal_is_covered_once() {
+    // 0° 和 45° 时对角线正好穿过一排像素中心
+    let center = vec2(32.5, 32.5);
+    for degrees in [0.0, 30.0, 45.0, 90.0, 117.0, 200.0] {
+        let quad = [
+            vec2(22.0, 0.0),
+            vec2(0.0, 22.0),
+            vec2(-22.0, 0.0),
+            vec2(0.0, -22.0),
+        ]
+        .map(|p| center + rotate(p, degrees));
+        // 沿对角线 0-2 拆开，第二个三角形的顶点顺序是顺时针
+        let triangles = [[quad[0], quad[1], quad[2]], [quad[0], quad[3], quad[2]]];
+        for quads in [false, true] {
+            let label = format!("{}° quads={}", degrees, quads);
+            assert_covered_once(&quad, &coverage(&triangles, quads), &label);
+        }
+    }
+}
+
+#[test]
+fn triangle_fan_around_shared_vertex_is_covered_once() {
+    // 公共顶点分别在像素中心、像素角和任意位置
+    for center in [vec2(32.5, 32.5), vec2(32.0, 32.0), vec2(31.3, 33.7)] {
+        for n in [3, 5, 8, 12] {
+            let outline: Vec<Vec2> = (0..n)
+                .map(|i| center + rotate(vec2(25.0, 0.0), 10.0 + 360.0 * i as f32 / n as f32))
+                .collect();
+            let triangles: Vec<[Vec2; 3]> = (0..n)
+                .map(|i| [center, outline[i], outline[(i + 1) % n]])
+                .collect();
+            for quads in [false, true] {
+                let label = format!("fan of {} around {:?} quads={}", n, center, quads);
+                let counts = coverage(&triangles, quads);
+                assert_covered_once(&outline, &counts, &label);
+            }
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use games101_core::color::Color;
+use games101_core::rasterizer::{Buffers, Exercises, Primitive, RasterMode, Rasterizer};
+use games101_core::triangle::Triangle;
+use nalgebra_glm::vec3;
+
+/// 覆盖整个视口中心的三角形
+fn load_triangle(r: &mut Rasterizer, color: Color) -> impl Fn(&mut Rasterizer, Primitive) {
+    let pos = r.load_positions(vec![
+        vec3(-0.8, -0.8, 0.0),
+        vec3(0.8, -0.8, 0.0),
+        vec3(0.0, 0.8, 0.0),
+    ]);
+    let ind = r.load_indices(vec![vec3(0.0, 1.0, 2.0)]);
+    let col = r.load_colors(vec![color, Color::GREEN, Color::BLUE]);
+    move |r: &mut Rasterizer, primitive| {
+        r.clear(Buffers::all());
+        r.draw(pos, ind, col, primitive);
+    }
+}
+
+/// 只画包围盒的左下角
+fn corner(r: &mut Rasterizer, t: &Triangle) {
+    let v = t.to_vector4();
+    r.set_pixel(v[0].x as u32, v[0].y as u32, Color::WHITE, 0.0);
+}
+
+#[test]
+fn bounding_box_mode_calls_exercises() {
+    let mut r = Rasterizer::new(16, 16);
+    let draw = load_triangle(&mut r, Color::RED);
+    r.set_exercises(Exercises {
+        inside_triangle: |_, _, _| true,
+        rasterize_triangle: corner,
+    });
+    r.set_raster_mode(RasterMode::BoundingBox);
+    draw(&mut r, Primitive::Triangle);
+    let lit: Vec<usize> = (0..256)
+        .filter(|&i| r.framebuffer()[i] != Color::BLACK)
+        .collect();
+    // 左下角 (1, 1)，帧缓冲从上到下存放
+    assert_eq!(lit, vec![14 * 16 + 1]);
+
+    // 多线程时同样串行调用作业中的函数
+    r.set_threads(4);
+    draw(&mut r, Primitive::Triangle);
+    let lit: Vec<usize> = (0..256)
+        .filter(|&i| r.framebuffer()[i] != Color::BLACK)
+        .collect();
+    assert_eq!(lit, vec![14 * 16 + 1]);
+}
+
+#[test]
+#[should_panic(expected = "set_exercises")]
+fn exercises_default_to_unimplemented() {
+    let mut r = Rasterizer::new(16, 16);
+    r.set_raster_mode(RasterMode::BoundingBox);
+    let draw = load_triangle(&mut r, Color::RED);
+    draw(&mut r, Primitive::Triangle);
+}
+
+#[test]
+fn default_rasterizer_draws_triangles() {
+    // 没有调用 set_exercises 的光栅化器使用内置的边函数
+    let mut r = Rasterizer::new(16, 16);
+    assert_eq!(r.raster_mode(), RasterMode::EdgeFunction);
+    let draw = load_triangle(&mut r, Color::RED);
+    draw(&mut r, Primitive::Triangle);
+    assert_ne!(r.framebuffer()[8 * 16 + 8], Color::BLACK);
+    assert_eq!(r.framebuffer()[0], Color::BLACK);
+    assert_eq!(r.stats().rasterized, 1);
+}
+
+#[test]
+fn line_primitive_draws_wireframe() {
+    let mut r = Rasterizer::new(16, 16);
+    let draw = load_triangle(&mut r, Color::RED);
+    // 线框不需要作业中的函数
+    draw(&mut r, Primitive::Line);
+    let count = |c: Color| r.framebuffer().iter().filter(|&&p| p == c).count();
+    // 底边是红色，两条斜边是绿色和蓝色，内部为空
+    assert!(count(Color::RED) >= 12);
+    assert!(count(Color::GREEN) > 5 && count(Color::BLUE) > 5);
+    assert_eq!(r.framebuffer()[8 * 16 + 8], Color::BLACK);
+    assert_eq!(r.stats().rasterized, 1);
+}
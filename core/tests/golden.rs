@@ -0,0 +1,287 @@
+//! 把典型场景的渲染结果与 `tests/golden` 中的参考图像比较。
+//!
+//! 有意修改渲染结果后，用 `GOLDEN_UPDATE=1 cargo test -p games101-core --test golden`
+//! 重新生成参考图像，检查无误后一起提交。比较失败时实际结果和差异图保存在
+//! `target/tmp/golden` 中。
+
+use games101_core::color::Color;
+use games101_core::lighting::{Light, Material, ShadingMode};
+use games101_core::rasterizer::{Primitive, RasterMode, Rasterizer};
+use games101_core::texture::Texture;
+use games101_core::{metrics, obj, ply, transform};
+use nalgebra_glm::{vec3, Mat4, Vec3};
+use std::path::{Path, PathBuf};
+
+const SIZE: u32 = 128;
+
+/// 允许的误差
+#[derive(Debug, Clone, Copy)]
+struct Tolerance {
+    /// 每个通道允许的差值
+    channel: u8,
+    /// 超出 `channel` 的像素允许的个数
+    pixels: usize,
+}
+
+impl Tolerance {
+    const EXACT: Tolerance = Tolerance {
+        channel: 0,
+        pixels: 0,
+    };
+    /// 浮点运算顺序不同时三角形边缘可能差一两个像素
+    const EDGES: Tolerance = Tolerance {
+        channel: 2,
+        pixels: 16,
+    };
+}
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+fn failure_dir() -> PathBuf {
+    Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden")
+}
+
+/// 把 `r` 的帧缓冲与参考图像 `name.png` 比较，失败时保存实际结果和差异图并返回说明
+fn check_golden(name: &str, r: &Rasterizer, tolerance: Tolerance) -> Result<(), String> {
+    let path = golden_dir().join(format!("{}.png", name));
+    let expected = image::open(&path)
+        .map_err(|e| {
+            format!(
+                "cannot read {}: {}; run with GOLDEN_UPDATE=1 to create it",
+                path.display(),
+                e
+            )
+        })?
+        .to_rgba8();
+    let (width, height) = (r.width(), r.height());
+    if expected.dimensions() != (width, height) {
+        return Err(format!(
+            "{}: expected a {}x{} image, rendered {}x{}",
+            name,
+            expected.width(),
+            expected.height(),
+            width,
+            height
+        ));
+    }
+
+    let actual = r.framebuffer_u8();
+    let mut diff = Vec::with_capacity(actual.len());
+    let mut mismatched = 0;
+    let mut max_difference = 0;
+    for (a, e) in actual.chunks(4).zip(expected.as_raw().chunks(4)) {
+        let difference = a.iter().zip(e).map(|(a, e)| a.abs_diff(*e)).max().unwrap();
+        max_difference = max_difference.max(difference);
+        if difference > tolerance.channel {
+            mismatched += 1;
+            diff.extend([255, 0, 0, 255]);
+        } else {
+            // 相同的像素画成变暗的灰度，方便看出差异的位置
+            let gray = ((e[0] as u32 + e[1] as u32 + e[2] as u32) / 9) as u8;
+            diff.extend([gray, gray, gray, 255]);
+        }
+    }
+    if mismatched <= tolerance.pixels {
+        return Ok(());
+    }
+
+    let dir = failure_dir();
+    std::fs::create_dir_all(&dir).unwrap();
+    let save = |suffix: &str, data: &[u8]| {
+        let path = dir.join(format!("{}.{}.png", name, suffix));
+        image::save_buffer(&path, data, width, height, image::ExtendedColorType::Rgba8).unwrap();
+        path
+    };
+    save("actual", actual);
+    let diff_path = save("diff", &diff);
+    let (a, e) = (r.framebuffer(), bytemuck::cast_slice(expected.as_raw()));
+    Err(format!(
+        "{}: {} pixels differ by more than {} (max difference {}, {} allowed, PSNR {:.2} dB, FLIP {:.4}); see {}",
+        name,
+        mismatched,
+        tolerance.channel,
+        max_difference,
+        tolerance.pixels,
+        metrics::psnr(a, e),
+        metrics::flip(a, e, width, height, metrics::DEFAULT_PPD),
+        diff_path.display()
+    ))
+}
+
+/// 设置了 `GOLDEN_UPDATE` 时用 `r` 的帧缓冲覆盖参考图像，否则调用 [`check_golden`]
+fn assert_golden(name: &str, r: &Rasterizer, tolerance: Tolerance) {
+    if std::env::var_os("GOLDEN_UPDATE").is_some() {
+        let path = golden_dir().join(format!("{}.png", name));
+        r.save_framebuffer_to_png(path.to_str().unwrap()).unwrap();
+        return;
+    }
+    if let Err(message) = check_golden(name, r, tolerance) {
+        panic!("{}", message);
+    }
+}
+
+/// 作业框架中的相机：位于 (0, 0, 5) 看向 -z，视角 45°
+fn camera(r: &mut Rasterizer, model: Mat4) {
+    r.set_model(model);
+    r.set_view(transform::translation(vec3(0.0, 0.0, -5.0)));
+    r.set_projection(transform::perspective(45f32.to_radians(), 1.0, 0.1, 50.0));
+}
+
+/// 作业 1 的三角形线框
+fn assignment1(mode: RasterMode) -> Rasterizer {
+    let mut r = Rasterizer::new(SIZE, SIZE);
+    r.set_raster_mode(mode);
+    let pos = r.load_positions(vec![
+        vec3(2.0, 0.0, -2.0),
+        vec3(0.0, 2.0, -2.0),
+        vec3(-2.0, 0.0, -2.0),
+    ]);
+    let ind = r.load_indices(vec![vec3(0.0, 1.0, 2.0)]);
+    let col = r.load_colors(vec![Color::RED, Color::GREEN, Color::BLUE]);
+    camera(&mut r, transform::rotation_z(20f32.to_radians()));
+    r.draw(pos, ind, col, Primitive::Line);
+    r
+}
+
+/// 作业 2 中互相遮挡的两个三角形
+fn assignment2(mode: RasterMode) -> Rasterizer {
+    let mut r = Rasterizer::new(SIZE, SIZE);
+    r.set_raster_mode(mode);
+    let pos = r.load_positions(vec![
+        vec3(2.0, 0.0, -2.0),
+        vec3(0.0, 2.0, -2.0),
+        vec3(-2.0, 0.0, -2.0),
+        vec3(3.5, -1.0, -5.0),
+        vec3(2.5, 1.5, -5.0),
+        vec3(-1.0, 0.5, -5.0),
+    ]);
+    let ind = r.load_indices(vec![vec3(0.0, 1.0, 2.0), vec3(3.0, 4.0, 5.0)]);
+    let green = Color::new_rgb(217, 238, 185);
+    let blue = Color::new_rgb(185, 217, 238);
+    let col = r.load_colors(vec![green, green, green, blue, blue, blue]);
+    camera(&mut r, Mat4::identity());
+    r.draw(pos, ind, col, Primitive::Triangle);
+    r
+}
+
+fn lit(r: &mut Rasterizer, shading: ShadingMode) {
+    r.set_material(Some(Material::default()));
+    r.set_lights(vec![
+        Light::point(vec3(20.0, 20.0, 20.0), Vec3::repeat(1500.0)),
+        Light::point(vec3(-20.0, 20.0, 0.0), Vec3::repeat(1500.0)),
+    ]);
+    r.set_shading_mode(shading);
+}
+
+/// 8x8 的黑白棋盘格
+fn checkerboard() -> Texture {
+    let data = (0..64)
+        .map(|i| {
+            if (i % 8 + i / 8) % 2 == 0 {
+                Color::WHITE
+            } else {
+                Color::new_rgb(40, 40, 40)
+            }
+        })
+        .collect();
+    Texture::new(8, 8, data)
+}
+
+fn mesh_model() -> Mat4 {
+    transform::rotation_x(25f32.to_radians()) * transform::rotation_y(35f32.to_radians())
+}
+
+#[test]
+fn assignment1_wireframe() {
+    assert_golden(
+        "assignment1_wireframe",
+        &assignment1(RasterMode::BoundingBox),
+        Tolerance::EXACT,
+    );
+}
+
+#[test]
+fn assignment2_triangles() {
+    assert_golden(
+        "assignment2_triangles",
+        &assignment2(RasterMode::EdgeFunction),
+        Tolerance::EXACT,
+    );
+    // 扫描线只在边缘上可能有差别。包围盒方式调用的是作业中的函数，不在这里测试
+    let r = assignment2(RasterMode::Scanline);
+    check_golden("assignment2_triangles", &r, Tolerance::EDGES).unwrap();
+}
+
+#[test]
+fn textured_cube() {
+    let mesh = obj::load(golden_dir().join("cube.obj")).unwrap();
+    assert_eq!(mesh.indices.len(), 12);
+    let render = |threads| {
+        let mut r = Rasterizer::new(SIZE, SIZE);
+        r.set_raster_mode(RasterMode::EdgeFunction);
+        r.set_threads(threads);
+        lit(&mut r, ShadingMode::Phong);
+        let texture = r.load_texture(checkerboard());
+        r.bind_texture(Some(texture));
+        let buffers = r.load_mesh(&mesh);
+        camera(&mut r, mesh_model());
+        r.draw_mesh(&buffers);
+        r
+    };
+    assert_golden("textured_cube", &render(1), Tolerance::EXACT);
+    // 分块的多线程光栅化与单线程的结果相同
+    check_golden("textured_cube", &render(4), Tolerance::EXACT).unwrap();
+}
+
+#[test]
+fn colored_octahedron() {
+    let mut mesh = ply::load(golden_dir().join("octahedron.ply")).unwrap();
+    mesh.compute_normals();
+    let mut r = Rasterizer::new(SIZE, SIZE);
+    r.set_raster_mode(RasterMode::Scanline);
+    lit(&mut r, ShadingMode::Gouraud);
+    let buffers = r.load_mesh(&mesh);
+    camera(&mut r, mesh_model() * transform::scaling(Vec3::repeat(1.5)));
+    r.draw_mesh(&buffers);
+    assert_golden("colored_octahedron", &r, Tolerance::EXACT);
+}
+
+#[test]
+fn mismatch_writes_diff_image() {
+    let mut r = assignment2(RasterMode::EdgeFunction);
+    // 在没有三角形的角落画一块不同的颜色
+    for y in 0..4 {
+        for x in 0..5 {
+            r.set_pixel(x, y, Color::WHITE, 0.0);
+        }
+    }
+    let message = check_golden("assignment2_triangles", &r, Tolerance::EDGES).unwrap_err();
+    assert!(
+        message.contains("20 pixels differ by more than 2"),
+        "{}",
+        message
+    );
+    assert!(message.contains("dB, FLIP 0.0"), "{}", message);
+    let diff = image::open(failure_dir().join("assignment2_triangles.diff.png"))
+        .unwrap()
+        .to_rgba8();
+    // 屏幕的 y 轴向上，帧缓冲的最后一行是 y = 0
+    assert_eq!(diff.get_pixel(0, SIZE - 1).0, [255, 0, 0, 255]);
+    assert_ne!(diff.get_pixel(64, 64).0, [255, 0, 0, 255]);
+    assert!(failure_dir()
+        .join("assignment2_triangles.actual.png")
+        .exists());
+
+    // 参考图像大小不同或者不存在时也报错
+    let small = Rasterizer::new(4, 4);
+    assert!(
+        check_golden("assignment2_triangles", &small, Tolerance::EDGES)
+            .unwrap_err()
+            .contains("expected a 128x128 image")
+    );
+    assert!(check_golden("missing", &small, Tolerance::EXACT)
+        .unwrap_err()
+        .contains("GOLDEN_UPDATE=1"));
+}
@@ -0,0 +1,292 @@
+use games101_core::color::Color;
+use games101_core::lighting::{Attenuation, Light, Lighting, Material, ShadingMode};
+use games101_core::rasterizer::{Buffers, Primitive, RasterMode, Rasterizer};
+use nalgebra_glm::{vec3, Vec3};
+
+/// 只有漫反射的白色材质
+fn diffuse_only() -> Material {
+    Material {
+        ka: Vec3::zeros(),
+        kd: vec3(1.0, 1.0, 1.0),
+        ks: Vec3::zeros(),
+        shininess: 1.0,
+    }
+}
+
+fn lighting(material: Material, lights: Vec<Light>) -> Lighting {
+    Lighting {
+        material,
+        lights,
+        ambient: Vec3::zeros(),
+        shadows: vec![],
+        pbr: None,
+    }
+}
+
+const ORIGIN: Vec3 = Vec3::new(0.0, 0.0, 0.0);
+const UP: Vec3 = Vec3::new(0.0, 0.0, 1.0);
+
+#[test]
+fn diffuse_follows_lambert() {
+    let white = vec3(1.0, 1.0, 1.0);
+    let head_on = lighting(
+        diffuse_only(),
+        vec![Light::directional(vec3(0.0, 0.0, -1.0), white)],
+    );
+    let grazing = lighting(
+        diffuse_only(),
+        vec![Light::directional(vec3(1.0, 0.0, -1.0), white)],
+    );
+    let behind = lighting(
+        diffuse_only(),
+        vec![Light::directional(vec3(0.0, 0.0, 1.0), white)],
+    );
+    assert_eq!(head_on.shade(ORIGIN, UP, Color::WHITE), Color::WHITE);
+    // cos 45° ≈ 0.707，法线不需要是单位向量
+    assert_eq!(grazing.shade(ORIGIN, UP * 3.0, Color::WHITE).r, 180);
+    assert_eq!(behind.shade(ORIGIN, UP, Color::WHITE), Color::BLACK);
+    // 漫反射系数与物体颜色相乘
+    let red = head_on.shade(ORIGIN, UP, Color::new_rgb(200, 0, 0));
+    assert_eq!((red.r, red.g, red.b), (200, 0, 0));
+    // 没有法线时不计算光照
+    assert_eq!(behind.shade(ORIGIN, Vec3::zeros(), Color::RED), Color::RED);
+}
+
+#[test]
+fn ambient_and_specular() {
+    let mut material = Material {
+        ka: vec3(0.1, 0.1, 0.1),
+        ..diffuse_only()
+    };
+    let mut l = lighting(material, vec![]);
+    l.ambient = vec3(2.0, 2.0, 2.0);
+    // 环境光与物体颜色无关
+    assert_eq!(l.shade(ORIGIN, UP, Color::BLACK).r, 51);
+
+    // 视线和光线都沿法线方向时半程向量与法线重合，高光最强
+    material = Material {
+        kd: Vec3::zeros(),
+        ks: vec3(0.5, 0.5, 0.5),
+        shininess: 100.0,
+        ..diffuse_only()
+    };
+    let light = Light::point(vec3(0.0, 0.0, 1.0), vec3(1.0, 1.0, 1.0));
+    let eye = vec3(0.0, 0.0, 0.0);
+    let l = lighting(material, vec![light]);
+    assert_eq!(l.shade(eye - UP, UP, Color::BLACK).r, 32);
+    // 偏离后高光迅速减弱
+    let p = vec3(0.5, 0.0, -1.0);
+    assert!(l.shade(p, UP, Color::BLACK).r < 5);
+}
+
+#[test]
+fn point_light_attenuation() {
+    let white = vec3(1.0, 1.0, 1.0);
+    // 默认按距离平方衰减
+    let l = lighting(
+        diffuse_only(),
+        vec![Light::point(vec3(0.0, 0.0, 2.0), white * 4.0)],
+    );
+    assert_eq!(l.shade(ORIGIN, UP, Color::WHITE), Color::WHITE);
+    let l = lighting(
+        diffuse_only(),
+        vec![Light::point(vec3(0.0, 0.0, 4.0), white * 4.0)],
+    );
+    assert_eq!(l.shade(ORIGIN, UP, Color::WHITE).r, 64);
+
+    let constant = Light::Point {
+        position: vec3(0.0, 0.0, 100.0),
+        intensity: white * 0.5,
+        attenuation: Attenuation::NONE,
+    };
+    let l = lighting(diffuse_only(), vec![constant]);
+    assert_eq!(l.shade(ORIGIN, UP, Color::WHITE).r, 128);
+    assert_eq!(
+        Attenuation {
+            constant: 1.0,
+            linear: 1.0,
+            quadratic: 0.0
+        }
+        .factor(3.0),
+        0.25
+    );
+}
+
+#[test]
+fn spot_light_cone() {
+    let spot = Light::spot(
+        vec3(0.0, 0.0, 1.0),
+        vec3(0.0, 0.0, -1.0),
+        vec3(1.0, 1.0, 1.0),
+        20f32.to_radians(),
+        40f32.to_radians(),
+    );
+    let l = lighting(
+        Material {
+            ks: Vec3::zeros(),
+            ..diffuse_only()
+        },
+        vec![spot],
+    );
+    // 只看锥角造成的衰减：把照射方向与法线的夹角固定为 0
+    let at = |angle: f32| {
+        let p = vec3(angle.to_radians().tan(), 0.0, 0.0);
+        let n = vec3(0.0, 0.0, 1.0) - p;
+        let distance2 = n.norm_squared();
+        l.shade(p, n, Color::WHITE).r as f32 * distance2
+    };
+    assert!((at(0.0) - 255.0).abs() < 1.0);
+    assert!((at(15.0) - 255.0).abs() < 2.0);
+    let middle = at(30.0);
+    assert!(middle > 20.0 && middle < 240.0, "{}", middle);
+    assert_eq!(at(45.0), 0.0);
+}
+
+/// 在相机前方画一块朝向 (1, 1, 1) 的正方形，模型矩阵带有非均匀缩放
+fn draw_tilted_quad(r: &mut Rasterizer) {
+    let positions = vec![
+        vec3(-0.5, -0.5, 1.0),
+        vec3(0.5, -0.5, 0.0),
+        vec3(0.5, 0.5, -1.0),
+        vec3(-0.5, 0.5, 0.0),
+    ];
+    let normals = vec![vec3(1.0, 1.0, 1.0).normalize(); 4];
+    let pos = r.load_positions(positions);
+    let ind = r.load_indices(vec![vec3(0.0, 1.0, 2.0), vec3(0.0, 2.0, 3.0)]);
+    let col = r.load_colors(vec![Color::WHITE; 4]);
+    let norm = r.load_normals(normals);
+    r.bind_normals(Some(norm));
+    r.set_model(nalgebra_glm::scaling(&vec3(2.0, 1.0, 1.0)));
+    r.set_view(nalgebra_glm::translation(&vec3(0.0, 0.0, -3.0)));
+    r.set_projection(nalgebra_glm::perspective_rh_no(
+        1.0,
+        90f32.to_radians(),
+        0.1,
+        50.0,
+    ));
+    r.clear(Buffers::all());
+    r.draw(pos, ind, col, Primitive::Triangle);
+}
+
+#[test]
+fn normals_use_inverse_transpose() {
+    let mut r = Rasterizer::new(32, 32);
+    r.set_raster_mode(RasterMode::EdgeFunction);
+    draw_tilted_quad(&mut r);
+    // 没有设置材质时不计算光照，顶点颜色插值有舍入误差
+    assert!(r.framebuffer()[16 * 32 + 16].r > 250);
+
+    // 光线方向与直接用模型矩阵变换的法线 (2, 1, 1) 垂直，
+    // 但与正确的法线 (0.5, 1, 1) 的夹角余弦为 1/√3
+    r.set_material(Some(diffuse_only()));
+    r.set_lights(vec![Light::directional(
+        vec3(1.0, -1.0, -1.0),
+        vec3(1.0, 1.0, 1.0),
+    )]);
+    draw_tilted_quad(&mut r);
+    let c = r.framebuffer()[16 * 32 + 16];
+    assert!((145..=150).contains(&c.r), "{:?}", c);
+
+    // 光源在世界空间中指定，随观察矩阵变换到观察空间
+    r.set_lights(vec![Light::point(
+        vec3(-100.0, 100.0, 100.0),
+        vec3(3.0e4, 3.0e4, 3.0e4),
+    )]);
+    draw_tilted_quad(&mut r);
+    assert!(r.framebuffer()[16 * 32 + 16].r > 100);
+    r.set_lights(vec![Light::point(
+        vec3(100.0, -100.0, -100.0),
+        vec3(3.0e4, 3.0e4, 3.0e4),
+    )]);
+    draw_tilted_quad(&mut r);
+    assert_eq!(r.framebuffer()[16 * 32 + 16].r, 0);
+}
+
+/// 正对相机的正方形，顶点法线向外倾斜约 43°，像一个拱起的曲面
+fn draw_dome(r: &mut Rasterizer) {
+    let positions = vec![
+        vec3(-1.0, -1.0, 0.0),
+        vec3(1.0, -1.0, 0.0),
+        vec3(1.0, 1.0, 0.0),
+        vec3(-1.0, 1.0, 0.0),
+    ];
+    let normals = positions.iter().map(|p| p + vec3(0.0, 0.0, 1.5)).collect();
+    let pos = r.load_positions(positions);
+    let ind = r.load_indices(vec![vec3(0.0, 1.0, 2.0), vec3(0.0, 2.0, 3.0)]);
+    let col = r.load_colors(vec![Color::WHITE; 4]);
+    let norm = r.load_normals(normals);
+    r.bind_normals(Some(norm));
+    r.set_view(nalgebra_glm::translation(&vec3(0.0, 0.0, -3.0)));
+    r.set_projection(nalgebra_glm::perspective_rh_no(
+        1.0,
+        90f32.to_radians(),
+        0.1,
+        50.0,
+    ));
+    r.clear(Buffers::all());
+    r.draw(pos, ind, col, Primitive::Triangle);
+}
+
+#[test]
+fn flat_shading_uses_face_normal() {
+    let mut r = Rasterizer::new(32, 32);
+    r.set_raster_mode(RasterMode::EdgeFunction);
+    assert_eq!(r.shading_mode(), ShadingMode::Phong);
+    r.set_material(Some(diffuse_only()));
+    r.set_lights(vec![Light::directional(
+        vec3(0.0, 0.0, -1.0),
+        vec3(1.0, 1.0, 1.0),
+    )]);
+
+    // 插值法线时越靠近边缘越暗
+    draw_dome(&mut r);
+    let (center, edge) = (r.framebuffer()[16 * 32 + 16], r.framebuffer()[16 * 32 + 11]);
+    assert!(
+        center.r > 250 && (150..240).contains(&edge.r),
+        "{:?} {:?}",
+        center,
+        edge
+    );
+
+    // 面法线正对光源，整个正方形同样亮，顶点颜色插值有舍入误差
+    r.set_shading_mode(ShadingMode::Flat);
+    draw_dome(&mut r);
+    let fb = r.framebuffer();
+    for i in [16 * 32 + 16, 16 * 32 + 11, 12 * 32 + 20, 20 * 32 + 12] {
+        assert!(fb[i].r > 250, "{}: {:?}", i, fb[i]);
+    }
+}
+
+#[test]
+fn gouraud_interpolates_vertex_lighting() {
+    let mut r = Rasterizer::new(32, 32);
+    r.set_raster_mode(RasterMode::EdgeFunction);
+    r.set_material(Some(Material {
+        kd: Vec3::zeros(),
+        ks: vec3(1.0, 1.0, 1.0),
+        shininess: 100.0,
+        ..diffuse_only()
+    }));
+    r.set_lights(vec![Light::directional(
+        vec3(0.0, 0.0, -1.0),
+        vec3(1.0, 1.0, 1.0),
+    )]);
+
+    // 逐像素计算时中心处的法线正对相机，出现高光
+    draw_dome(&mut r);
+    assert!(r.framebuffer()[16 * 32 + 16].r > 100);
+
+    // 顶点处几乎没有高光，插值后中心的高光也消失了
+    r.set_shading_mode(ShadingMode::Gouraud);
+    draw_dome(&mut r);
+    assert!(r.framebuffer()[16 * 32 + 16].r < 5);
+
+    // 只有漫反射时两种方式在顶点附近的结果接近
+    r.set_material(Some(diffuse_only()));
+    draw_dome(&mut r);
+    let gouraud = r.framebuffer()[28 * 32 + 28].r as i32;
+    r.set_shading_mode(ShadingMode::Phong);
+    draw_dome(&mut r);
+    let phong = r.framebuffer()[28 * 32 + 28].r as i32;
+    assert!((gouraud - phong).abs() < 10, "{} {}", gouraud, phong);
+}
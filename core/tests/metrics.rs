@@ -0,0 +1,217 @@
+use games101_core::color::Color;
+use games101_core::metrics::{self, ErrorMap, DEFAULT_PPD};
+use std::process::Command;
+
+const W: u32 = 32;
+const H: u32 = 32;
+
+/// 水平方向的灰度渐变，范围是 [40, 195]
+fn gradient() -> Vec<Color> {
+    (0..W * H)
+        .map(|i| {
+            let v = 40 + (i % W * 5) as u8;
+            Color::new_rgb(v, v, v)
+        })
+        .collect()
+}
+
+fn map(image: &[Color], f: impl Fn(usize, u8) -> u8) -> Vec<Color> {
+    image
+        .iter()
+        .enumerate()
+        .map(|(i, c)| Color::new_rgb(f(i, c.r), f(i, c.g), f(i, c.b)))
+        .collect()
+}
+
+/// 棋盘格状的 ±20 噪声
+fn noise(image: &[Color]) -> Vec<Color> {
+    map(image, |i, v| {
+        if (i + i / W as usize).is_multiple_of(2) {
+            v + 20
+        } else {
+            v - 20
+        }
+    })
+}
+
+/// 左边 `edge` 列是白色，其余是黑色
+fn edge(edge: f32) -> Vec<Color> {
+    (0..W * H)
+        .map(|i| {
+            let coverage = (edge - (i % W) as f32).clamp(0.0, 1.0);
+            let v = (255.0 * coverage).round() as u8;
+            Color::new_rgb(v, v, v)
+        })
+        .collect()
+}
+
+#[test]
+fn identical_images() {
+    let a = gradient();
+    let m = metrics::compare(&a, &a, W, H);
+    assert_eq!(m.mse, 0.0);
+    assert_eq!(m.psnr, f64::INFINITY);
+    assert!((m.ssim - 1.0).abs() < 1e-6, "{}", m.ssim);
+    assert_eq!(m.flip, 0.0);
+    assert_eq!(metrics::flip_map(&a, &a, W, H, DEFAULT_PPD).max(), 0.0);
+    assert_eq!(metrics::ssim_map(&a, &a, W, H).max(), 0.0);
+}
+
+#[test]
+fn mse_and_psnr() {
+    let a = gradient();
+    // 只有红色分量差 10
+    let b: Vec<Color> = a
+        .iter()
+        .map(|c| Color::new_rgb(c.r + 10, c.g, c.b))
+        .collect();
+    assert!((metrics::mse(&a, &b) - 100.0 / 3.0).abs() < 1e-9);
+    let expected = 10.0 * (255.0f64 * 255.0 * 3.0 / 100.0).log10();
+    assert!((metrics::psnr(&a, &b) - expected).abs() < 1e-9);
+    let diff = metrics::difference_map(&a, &b, W, H);
+    assert!(diff.values.iter().all(|&v| v == 10.0 / 255.0));
+    // 对称
+    assert_eq!(
+        metrics::compare(&a, &b, W, H),
+        metrics::compare(&b, &a, W, H)
+    );
+}
+
+#[test]
+fn ssim_prefers_structure() {
+    let a = gradient();
+    // 两张图像的 MSE 相同，但整体变亮保留了结构，噪声破坏了结构
+    let brighter = map(&a, |_, v| v + 20);
+    let noisy = noise(&a);
+    assert_eq!(metrics::mse(&a, &brighter), metrics::mse(&a, &noisy));
+    let (ssim_brighter, ssim_noisy) = (
+        metrics::ssim(&a, &brighter, W, H),
+        metrics::ssim(&a, &noisy, W, H),
+    );
+    assert!(ssim_brighter > 0.9, "{}", ssim_brighter);
+    assert!(ssim_noisy < 0.5, "{}", ssim_noisy);
+    let errors = metrics::ssim_map(&a, &noisy, W, H);
+    assert!(errors.values.iter().all(|v| (0.0..=1.0).contains(v)));
+}
+
+#[test]
+fn flip_is_perceptual() {
+    let black = vec![Color::BLACK; (W * H) as usize];
+    let white = vec![Color::WHITE; (W * H) as usize];
+    let flip = metrics::flip(&black, &white, W, H, DEFAULT_PPD);
+    assert!(flip > 0.9 && flip <= 1.0, "{}", flip);
+
+    // 抗锯齿的边缘与锯齿边缘很接近，移动了几个像素的边缘差别明显
+    let aliased = edge(16.0);
+    let antialiased = edge(16.5);
+    let shifted = edge(20.0);
+    let small = metrics::flip(&aliased, &antialiased, W, H, DEFAULT_PPD);
+    let large = metrics::flip(&aliased, &shifted, W, H, DEFAULT_PPD);
+    assert!(small > 0.0 && small * 4.0 < large, "{} {}", small, large);
+    // 误差集中在边缘附近
+    let errors = metrics::flip_map(&aliased, &shifted, W, H, DEFAULT_PPD);
+    let row = &errors.values[(H / 2 * W) as usize..][..W as usize];
+    assert!(
+        row[18] > 0.5 && row[2] < 0.05 && row[30] < 0.05,
+        "{:?}",
+        row
+    );
+
+    // 离屏幕越远（每度的像素越多），细小的噪声越不明显
+    let a = gradient();
+    let noisy = noise(&a);
+    let near = metrics::flip(&a, &noisy, W, H, 20.0);
+    let far = metrics::flip(&a, &noisy, W, H, 200.0);
+    assert!(far < near, "{} {}", far, near);
+}
+
+#[test]
+fn heat_maps_and_files() {
+    let errors = ErrorMap {
+        width: 3,
+        height: 1,
+        values: vec![0.0, 0.5, 1.0],
+    };
+    assert_eq!(errors.mean(), 0.5);
+    assert_eq!(
+        errors.heat_map(),
+        vec![
+            Color::new_rgb(0, 0, 4),
+            Color::new_rgb(183, 55, 121),
+            Color::new_rgb(252, 253, 191)
+        ]
+    );
+
+    let dir = std::env::temp_dir().join(format!("games101-metrics-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let save = |name: &str, image: &[Color]| {
+        let path = dir.join(name);
+        image::save_buffer(
+            &path,
+            bytemuck::cast_slice(image),
+            W,
+            H,
+            image::ExtendedColorType::Rgba8,
+        )
+        .unwrap();
+        path
+    };
+    let a = save("a.png", &edge(16.0));
+    let b = save("b.png", &edge(20.0));
+    let (width, height, loaded) = metrics::load_image(&a).unwrap();
+    assert_eq!((width, height), (W, H));
+    assert_eq!(loaded, edge(16.0));
+
+    // 命令行程序
+    let heat_map = dir.join("heat.png");
+    let output = Command::new(env!("CARGO_BIN_EXE_compare"))
+        .args([&a, &b])
+        .arg("--heat-map")
+        .arg(&heat_map)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("MSE   "), "{}", stdout);
+    for line in ["PSNR ", "SSIM ", "FLIP "] {
+        assert!(stdout.contains(line), "{}", stdout);
+    }
+    assert_eq!(
+        image::open(&heat_map).unwrap().to_rgba8().dimensions(),
+        (W, H)
+    );
+    let status = Command::new(env!("CARGO_BIN_EXE_compare"))
+        .arg(&a)
+        .output()
+        .unwrap()
+        .status;
+    assert_eq!(status.code(), Some(2));
+    // 缺少参数值是用法错误，而不是忽略这个选项
+    for option in ["--heat-map", "--map", "--ppd"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_compare"))
+            .args([&a, &b])
+            .arg(option)
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(2), "{}", option);
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains(&format!("missing value for {}", option)));
+    }
+    for (option, value) in [
+        ("--ppd", "abc"),
+        ("--ppd", "0"),
+        ("--ppd", "-3"),
+        ("--map", "rms"),
+    ] {
+        let output = Command::new(env!("CARGO_BIN_EXE_compare"))
+            .args([&a, &b])
+            .args([option, value])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(2), "{} {}", option, value);
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        let message = format!("compare: invalid value for {}: {}\n", option, value);
+        assert!(stderr.starts_with(&message), "{}", stderr);
+    }
+    std::fs::remove_dir_all(dir).unwrap();
+}
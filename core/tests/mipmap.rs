@@ -0,0 +1,209 @@
+use games101_core::color::Color;
+use games101_core::edge::EdgeTriangle;
+use games101_core::rasterizer::{Buffers, Primitive, RasterMode, Rasterizer};
+use games101_core::texture::{Filter, Texture};
+use nalgebra_glm::{vec2, vec3, vec4};
+
+/// 每个纹素黑白交替的棋盘格
+fn checkerboard(width: u32, height: u32) -> Texture {
+    let data = (0..width * height)
+        .map(|i| {
+            if (i % width + i / width).is_multiple_of(2) {
+                Color::WHITE
+            } else {
+                Color::BLACK
+            }
+        })
+        .collect();
+    Texture::new(width, height, data)
+}
+
+/// 每 4 行黑白交替、沿 u 方向不变的条纹
+fn stripes(size: u32) -> Texture {
+    let data = (0..size * size)
+        .map(|i| {
+            if (i / size / 4).is_multiple_of(2) {
+                Color::WHITE
+            } else {
+                Color::BLACK
+            }
+        })
+        .collect();
+    Texture::new(size, size, data)
+}
+
+fn is_gray(c: Color, tolerance: u8) -> bool {
+    [c.r, c.g, c.b]
+        .iter()
+        .all(|&x| x.abs_diff(128) <= tolerance)
+}
+
+#[test]
+fn mip_chain_halves_down_to_one_texel() {
+    let mut t = checkerboard(8, 4);
+    assert_eq!(t.mip_levels(), 1);
+    t.generate_mipmaps();
+    let sizes: Vec<_> = (0..t.mip_levels())
+        .map(|i| {
+            let (w, h, data) = t.mip_level(i).unwrap();
+            assert_eq!(data.len(), (w * h) as usize);
+            (w, h)
+        })
+        .collect();
+    assert_eq!(sizes, [(8, 4), (4, 2), (2, 1), (1, 1)]);
+    // 第一层之后每个纹素都是黑白各半
+    for level in 1..t.mip_levels() {
+        let (_, _, data) = t.mip_level(level).unwrap();
+        assert!(data.iter().all(|&c| is_gray(c, 1)), "level {}", level);
+    }
+
+    // 奇数尺寸也会一直缩小到 1x1，且不丢掉边缘的纹素
+    let mut t = Texture::new(3, 3, vec![Color::WHITE; 9]);
+    t.generate_mipmaps();
+    assert_eq!(t.mip_levels(), 2);
+    assert_eq!(t.mip_level(1).unwrap().2, [Color::WHITE]);
+}
+
+#[test]
+fn trilinear_selects_level_from_derivatives() {
+    let mut t = checkerboard(64, 64);
+    t.generate_mipmaps();
+    t.set_filter(Filter::Trilinear);
+    let (u, v) = (0.5 / 64.0, 1.0 - 0.5 / 64.0);
+
+    // 每个像素一个纹素时与原图一致
+    let texel = 1.0 / 64.0;
+    assert_eq!(
+        t.sample_grad(u, v, vec2(texel, 0.0), vec2(0.0, texel)),
+        Color::WHITE
+    );
+    // 缩小后取平均
+    let c = t.sample_grad(u, v, vec2(8.0 * texel, 0.0), vec2(0.0, 8.0 * texel));
+    assert!(is_gray(c, 1), "{:?}", c);
+    // 没有导数时只在原图上采样
+    assert_eq!(t.sample(u, v), Color::WHITE);
+}
+
+#[test]
+fn anisotropic_keeps_detail_along_minor_axis() {
+    let mut t = stripes(64);
+    t.generate_mipmaps();
+    let texel = 1.0 / 64.0;
+    // 第一条白色条纹的中心，足迹沿 u 方向拉长 16 倍
+    let (u, v) = (0.5, 1.0 - 2.0 * texel);
+    let (ddx, ddy) = (vec2(16.0 * texel, 0.0), vec2(0.0, texel));
+
+    t.set_filter(Filter::Trilinear);
+    let trilinear = t.sample_grad(u, v, ddx, ddy);
+    assert!(is_gray(trilinear, 1), "{:?}", trilinear);
+
+    t.set_filter(Filter::Anisotropic);
+    let anisotropic = t.sample_grad(u, v, ddx, ddy);
+    assert!(anisotropic.r > 240, "{:?}", anisotropic);
+
+    // 限制各向异性之后足迹被加宽，结果又变回模糊
+    t.set_max_anisotropy(1.0);
+    let clamped = t.sample_grad(u, v, ddx, ddy);
+    assert!(is_gray(clamped, 1), "{:?}", clamped);
+}
+
+#[test]
+fn quads_cover_the_same_pixels() {
+    let triangles = [
+        [
+            vec4(3.3, 2.7, 0.0, 1.0),
+            vec4(40.2, 9.9, 0.0, 1.0),
+            vec4(12.5, 37.1, 0.0, 1.0),
+        ],
+        [
+            vec4(5.0, 5.0, 0.0, 1.0),
+            vec4(5.0, 30.0, 0.0, 1.0),
+            vec4(31.0, 5.0, 0.0, 1.0),
+        ],
+    ];
+    for v in &triangles {
+        let edge = EdgeTriangle::new(v).unwrap();
+        for (x_min, y_min, x_max, y_max) in [(0, 0, 63, 63), (7, 4, 20, 21)] {
+            let mut pixels = vec![];
+            edge.for_each_pixel(x_min, y_min, x_max, y_max, |x, y, b| pixels.push((x, y, b)));
+
+            let mut quads = vec![];
+            edge.for_each_quad(x_min, y_min, x_max, y_max, |x, y, quad, mask| {
+                assert!(x % 2 == 0 && y % 2 == 0);
+                for i in (0..4).filter(|&i| mask[i]) {
+                    quads.push((x + (i & 1) as u32, y + (i >> 1) as u32, quad[i]));
+                }
+            });
+            pixels.sort_by_key(|&(x, y, _)| (y, x));
+            quads.sort_by_key(|&(x, y, _)| (y, x));
+            assert_eq!(pixels, quads);
+        }
+    }
+}
+
+/// 从相机高度 1 处看向远处的棋盘格地面
+fn render_ground(filter: Filter, threads: usize) -> Rasterizer {
+    const SIZE: u32 = 96;
+    let mut r = Rasterizer::new(SIZE, SIZE);
+    r.set_raster_mode(RasterMode::EdgeFunction);
+    r.set_threads(threads);
+    r.set_view(nalgebra_glm::translation(&vec3(0.0, -1.0, 0.0)));
+    r.set_projection(nalgebra_glm::perspective_rh_no(
+        1.0,
+        60f32.to_radians(),
+        0.1,
+        50.0,
+    ));
+    let pos = vec![
+        vec3(-40.0, 0.0, -1.0),
+        vec3(40.0, 0.0, -1.0),
+        vec3(40.0, 0.0, -80.0),
+        vec3(-40.0, 0.0, -80.0),
+    ];
+    let uv = pos.iter().map(|p| vec2(p.x, -p.z)).collect();
+    let mut texture = checkerboard(8, 8);
+    texture.generate_mipmaps();
+    texture.set_filter(filter);
+
+    let pos_id = r.load_positions(pos);
+    let ind_id = r.load_indices(vec![vec3(0.0, 1.0, 2.0), vec3(0.0, 2.0, 3.0)]);
+    let col_id = r.load_colors(vec![Color::WHITE; 4]);
+    let uv_id = r.load_tex_coords(uv);
+    let texture_id = r.load_texture(texture);
+    r.bind_tex_coords(Some(uv_id));
+    r.bind_texture(Some(texture_id));
+    r.clear(Buffers::all());
+    r.draw(pos_id, ind_id, col_id, Primitive::Triangle);
+    r
+}
+
+#[test]
+fn minified_ground_converges_to_average() {
+    // 地平线在屏幕中间，取紧挨着地平线下方的一行像素（帧缓冲的第一行是屏幕最上方）
+    let row = |r: &Rasterizer| {
+        let start = 50 * 96;
+        r.framebuffer()[start..start + 96].to_vec()
+    };
+    let nearest = row(&render_ground(Filter::Nearest, 1));
+    assert!(nearest.contains(&Color::WHITE));
+    assert!(nearest.contains(&Color::BLACK));
+
+    for filter in [Filter::Trilinear, Filter::Anisotropic] {
+        let filtered = row(&render_ground(filter, 1));
+        assert!(
+            filtered.iter().all(|&c| is_gray(c, 40)),
+            "{:?}: {:?}",
+            filter,
+            filtered
+        );
+    }
+}
+
+#[test]
+fn tiled_quads_match_serial() {
+    for filter in [Filter::Trilinear, Filter::Anisotropic] {
+        let serial = render_ground(filter, 1);
+        let tiled = render_ground(filter, 4);
+        assert_eq!(serial.framebuffer(), tiled.framebuffer());
+    }
+}
@@ -0,0 +1,237 @@
+use games101_core::color::Color;
+use games101_core::obj::{self, ObjError};
+use games101_core::rasterizer::{Buffers, RasterMode, Rasterizer};
+use nalgebra_glm::{vec2, vec3, Vec3};
+use std::path::PathBuf;
+
+/// 测试用的临时目录，每个测试使用不同的名字
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("games101-obj-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn area(a: Vec3, b: Vec3, c: Vec3) -> Vec3 {
+    (b - a).cross(&(c - a)) * 0.5
+}
+
+#[test]
+fn parses_attributes_and_shares_vertices() {
+    let mesh = obj::parse(
+        "
+        # 一个带纹理坐标和法线的四边形
+        v 0 0 0
+        v 1 0 0
+        v 1 1 0
+        v 0 1 0
+        vt 0 0
+        vt 1 0
+        vt 1 1
+        vt 0 1
+        vn 0 0 1
+        f 1/1/1 2/2/1 3/3/1 4/4/1
+        f -4/-4/-1 -2/-2/-1 -1/-1/-1
+        ",
+    )
+    .unwrap();
+    // 第二个面引用的 v/vt/vn 组合都已经出现过
+    assert_eq!(mesh.positions.len(), 4);
+    assert_eq!(mesh.indices, [[0, 1, 2], [0, 2, 3], [0, 2, 3]]);
+    assert_eq!(mesh.tex_coords[2], vec2(1.0, 1.0));
+    assert!(mesh.normals.iter().all(|&n| n == vec3(0.0, 0.0, 1.0)));
+    assert!(mesh.colors.is_empty());
+    assert_eq!(mesh.groups.len(), 1);
+    assert_eq!(mesh.groups[0].triangles, 0..3);
+}
+
+#[test]
+fn splits_vertices_with_different_attributes() {
+    let mesh = obj::parse(
+        "
+        v 0 0 0
+        v 1 0 0
+        v 0 1 0
+        v 0 0 1
+        vn 0 0 1
+        vn 0 1 0
+        f 1//1 2//1 3//1
+        f 1//2 4//2 2//2
+        ",
+    )
+    .unwrap();
+    // 顶点 1、2 在两个面上的法线不同，需要各自展开
+    assert_eq!(mesh.positions.len(), 6);
+    assert!(mesh.tex_coords.is_empty());
+    assert_eq!(mesh.normals[3], vec3(0.0, 1.0, 0.0));
+}
+
+#[test]
+fn triangulates_concave_polygons() {
+    // L 形六边形
+    let polygon = [
+        vec3(0.0, 0.0, 0.0),
+        vec3(2.0, 0.0, 0.0),
+        vec3(2.0, 1.0, 0.0),
+        vec3(1.0, 1.0, 0.0),
+        vec3(1.0, 2.0, 0.0),
+        vec3(0.0, 2.0, 0.0),
+    ];
+    // 在 yz 平面上并且顺时针排列也一样
+    for polygon in [
+        polygon.to_vec(),
+        polygon.iter().rev().map(|p| vec3(0.0, p.x, p.y)).collect(),
+    ] {
+        let triangles = obj::triangulate(&polygon);
+        assert_eq!(triangles.len(), 4);
+        let total = triangles
+            .iter()
+            .map(|&[a, b, c]| area(polygon[a], polygon[b], polygon[c]))
+            .fold(Vec3::zeros(), |sum, a| sum + a);
+        // 所有三角形与多边形同向，面积之和等于多边形面积 3
+        assert!((total.norm() - 3.0).abs() < 1e-5);
+        for &[a, b, c] in &triangles {
+            let n = area(polygon[a], polygon[b], polygon[c]);
+            assert!(n.dot(&total) > 0.0);
+        }
+    }
+}
+
+#[test]
+fn groups_and_materials() {
+    let dir = temp_dir("materials");
+    std::fs::write(
+        dir.join("scene.mtl"),
+        "
+        newmtl red
+        Kd 1 0 0
+        Ks 0.5 0.5 0.5
+        Ns 32
+        map_Kd -bm 1.0 red.png
+        newmtl blue
+        Kd 0 0 1
+        d 0.5
+        ",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("scene.obj"),
+        "
+        mtllib scene.mtl
+        v -1 -1 -1
+        v 1 -1 -1
+        v 1 1 -1
+        v -1 1 -1
+        g left
+        usemtl red
+        f 1 2 3
+        g right
+        usemtl blue
+        f 1 3 4
+        usemtl missing
+        ",
+    )
+    .unwrap();
+
+    let mesh = obj::load(dir.join("scene.obj")).unwrap();
+    let names: Vec<_> = mesh.materials.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, ["red", "blue", "missing"]);
+    let red = &mesh.materials[0];
+    assert_eq!(red.diffuse, vec3(1.0, 0.0, 0.0));
+    assert_eq!(red.shininess, 32.0);
+    assert_eq!(red.diffuse_texture, Some(dir.join("red.png")));
+    assert_eq!(mesh.materials[1].opacity, 0.5);
+
+    // 空的分组不会保留
+    assert_eq!(mesh.groups.len(), 2);
+    assert_eq!(
+        (mesh.groups[0].name.as_str(), mesh.groups[0].material),
+        ("left", Some(0))
+    );
+    assert_eq!(
+        (mesh.groups[1].name.as_str(), mesh.groups[1].material),
+        ("right", Some(1))
+    );
+    // 不同材质的分组不共享顶点
+    assert_eq!(mesh.positions.len(), 6);
+    assert_eq!(mesh.group_indices(1), [vec3(3.0, 4.0, 5.0)]);
+
+    // 没有顶点颜色时用材质的漫反射颜色绘制
+    let mut r = Rasterizer::new(32, 32);
+    r.set_raster_mode(RasterMode::EdgeFunction);
+    let buffers = r.load_mesh(&mesh);
+    r.clear(Buffers::all());
+    r.draw_mesh(&buffers);
+    let fb = r.framebuffer();
+    // 第一个三角形在右下方，第二个在左上方，顶点颜色插值有舍入误差
+    let (bottom_right, top_left) = (fb[31 * 32 + 31], fb[0]);
+    assert!(bottom_right.r > 250 && bottom_right.g == 0 && bottom_right.b == 0);
+    assert!(top_left.b > 250 && top_left.r == 0 && top_left.g == 0);
+    assert!(mesh.warnings.is_empty());
+
+    // 找不到的材质库记录为警告，网格照常读取
+    std::fs::write(
+        dir.join("missing.obj"),
+        "mtllib scene.mtl nowhere.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl red\nf 1 2 3\n",
+    )
+    .unwrap();
+    let mesh = obj::load(dir.join("missing.obj")).unwrap();
+    assert_eq!(mesh.indices.len(), 1);
+    assert_eq!(mesh.materials[0].diffuse, vec3(1.0, 0.0, 0.0));
+    assert_eq!(mesh.warnings.len(), 1);
+    assert!(
+        mesh.warnings[0].contains("nowhere.mtl"),
+        "{:?}",
+        mesh.warnings
+    );
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn missing_normals_are_computed() {
+    let mesh = obj::parse(
+        "
+        v 0 0 0
+        v 1 0 0
+        v 0 1 0
+        v 0 0 -1
+        vn 1 0 0
+        f 1 2 3
+        f 1//1 4//1 3//1
+        ",
+    )
+    .unwrap();
+    assert_eq!(mesh.normals.len(), mesh.positions.len());
+    assert_eq!(mesh.normals[1], vec3(0.0, 0.0, 1.0));
+    assert_eq!(mesh.normals[3], vec3(1.0, 0.0, 0.0));
+}
+
+#[test]
+fn vertex_colors_extension() {
+    let mesh = obj::parse(
+        "
+        v 0 0 0 1 0 0
+        v 1 0 0 0 1 0
+        v 0 1 0 0 0 1
+        f 1 2 3
+        ",
+    )
+    .unwrap();
+    assert_eq!(mesh.colors, [Color::RED, Color::GREEN, Color::BLUE]);
+}
+
+#[test]
+fn reports_line_numbers() {
+    let error = |source: &str| match obj::parse(source) {
+        Err(ObjError::Parse { line, .. }) => line,
+        other => panic!("{:?}", other),
+    };
+    assert_eq!(error("v 0 0 0\nv 1 0\n"), 2);
+    assert_eq!(error("v 0 0 0\nv 1 0 0\nv 0 1 0\n\nf 1 2 4\n"), 5);
+    assert_eq!(error("v 0 0 0\nv 1 0 0\nf 1 2\n"), 3);
+    assert_eq!(error("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1/1 2/1 3/1\n"), 4);
+    assert_eq!(error("v 0 0 zero\n"), 1);
+    assert!(matches!(
+        obj::load("/nonexistent/model.obj"),
+        Err(ObjError::Io(_))
+    ));
+}
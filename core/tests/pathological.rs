@@ -0,0 +1,279 @@
+use games101_core::color::Color;
+use games101_core::edge::EdgeTriangle;
+use games101_core::rasterizer::{Buffers, DrawStats, Primitive, RasterMode, Rasterizer};
+use nalgebra_glm::{vec3, vec4, Vec3};
+
+const SIZE: u32 = 32;
+
+/// 模型、视图、投影矩阵都是单位矩阵，顶点坐标直接就是 NDC 坐标
+fn draw(mode: RasterMode, threads: usize, pos: Vec<Vec3>, ind: Vec<Vec3>) -> Rasterizer {
+    let cols = vec![Color::WHITE; pos.len()];
+    draw_with_colors(mode, threads, pos, ind, cols)
+}
+
+fn draw_with_colors(
+    mode: RasterMode,
+    threads: usize,
+    pos: Vec<Vec3>,
+    ind: Vec<Vec3>,
+    cols: Vec<Color>,
+) -> Rasterizer {
+    let mut r = Rasterizer::new(SIZE, SIZE);
+    r.set_raster_mode(mode);
+    r.set_threads(threads);
+    let pos_id = r.load_positions(pos);
+    let ind_id = r.load_indices(ind);
+    let col_id = r.load_colors(cols);
+    r.clear(Buffers::all());
+    r.draw(pos_id, ind_id, col_id, Primitive::Triangle);
+    r
+}
+
+fn assert_blank(r: &Rasterizer) {
+    assert!(r.framebuffer().iter().all(|&c| c == Color::BLACK));
+}
+
+/// 所有模式下都应该在光栅化之前被剔除，因此也不会调用作业中的 `rasterize_triangle`
+fn assert_culled(pos: Vec<Vec3>, ind: Vec<Vec3>, expected: DrawStats) {
+    for mode in [RasterMode::BoundingBox, RasterMode::EdgeFunction] {
+        for threads in [1, 4] {
+            let r = draw(mode, threads, pos.clone(), ind.clone());
+            assert_blank(&r);
+            assert_eq!(r.stats(), expected, "{:?} with {} threads", mode, threads);
+        }
+    }
+}
+
+#[test]
+fn nan_vertices_are_culled() {
+    let pos = vec![
+        vec3(f32::NAN, 0.0, 0.0),
+        vec3(0.5, 0.5, 0.0),
+        vec3(-0.5, 0.5, 0.0),
+        vec3(0.0, 0.0, f32::NAN),
+    ];
+    let ind = vec![vec3(0.0, 1.0, 2.0), vec3(3.0, 1.0, 2.0)];
+    assert_culled(
+        pos,
+        ind,
+        DrawStats {
+            submitted: 2,
+            non_finite: 2,
+            vertices: 4,
+            ..Default::default()
+        },
+    );
+}
+
+#[test]
+fn infinite_vertices_are_culled() {
+    let pos = vec![
+        vec3(f32::INFINITY, 0.0, 0.0),
+        vec3(0.5, f32::NEG_INFINITY, 0.0),
+        vec3(-0.5, 0.5, 0.0),
+    ];
+    let ind = vec![vec3(0.0, 1.0, 2.0), vec3(1.0, 2.0, 2.0)];
+    assert_culled(
+        pos,
+        ind,
+        DrawStats {
+            submitted: 2,
+            non_finite: 2,
+            vertices: 3,
+            ..Default::default()
+        },
+    );
+}
+
+#[test]
+fn vertex_on_camera_plane_is_culled() {
+    // 投影后 w = 0，透视除法得到无穷大或 NaN
+    let mut r = Rasterizer::new(SIZE, SIZE);
+    r.set_projection(nalgebra_glm::perspective_rh_no(1.0, 1.0, 0.1, 50.0));
+    let pos_id = r.load_positions(vec![
+        vec3(0.0, 0.0, 0.0),
+        vec3(1.0, 0.0, -1.0),
+        vec3(0.0, 1.0, -1.0),
+    ]);
+    let ind_id = r.load_indices(vec![vec3(0.0, 1.0, 2.0)]);
+    let col_id = r.load_colors(vec![Color::WHITE; 3]);
+    r.clear(Buffers::all());
+    r.draw(pos_id, ind_id, col_id, Primitive::Triangle);
+    assert_blank(&r);
+    assert_eq!(r.stats().non_finite, 1);
+}
+
+#[test]
+fn zero_area_triangles_are_culled() {
+    let pos = vec![
+        vec3(-0.5, -0.5, 0.0),
+        vec3(0.0, 0.0, 0.0),
+        vec3(0.5, 0.5, 0.0),
+        vec3(0.2, -0.7, 0.3),
+    ];
+    // 共线、两点重合、三点重合
+    let ind = vec![
+        vec3(0.0, 1.0, 2.0),
+        vec3(0.0, 0.0, 3.0),
+        vec3(3.0, 3.0, 3.0),
+    ];
+    assert_culled(
+        pos,
+        ind,
+        DrawStats {
+            submitted: 3,
+            degenerate: 3,
+            vertices: 4,
+            ..Default::default()
+        },
+    );
+}
+
+#[test]
+fn invalid_indices_are_culled() {
+    let pos = vec![
+        vec3(-0.5, -0.5, 0.0),
+        vec3(0.5, -0.5, 0.0),
+        vec3(0.0, 0.5, 0.0),
+    ];
+    let ind = vec![
+        vec3(0.0, 1.0, 3.0),
+        vec3(-1.0, 1.0, 2.0),
+        vec3(0.0, f32::NAN, 2.0),
+        vec3(0.0, 1.5, 2.0),
+        vec3(0.0, 1.0, f32::INFINITY),
+        vec3(0.0, 1.0, 1e20),
+    ];
+    assert_culled(
+        pos,
+        ind,
+        DrawStats {
+            submitted: 6,
+            invalid_indices: 6,
+            ..Default::default()
+        },
+    );
+}
+
+#[test]
+fn short_color_buffer_is_treated_as_invalid_indices() {
+    let pos = vec![
+        vec3(-0.5, -0.5, 0.0),
+        vec3(0.5, -0.5, 0.0),
+        vec3(0.0, 0.5, 0.0),
+    ];
+    let r = draw_with_colors(
+        RasterMode::EdgeFunction,
+        1,
+        pos,
+        vec![vec3(0.0, 1.0, 2.0)],
+        vec![Color::WHITE; 2],
+    );
+    assert_blank(&r);
+    assert_eq!(r.stats().invalid_indices, 1);
+}
+
+#[test]
+fn huge_coordinates_are_culled() {
+    let pos = vec![
+        vec3(-1e30, -0.5, 0.0),
+        vec3(1e30, -0.5, 0.0),
+        vec3(0.0, 1e30, 0.0),
+        vec3(f32::MAX, f32::MAX, 0.0),
+        vec3(-f32::MAX, f32::MAX, 0.0),
+        vec3(0.0, -f32::MAX, 0.0),
+    ];
+    let ind = vec![vec3(0.0, 1.0, 2.0), vec3(3.0, 4.0, 5.0)];
+    for mode in [RasterMode::BoundingBox, RasterMode::EdgeFunction] {
+        let r = draw(mode, 1, pos.clone(), ind.clone());
+        assert_blank(&r);
+        assert_eq!(r.stats().rasterized, 0);
+    }
+}
+
+#[test]
+fn empty_viewport_does_not_panic() {
+    let mut r = Rasterizer::new(0, 0);
+    let pos_id = r.load_positions(vec![
+        vec3(-0.5, -0.5, 0.0),
+        vec3(0.5, -0.5, 0.0),
+        vec3(0.0, 0.5, 0.0),
+    ]);
+    let ind_id = r.load_indices(vec![vec3(0.0, 1.0, 2.0)]);
+    let col_id = r.load_colors(vec![Color::WHITE; 3]);
+    r.draw(pos_id, ind_id, col_id, Primitive::Triangle);
+    assert!(r.framebuffer().is_empty());
+}
+
+#[test]
+fn pathological_triangles_do_not_disturb_valid_ones() {
+    let valid = vec![
+        vec3(-0.8, -0.8, 0.0),
+        vec3(0.8, -0.6, 0.0),
+        vec3(0.1, 0.9, 0.0),
+    ];
+    let mut pos = valid.clone();
+    pos.extend([
+        vec3(f32::NAN, 0.0, 0.0),
+        vec3(0.0, f32::INFINITY, 0.0),
+        vec3(1e30, 1e30, 0.0),
+    ]);
+    let ind = vec![
+        vec3(3.0, 1.0, 2.0),
+        vec3(0.0, 1.0, 2.0),
+        vec3(0.0, 4.0, 2.0),
+        vec3(0.0, 0.0, 1.0),
+        vec3(0.0, 1.0, 5.0),
+        vec3(0.0, 1.0, 9.0),
+    ];
+
+    for threads in [1, 4] {
+        let expected = draw(
+            RasterMode::EdgeFunction,
+            threads,
+            valid.clone(),
+            vec![vec3(0.0, 1.0, 2.0)],
+        );
+        let r = draw(RasterMode::EdgeFunction, threads, pos.clone(), ind.clone());
+        assert_eq!(r.framebuffer(), expected.framebuffer());
+        assert!(r.framebuffer().iter().any(|&c| c != Color::BLACK));
+        assert_eq!(
+            r.stats(),
+            DrawStats {
+                submitted: 6,
+                rasterized: 1,
+                invalid_indices: 1,
+                non_finite: 2,
+                degenerate: 1,
+                out_of_range: 1,
+                vertices: 6,
+            }
+        );
+    }
+}
+
+#[test]
+fn edge_triangle_rejects_unrepresentable_input() {
+    let ok = vec4(1.0, 1.0, 0.0, 1.0);
+    let b = vec4(9.0, 1.0, 0.0, 1.0);
+    assert!(EdgeTriangle::new(&[ok, b, vec4(1.0, 9.0, 0.0, 1.0)]).is_some());
+    assert!(EdgeTriangle::new(&[ok, b, vec4(f32::NAN, 9.0, 0.0, 1.0)]).is_none());
+    assert!(EdgeTriangle::new(&[ok, b, vec4(1.0, f32::INFINITY, 0.0, 1.0)]).is_none());
+    assert!(EdgeTriangle::new(&[ok, b, vec4(1.0, 1e30, 0.0, 1.0)]).is_none());
+    // 面积小于一个定点网格单位，吸附后退化
+    assert!(
+        EdgeTriangle::new(&[ok, vec4(1.001, 1.0, 0.0, 1.0), vec4(1.0, 1.001, 0.0, 1.0)]).is_none()
+    );
+}
+
+#[test]
+fn tiny_triangles_produce_no_stray_pixels() {
+    // 面积远小于一个像素且不覆盖任何像素中心
+    let pos = vec![
+        vec3(0.01, 0.01, 0.0),
+        vec3(0.011, 0.01, 0.0),
+        vec3(0.01, 0.0101, 0.0),
+    ];
+    let r = draw(RasterMode::EdgeFunction, 1, pos, vec![vec3(0.0, 1.0, 2.0)]);
+    assert_blank(&r);
+}
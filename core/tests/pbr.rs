@@ -0,0 +1,167 @@
+use games101_core::color::{linear_to_srgb, srgb_to_linear, Color};
+use games101_core::lighting::{Light, Lighting, PbrMaterial};
+use games101_core::rasterizer::{Buffers, Primitive, RasterMode, Rasterizer};
+use nalgebra_glm::{vec3, Vec3};
+use std::f32::consts::PI;
+
+fn pbr(material: PbrMaterial, lights: Vec<Light>) -> Lighting {
+    Lighting {
+        lights,
+        pbr: Some(PbrMaterial {
+            ambient: Vec3::zeros(),
+            ..material
+        }),
+        ..Lighting::default()
+    }
+}
+
+const UP: Vec3 = Vec3::new(0.0, 0.0, 1.0);
+
+/// 视线和光线与法线的夹角都是 `angle`（度），分别在法线两侧，正好是镜面反射方向
+fn mirror(lighting: &Lighting, angle: f32) -> Color {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let mut lighting = lighting.clone();
+    for light in &mut lighting.lights {
+        if let Light::Directional { direction, .. } = light {
+            *direction = vec3(sin, 0.0, -cos);
+        }
+    }
+    lighting.shade(vec3(-sin, 0.0, -cos), UP, Color::WHITE)
+}
+
+#[test]
+fn srgb_round_trip() {
+    for i in 0..=255u8 {
+        let c = Vec3::repeat(i as f32 / 255.0);
+        let back = linear_to_srgb(srgb_to_linear(c));
+        assert!((back - c).norm() < 1e-5, "{}", i);
+    }
+    // sRGB 的中灰在线性空间中约为 0.214
+    assert!((srgb_to_linear(Vec3::repeat(0.5)).x - 0.214).abs() < 1e-3);
+}
+
+#[test]
+fn diffuse_conserves_energy() {
+    // 粗糙电介质：光强为 π 时白色表面的漫反射接近 1，扣除了被镜面反射的部分
+    let rough = PbrMaterial {
+        roughness: 1.0,
+        ..PbrMaterial::default()
+    };
+    let l = pbr(rough, vec![Light::directional(-UP, Vec3::repeat(PI))]);
+    let c = l.shade(vec3(0.3, 0.0, -1.0), UP, Color::WHITE);
+    assert!((245..=255).contains(&c.r), "{:?}", c);
+    // 光照在线性空间中累加，一半的光强不是一半的 sRGB 值
+    let half = pbr(rough, vec![Light::directional(-UP, Vec3::repeat(PI / 2.0))]);
+    let c = half.shade(vec3(0.3, 0.0, -1.0), UP, Color::WHITE);
+    assert!((180..=190).contains(&c.r), "{:?}", c);
+    // 顶点颜色是 sRGB 的，与基础颜色相乘，电介质的镜面反射不带颜色
+    let c = l.shade(vec3(0.3, 0.0, -1.0), UP, Color::new_rgb(128, 0, 0));
+    assert!(
+        (c.r as i32 - 128).abs() <= 4 && c.g == c.b && c.g < 30,
+        "{:?}",
+        c
+    );
+}
+
+#[test]
+fn metals_have_no_diffuse() {
+    let gold = PbrMaterial {
+        base_color: vec3(1.0, 0.77, 0.34),
+        metallic: 1.0,
+        roughness: 0.2,
+        ..PbrMaterial::default()
+    };
+    let l = pbr(gold, vec![Light::directional(-UP, Vec3::repeat(0.015))]);
+    // 偏离镜面反射方向时几乎没有反射
+    assert!(l.shade(vec3(1.0, 0.0, -0.5), UP, Color::WHITE).r < 5);
+    // 镜面反射带有金属本身的颜色
+    let c = mirror(&l, 0.0);
+    assert!(c.r > 200 && c.r > c.g && c.g > c.b, "{:?}", c);
+}
+
+#[test]
+fn roughness_spreads_highlight() {
+    let light = vec![Light::directional(-UP, Vec3::repeat(1.0))];
+    let smooth = pbr(
+        PbrMaterial {
+            roughness: 0.1,
+            ..PbrMaterial::default()
+        },
+        light.clone(),
+    );
+    let rough = pbr(
+        PbrMaterial {
+            roughness: 0.6,
+            ..PbrMaterial::default()
+        },
+        light,
+    );
+    let black = |l: &Lighting, p: Vec3| l.shade(p, UP, Color::BLACK).r;
+    // 黑色电介质只有镜面反射
+    let (peak, off) = (vec3(0.0, 0.0, -1.0), vec3(0.5, 0.0, -1.0));
+    assert!(black(&smooth, peak) > black(&rough, peak));
+    assert!(black(&smooth, off) < black(&rough, off));
+    assert!(black(&smooth, off) < 5);
+}
+
+#[test]
+fn fresnel_and_rasterizer_vertex_colors() {
+    // 掠射角下电介质的反射变强
+    let glossy = pbr(
+        PbrMaterial {
+            base_color: Vec3::zeros(),
+            roughness: 0.3,
+            ..PbrMaterial::default()
+        },
+        vec![Light::directional(-UP, Vec3::repeat(1.0))],
+    );
+    assert!(mirror(&glossy, 80.0).r > mirror(&glossy, 0.0).r + 20);
+
+    // 光栅化时使用顶点颜色，两个光源的结果在浮点数中累加
+    let mut r = Rasterizer::new(16, 16);
+    r.set_raster_mode(RasterMode::EdgeFunction);
+    r.set_pbr_material(Some(PbrMaterial {
+        roughness: 1.0,
+        ambient: Vec3::zeros(),
+        ..PbrMaterial::default()
+    }));
+    let pos = r.load_positions(vec![
+        vec3(-1.0, -1.0, 0.0),
+        vec3(1.0, -1.0, 0.0),
+        vec3(1.0, 1.0, 0.0),
+        vec3(-1.0, 1.0, 0.0),
+    ]);
+    let ind = r.load_indices(vec![vec3(0.0, 1.0, 2.0), vec3(0.0, 2.0, 3.0)]);
+    let col = r.load_colors(vec![Color::new_rgb(0, 200, 0); 4]);
+    let norm = r.load_normals(vec![UP; 4]);
+    r.bind_normals(Some(norm));
+    r.set_view(nalgebra_glm::translation(&vec3(0.0, 0.0, -3.0)));
+    r.set_projection(nalgebra_glm::perspective_rh_no(
+        1.0,
+        90f32.to_radians(),
+        0.1,
+        50.0,
+    ));
+    let draw = |r: &mut Rasterizer, lights: Vec<Light>| {
+        r.set_lights(lights);
+        r.clear(Buffers::all());
+        r.draw(pos, ind, col, Primitive::Triangle);
+        r.framebuffer()[8 * 16 + 8]
+    };
+    let one = draw(&mut r, vec![Light::directional(-UP, Vec3::repeat(2.0))]);
+    let two = draw(
+        &mut r,
+        vec![
+            Light::directional(-UP, Vec3::repeat(1.0)),
+            Light::directional(-UP, Vec3::repeat(1.0)),
+        ],
+    );
+    // 镜面反射不带颜色
+    assert!(one.r == one.b && one.r < 30 && one.g > 150, "{:?}", one);
+    assert!(
+        (one.g as i32 - two.g as i32).abs() <= 1,
+        "{:?} {:?}",
+        one,
+        two
+    );
+}
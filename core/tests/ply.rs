@@ -0,0 +1,181 @@
+use games101_core::color::Color;
+use games101_core::mesh::{Mesh, MeshError};
+use games101_core::ply::{self, PlyFormat};
+use games101_core::rasterizer::{Buffers, RasterMode, Rasterizer};
+use nalgebra_glm::{vec2, vec3};
+
+const FORMATS: [PlyFormat; 3] = [
+    PlyFormat::Ascii,
+    PlyFormat::BinaryLittleEndian,
+    PlyFormat::BinaryBigEndian,
+];
+
+/// 覆盖整个 NDC 的两个三角形，带全部顶点属性
+fn quad() -> Mesh {
+    Mesh {
+        positions: vec![
+            vec3(-1.0, -1.0, -0.5),
+            vec3(1.0, -1.0, -0.5),
+            vec3(1.0, 1.0, -0.5),
+            vec3(-1.0, 1.0, -0.5),
+        ],
+        normals: vec![vec3(0.0, 0.0, 1.0); 4],
+        tex_coords: vec![
+            vec2(0.0, 0.0),
+            vec2(1.0, 0.0),
+            vec2(1.0, 1.0),
+            vec2(0.0, 1.0),
+        ],
+        colors: vec![Color::RED, Color::GREEN, Color::BLUE, Color::WHITE],
+        indices: vec![[0, 1, 2], [0, 2, 3]],
+        ..Default::default()
+    }
+}
+
+fn to_bytes(mesh: &Mesh, format: PlyFormat) -> Vec<u8> {
+    let mut bytes = vec![];
+    ply::write(mesh, &mut bytes, format).unwrap();
+    bytes
+}
+
+#[test]
+fn round_trips_all_formats() {
+    let mesh = quad();
+    for format in FORMATS {
+        let bytes = to_bytes(&mesh, format);
+        assert_eq!(ply::read(&bytes).unwrap(), mesh, "{:?}", format);
+    }
+
+    // 没有法线、颜色和纹理坐标时也不会写出对应属性
+    let bare = Mesh {
+        normals: vec![],
+        tex_coords: vec![],
+        colors: vec![],
+        ..quad()
+    };
+    let bytes = to_bytes(&bare, PlyFormat::Ascii);
+    assert!(!String::from_utf8_lossy(&bytes).contains("red"));
+    assert_eq!(ply::read(&bytes).unwrap(), bare);
+}
+
+#[test]
+fn saves_and_loads_files() {
+    let path = std::env::temp_dir().join(format!("games101-ply-{}.ply", std::process::id()));
+    let mesh = quad();
+    ply::save(&mesh, &path, PlyFormat::BinaryLittleEndian).unwrap();
+    assert_eq!(ply::load(&path).unwrap(), mesh);
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(ply::load(&path), Err(MeshError::Io(_))));
+}
+
+#[test]
+fn reads_other_layouts() {
+    // 浮点颜色、double 坐标、额外的元素和属性、四边形面、\r\n 换行
+    let text = "ply\r\n\
+        format ascii 1.0\r\n\
+        comment made by hand\r\n\
+        element vertex 4\r\n\
+        property double x\r\n\
+        property double y\r\n\
+        property double z\r\n\
+        property float confidence\r\n\
+        property float red\r\n\
+        property float green\r\n\
+        property float blue\r\n\
+        element face 1\r\n\
+        property uchar flags\r\n\
+        property list uchar int vertex_index\r\n\
+        element edge 1\r\n\
+        property int vertex1\r\n\
+        property int vertex2\r\n\
+        end_header\r\n\
+        0 0 0 0.5 1 0 0\r\n\
+        1 0 0 0.5 0 1 0\r\n\
+        1 1 0 0.5 0 0 1\r\n\
+        0 1 0 0.5 0.5 0.5 0.5\r\n\
+        7 4 0 1 2 3\r\n\
+        0 1\r\n";
+    let mesh = ply::read(text.as_bytes()).unwrap();
+    assert_eq!(mesh.positions.len(), 4);
+    assert_eq!(mesh.indices, [[0, 1, 2], [0, 2, 3]]);
+    assert!(mesh.normals.is_empty() && mesh.tex_coords.is_empty());
+    assert_eq!(mesh.colors[0], Color::RED);
+    assert_eq!(mesh.colors[3], Color::new_rgb(128, 128, 128));
+}
+
+#[test]
+fn rejects_invalid_files() {
+    let header = "ply\nformat ascii 1.0\nelement vertex 1\nproperty float x\nproperty float y\nproperty float z\nelement face 1\nproperty list uchar int vertex_indices\nend_header\n";
+    let cases = [
+        "solid mesh\n".to_string(),
+        "ply\nformat ascii 1.0\nelement vertex 1\n".to_string(),
+        "ply\nformat ascii 1.0\nelement vertex 1\nproperty half x\nend_header\n".to_string(),
+        // 数据不足
+        format!("{}0 0\n", header),
+        // 索引越界
+        format!("{}0 0 0\n3 0 1 2\n", header),
+    ];
+    for case in cases {
+        assert!(
+            matches!(ply::read(case.as_bytes()), Err(MeshError::Format(_))),
+            "{:?}",
+            case
+        );
+    }
+
+    let bytes = to_bytes(&quad(), PlyFormat::BinaryBigEndian);
+    assert!(ply::read(&bytes[..bytes.len() - 1]).is_err());
+}
+
+#[test]
+fn header_ends_at_end_header_line() {
+    let header = "ply\nformat ascii 1.0\nelement vertex 3\nproperty float x\nproperty float y\nproperty float z\nelement face 1\nproperty list uchar int vertex_indices\n";
+    let body = "0 0 0\n1 0 0\n0 1 0\n3 0 1 2\n";
+    let unterminated = format!("{}{}", header, body);
+    match ply::read(unterminated.as_bytes()) {
+        Err(MeshError::Format(message)) => assert_eq!(message, "missing end_header"),
+        other => panic!("{:?}", other.map(|m| m.positions)),
+    }
+
+    // 注释里的 end_header 不是头部的结尾
+    let commented = format!(
+        "ply\ncomment no end_header here\n{}end_header\n{}",
+        &header[4..],
+        body
+    );
+    assert_eq!(
+        ply::read(commented.as_bytes()).unwrap().indices,
+        [[0, 1, 2]]
+    );
+
+    // 二进制数据中碰巧出现 end_header 的字节
+    let mut mesh = quad();
+    mesh.positions[1].x = f32::from_le_bytes(*b"end_");
+    mesh.positions[1].y = f32::from_le_bytes(*b"head");
+    mesh.positions[1].z = f32::from_le_bytes(*b"er\n\n");
+    let bytes = to_bytes(&mesh, PlyFormat::BinaryLittleEndian);
+    assert!(bytes.windows(11).any(|w| w == b"end_header\n"));
+    assert_eq!(ply::read(&bytes).unwrap().positions, mesh.positions);
+    // 去掉真正的 end_header 之后，数据中的字节不能充当头部的结尾
+    let end = bytes
+        .windows(11)
+        .position(|w| w == b"end_header\n")
+        .unwrap();
+    let headerless = [&bytes[..end], &bytes[end + 11..]].concat();
+    assert!(matches!(ply::read(&headerless), Err(MeshError::Format(_))));
+}
+
+#[test]
+fn vertex_colors_are_drawn() {
+    let mesh = ply::read(&to_bytes(&quad(), PlyFormat::BinaryLittleEndian)).unwrap();
+    let mut r = Rasterizer::new(32, 32);
+    r.set_raster_mode(RasterMode::EdgeFunction);
+    let buffers = r.load_mesh(&mesh);
+    r.clear(Buffers::all());
+    r.draw_mesh(&buffers);
+    let fb = r.framebuffer();
+    // 左下角接近红色，右上角接近蓝色，顶点颜色插值有舍入误差
+    let (bottom_left, top_right) = (fb[31 * 32], fb[31]);
+    assert!(bottom_left.r > 230 && bottom_left.g < 20 && bottom_left.b < 20);
+    assert!(top_right.b > 230 && top_right.r < 20 && top_right.g < 20);
+}
@@ -0,0 +1,178 @@
+use games101_core::color::Color;
+use games101_core::rasterizer::{Buffers, Primitive, RasterMode, Rasterizer};
+use nalgebra_glm::{vec3, Vec3};
+
+const WIDTH: u32 = 97;
+const HEIGHT: u32 = 83;
+
+/// 一个旋转过的网格，相邻三角形共享边，顶点颜色各不相同
+fn render_grid(mode: RasterMode, threads: usize) -> Rasterizer {
+    let n = 9;
+    let mut pos = vec![];
+    let mut cols = vec![];
+    for j in 0..=n {
+        for i in 0..=n {
+            let z = -3.0 - ((i * 7 + j * 3) % 4) as f32 * 0.2;
+            pos.push(vec3(i as f32 * 0.5 - 2.2, j as f32 * 0.5 - 2.3, z));
+            cols.push(Color::new_rgb((i * 25) as u8, (j * 25) as u8, 200));
+        }
+    }
+    let mut ind = vec![];
+    for j in 0..n {
+        for i in 0..n {
+            let a = (j * (n + 1) + i) as f32;
+            let c = a + (n + 1) as f32;
+            ind.push(vec3(a, a + 1.0, c + 1.0));
+            ind.push(vec3(a, c + 1.0, c));
+        }
+    }
+
+    let mut r = Rasterizer::new(WIDTH, HEIGHT);
+    r.set_raster_mode(mode);
+    r.set_threads(threads);
+    r.set_model(nalgebra_glm::rotation(0.4, &vec3(0.3, 0.2, 1.0)));
+    r.set_projection(nalgebra_glm::perspective_rh_no(
+        WIDTH as f32 / HEIGHT as f32,
+        45f32.to_radians(),
+        0.1,
+        50.0,
+    ));
+    let pos_id = r.load_positions(pos);
+    let ind_id = r.load_indices(ind);
+    let col_id = r.load_colors(cols);
+    r.clear(Buffers::all());
+    r.draw(pos_id, ind_id, col_id, Primitive::Triangle);
+    r
+}
+
+#[test]
+fn triangles_match_edge_function() {
+    for threads in [1, 3] {
+        let edge = render_grid(RasterMode::EdgeFunction, threads);
+        let scan = render_grid(RasterMode::Scanline, threads);
+        let mut covered = 0;
+        for (a, b) in edge.framebuffer().iter().zip(scan.framebuffer()) {
+            // 覆盖的像素完全一致，颜色只允许插值舍入带来的误差
+            assert_eq!(*a == Color::BLACK, *b == Color::BLACK);
+            for (x, y) in [(a.r, b.r), (a.g, b.g), (a.b, b.b)] {
+                assert!(x.abs_diff(y) <= 1, "{:?} != {:?}", a, b);
+            }
+            covered += (*a != Color::BLACK) as usize;
+        }
+        assert!(covered > 1000);
+    }
+}
+
+#[test]
+fn tiled_scanline_is_identical_to_serial() {
+    let serial = render_grid(RasterMode::Scanline, 1);
+    let tiled = render_grid(RasterMode::Scanline, 4);
+    assert_eq!(serial.framebuffer(), tiled.framebuffer());
+}
+
+/// 用奇偶规则判断像素中心是否在多边形内
+fn inside_even_odd(polygon: &[Vec3], x: f32, y: f32) -> bool {
+    let mut inside = false;
+    for i in 0..polygon.len() {
+        let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+        if (a.y > y) != (b.y > y) {
+            let t = (y - a.y) / (b.y - a.y);
+            if x < a.x + t * (b.x - a.x) {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+fn check_polygon(polygon: &[Vec3]) {
+    // 光栅化时顶点会吸附到 1/256 像素的网格上，参考实现也使用吸附后的坐标
+    let polygon: Vec<Vec3> = polygon
+        .iter()
+        .map(|v| {
+            vec3(
+                (v.x * 256.0).round() / 256.0,
+                (v.y * 256.0).round() / 256.0,
+                v.z,
+            )
+        })
+        .collect();
+    let polygon = &polygon[..];
+    let mut r = Rasterizer::new(WIDTH, HEIGHT);
+    r.clear(Buffers::all());
+    r.fill_polygon(polygon, &vec![Color::WHITE; polygon.len()]);
+
+    // 帧缓冲的第一行是屏幕最上方，即 y = HEIGHT - 1
+    let mut covered = 0;
+    for (index, c) in r.framebuffer().iter().enumerate() {
+        let x = (index as u32 % WIDTH) as f32 + 0.5;
+        let y = (HEIGHT - 1 - index as u32 / WIDTH) as f32 + 0.5;
+        assert_eq!(
+            *c == Color::WHITE,
+            inside_even_odd(polygon, x, y),
+            "pixel center ({}, {})",
+            x,
+            y
+        );
+        covered += (*c == Color::WHITE) as usize;
+    }
+    assert!(covered > 0);
+}
+
+#[test]
+fn fills_convex_polygon() {
+    let hexagon: Vec<Vec3> = (0..6)
+        .map(|i| {
+            let a = i as f32 * std::f32::consts::FRAC_PI_3 + 0.1;
+            vec3(48.3 + 30.0 * a.cos(), 41.7 + 30.0 * a.sin(), 0.5)
+        })
+        .collect();
+    check_polygon(&hexagon);
+}
+
+#[test]
+fn fills_concave_polygon() {
+    // U 形
+    let u_shape = [
+        vec3(10.2, 10.3, 0.5),
+        vec3(80.7, 10.3, 0.5),
+        vec3(80.7, 70.1, 0.5),
+        vec3(60.4, 70.1, 0.5),
+        vec3(60.4, 30.6, 0.5),
+        vec3(30.9, 30.6, 0.5),
+        vec3(30.9, 70.1, 0.5),
+        vec3(10.2, 70.1, 0.5),
+    ];
+    check_polygon(&u_shape);
+
+    // 自相交的五角星，奇偶规则下中心的五边形是空的
+    let star: Vec<Vec3> = (0..5)
+        .map(|i| {
+            let a = (i * 2) as f32 * std::f32::consts::TAU / 5.0 + 0.3;
+            vec3(48.1 + 35.0 * a.cos(), 41.9 + 35.0 * a.sin(), 0.5)
+        })
+        .collect();
+    check_polygon(&star);
+}
+
+#[test]
+fn polygon_colors_and_depth_are_interpolated() {
+    let mut r = Rasterizer::new(WIDTH, HEIGHT);
+    r.clear(Buffers::all());
+    let quad = [
+        vec3(0.0, 0.0, 1.0),
+        vec3(WIDTH as f32, 0.0, 1.0),
+        vec3(WIDTH as f32, HEIGHT as f32, 1.0),
+        vec3(0.0, HEIGHT as f32, 1.0),
+    ];
+    let colors = [Color::BLACK, Color::RED, Color::RED, Color::BLACK];
+    r.fill_polygon(&quad, &colors);
+    let row = &r.framebuffer()[..WIDTH as usize];
+    assert!(row.windows(2).all(|p| p[0].r <= p[1].r));
+    assert!(row[0].r < 5 && row[WIDTH as usize - 1].r > 250);
+
+    // 更远的多边形不会覆盖已有像素
+    let far = quad.map(|v| vec3(v.x, v.y, 2.0));
+    r.fill_polygon(&far, &[Color::GREEN; 4]);
+    assert!(r.framebuffer().iter().all(|c| c.g == 0));
+}
@@ -0,0 +1,277 @@
+use games101_core::camera::Projection;
+use games101_core::mesh::MeshError;
+use games101_core::rasterizer::{Buffers, RasterMode, Rasterizer};
+use games101_core::scene::{self, Camera};
+use games101_core::texture::{Filter, WrapMode};
+use nalgebra_glm::{vec2, vec3, Mat4};
+use std::path::PathBuf;
+
+/// 测试用的临时目录，每个测试使用不同的名字
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("games101-scene-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn f32_bytes(values: &[f32]) -> Vec<u8> {
+    values.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+/// 把 JSON 和二进制缓冲区打包为 GLB
+fn glb(json: &str, bin: &[u8]) -> Vec<u8> {
+    let mut json = json.as_bytes().to_vec();
+    json.resize(json.len().next_multiple_of(4), b' ');
+    let mut bin = bin.to_vec();
+    bin.resize(bin.len().next_multiple_of(4), 0);
+
+    let mut data = vec![];
+    data.extend(b"glTF");
+    data.extend(2u32.to_le_bytes());
+    data.extend((12 + 8 + json.len() as u32 + 8 + bin.len() as u32).to_le_bytes());
+    data.extend((json.len() as u32).to_le_bytes());
+    data.extend(b"JSON");
+    data.extend(json);
+    data.extend((bin.len() as u32).to_le_bytes());
+    data.extend(b"BIN\0");
+    data.extend(bin);
+    data
+}
+
+/// 一个在父节点下平移到右上方的红色正方形、一个三角形带和一个正交相机
+fn hierarchy() -> Vec<u8> {
+    let mut bin = f32_bytes(&[
+        -0.5, -0.5, 0.0, 0.5, -0.5, 0.0, 0.5, 0.5, 0.0, -0.5, 0.5, 0.0,
+    ]);
+    bin.extend([0u16, 1, 2, 0, 2, 3].iter().flat_map(|i| i.to_le_bytes()));
+    bin.extend(f32_bytes(&[
+        -10.0, 0.0, 0.0, -10.0, 1.0, 0.0, -9.0, 0.0, 0.0, -9.0, 1.0, 0.0,
+    ]));
+    let json = r#"{
+        "asset": {"version": "2.0"},
+        "scene": 0,
+        "scenes": [{"nodes": [0, 2]}],
+        "nodes": [
+            {"name": "parent", "translation": [0.5, 0.5, 0], "children": [1]},
+            {"name": "quad", "mesh": 0},
+            {"name": "camera", "camera": 0, "translation": [0, 0, 1]}
+        ],
+        "cameras": [{"type": "orthographic", "orthographic": {"xmag": 1, "ymag": 1, "znear": 0.1, "zfar": 10}}],
+        "materials": [{"name": "red", "pbrMetallicRoughness": {"baseColorFactor": [1, 0, 0, 1]}}],
+        "meshes": [{"name": "quad", "primitives": [
+            {"attributes": {"POSITION": 0}, "indices": 1, "material": 0},
+            {"attributes": {"POSITION": 2}, "mode": 5}
+        ]}],
+        "buffers": [{"byteLength": 108}],
+        "bufferViews": [
+            {"buffer": 0, "byteOffset": 0, "byteLength": 48},
+            {"buffer": 0, "byteOffset": 48, "byteLength": 12},
+            {"buffer": 0, "byteOffset": 60, "byteLength": 48}
+        ],
+        "accessors": [
+            {"bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3", "min": [-0.5, -0.5, 0], "max": [0.5, 0.5, 0]},
+            {"bufferView": 1, "componentType": 5123, "count": 6, "type": "SCALAR"},
+            {"bufferView": 2, "componentType": 5126, "count": 4, "type": "VEC3", "min": [-10, 0, 0], "max": [-9, 1, 0]}
+        ]
+    }"#;
+    glb(json, &bin)
+}
+
+#[test]
+fn loads_nodes_meshes_and_cameras() {
+    let scene = scene::read(&hierarchy()).unwrap();
+    assert_eq!(scene.roots, [0, 2]);
+    assert_eq!(scene.nodes[0].children, [1]);
+    assert_eq!(scene.nodes[1].mesh, Some(0));
+
+    // 子节点的世界变换包含父节点的平移
+    let transforms = scene.world_transforms();
+    assert_eq!(
+        transforms.iter().map(|&(i, _)| i).collect::<Vec<_>>(),
+        [0, 1, 2]
+    );
+    assert_eq!(
+        transforms[1].1,
+        nalgebra_glm::translation(&vec3(0.5, 0.5, 0.0))
+    );
+    let (view, _) = scene.camera(1.0).unwrap();
+    assert_eq!(view, nalgebra_glm::translation(&vec3(0.0, 0.0, -1.0)));
+    // 正交相机转换为相机模块中的投影，宽高比由 xmag / ymag 决定
+    let camera = &scene.cameras[0];
+    assert_eq!(
+        camera.projection,
+        Projection::Orthographic {
+            height: 2.0,
+            aspect: 1.0,
+            near: 0.1,
+            far: 10.0,
+        }
+    );
+    assert!(!camera.viewport_aspect);
+    assert_eq!(camera.projection_matrix(2.0), camera.projection_matrix(1.0));
+
+    // 每个图元是一个分组，三角形带被展开为三角形
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.positions.len(), 8);
+    assert_eq!(mesh.indices, [[0, 1, 2], [0, 2, 3], [4, 5, 6], [6, 5, 7]]);
+    assert_eq!(mesh.groups.len(), 2);
+    assert_eq!(mesh.groups[0].material, Some(0));
+    assert_eq!(mesh.groups[1].material, None);
+    assert!(mesh.normals.is_empty() && mesh.tex_coords.is_empty() && mesh.colors.is_empty());
+    // 基础颜色是线性空间的，转换到 sRGB
+    assert!((scene.materials[0].diffuse - vec3(1.0, 0.0, 0.0)).norm() < 1e-5);
+}
+
+#[test]
+fn draws_with_scene_camera() {
+    let scene = scene::read(&hierarchy()).unwrap();
+    let mut r = Rasterizer::new(32, 32);
+    r.set_raster_mode(RasterMode::EdgeFunction);
+    let buffers = r.load_scene(&scene);
+    let (view, projection) = scene.camera(1.0).unwrap();
+    r.set_view(view);
+    r.set_projection(projection);
+    // 绘制之后恢复原来的模型矩阵和绑定
+    let model = nalgebra_glm::translation(&vec3(1.0, 2.0, 3.0));
+    r.set_model(model);
+    let normals = r.load_normals(vec![vec3(0.0, 0.0, 1.0)]);
+    let tex_coords = r.load_tex_coords(vec![vec2(0.0, 0.0)]);
+    r.bind_normals(Some(normals));
+    r.bind_tex_coords(Some(tex_coords));
+    r.clear(Buffers::all());
+    r.draw_scene(&scene, &buffers);
+    assert_eq!(r.model(), model);
+    assert_eq!(r.bound_normals(), Some(normals));
+    assert_eq!(r.bound_tex_coords(), Some(tex_coords));
+    assert_eq!(r.bound_texture(), None);
+    assert!(scene.warnings.is_empty());
+
+    // 正方形在右上角的四分之一
+    let fb = r.framebuffer();
+    let (top_right, bottom_left) = (fb[5 * 32 + 27], fb[27 * 32 + 5]);
+    assert!(top_right.r > 250 && top_right.g == 0 && top_right.b == 0);
+    assert_eq!((bottom_left.r, bottom_left.g, bottom_left.b), (0, 0, 0));
+}
+
+#[test]
+fn loads_external_buffers_and_textures() {
+    let dir = temp_dir("texture");
+    // 1×2 的图片，上红下蓝
+    image::save_buffer(
+        dir.join("stripes.png"),
+        &[255, 0, 0, 0, 0, 255],
+        1,
+        2,
+        image::ExtendedColorType::Rgb8,
+    )
+    .unwrap();
+    let mut bin = f32_bytes(&[
+        -1.0, -1.0, 0.0, 1.0, -1.0, 0.0, 1.0, 1.0, 0.0, -1.0, 1.0, 0.0,
+    ]);
+    bin.extend(f32_bytes(&[0.0, 1.0, 1.0, 1.0, 1.0, 0.0, 0.0, 0.0]));
+    std::fs::write(dir.join("quad.bin"), bin).unwrap();
+    std::fs::write(
+        dir.join("quad.gltf"),
+        r#"{
+            "asset": {"version": "2.0"},
+            "scenes": [{"nodes": [0]}],
+            "nodes": [{"mesh": 0}],
+            "images": [{"uri": "stripes.png"}],
+            "samplers": [{"magFilter": 9728, "minFilter": 9728, "wrapS": 33071, "wrapT": 33071}],
+            "textures": [{"source": 0, "sampler": 0}],
+            "materials": [{"pbrMetallicRoughness": {"baseColorTexture": {"index": 0}}}],
+            "meshes": [{"primitives": [{"attributes": {"POSITION": 0, "TEXCOORD_0": 1}, "material": 0, "mode": 6}]}],
+            "buffers": [{"uri": "quad.bin", "byteLength": 80}],
+            "bufferViews": [
+                {"buffer": 0, "byteOffset": 0, "byteLength": 48},
+                {"buffer": 0, "byteOffset": 48, "byteLength": 32}
+            ],
+            "accessors": [
+                {"bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3", "min": [-1, -1, 0], "max": [1, 1, 0]},
+                {"bufferView": 1, "componentType": 5126, "count": 4, "type": "VEC2"}
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let scene = scene::load(dir.join("quad.gltf")).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(scene.material_textures, [Some(0)]);
+    let texture = &scene.textures[0];
+    assert_eq!((texture.width(), texture.height()), (1, 2));
+    assert_eq!(texture.filter(), Filter::Nearest);
+    assert_eq!(texture.wrap_mode(), WrapMode::ClampToEdge);
+    // glTF 的 v 轴向下，读取后翻转
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.tex_coords[0], vec2(0.0, 0.0));
+    assert_eq!(mesh.indices, [[0, 1, 2], [0, 2, 3]]);
+
+    let mut r = Rasterizer::new(16, 16);
+    r.set_raster_mode(RasterMode::EdgeFunction);
+    let buffers = r.load_scene(&scene);
+    r.clear(Buffers::all());
+    r.draw_scene(&scene, &buffers);
+    let fb = r.framebuffer();
+    assert_eq!((fb[8].r, fb[8].b), (255, 0));
+    assert_eq!((fb[15 * 16 + 8].r, fb[15 * 16 + 8].b), (0, 255));
+}
+
+#[test]
+fn infinite_perspective_without_zfar() {
+    let camera = Camera {
+        name: String::new(),
+        projection: Projection::Perspective {
+            fov_y: 1.0,
+            aspect: 1.0,
+            near: 0.1,
+            far: None,
+        },
+        viewport_aspect: true,
+    };
+    assert_eq!(
+        camera.projection_matrix(2.0),
+        nalgebra_glm::infinite_perspective_rh_no(2.0, 1.0, 0.1)
+    );
+    assert_ne!(camera.projection_matrix(2.0), Mat4::identity());
+}
+
+#[test]
+fn unsupported_primitives_become_warnings() {
+    let bin = f32_bytes(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+    let json = r#"{
+        "asset": {"version": "2.0"},
+        "scenes": [{"nodes": [0]}],
+        "nodes": [{"mesh": 0}],
+        "meshes": [{"name": "mixed", "primitives": [
+            {"attributes": {"POSITION": 0}, "mode": 0},
+            {"attributes": {"POSITION": 0}, "mode": 1},
+            {"attributes": {"POSITION": 0}}
+        ]}],
+        "buffers": [{"byteLength": 36}],
+        "bufferViews": [{"buffer": 0, "byteOffset": 0, "byteLength": 36}],
+        "accessors": [
+            {"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0, 0, 0], "max": [1, 1, 0]}
+        ]
+    }"#;
+    let scene = scene::read(&glb(json, &bin)).unwrap();
+    // 点和线被跳过，三角形照常读取
+    assert_eq!(scene.meshes[0].indices, [[6, 7, 8]]);
+    assert_eq!(
+        scene.warnings,
+        [
+            "mesh \"mixed\": ignoring Points primitive",
+            "mesh \"mixed\": ignoring Lines primitive"
+        ]
+    );
+}
+
+#[test]
+fn rejects_invalid_files() {
+    assert!(matches!(
+        scene::read(b"not a gltf file"),
+        Err(MeshError::Format(_))
+    ));
+    assert!(matches!(
+        scene::load(temp_dir("missing").join("missing.gltf")),
+        Err(MeshError::Io(_))
+    ));
+}
@@ -0,0 +1,200 @@
+use games101_core::color::Color;
+use games101_core::lighting::{Light, Material};
+use games101_core::rasterizer::{Buffers, Primitive, RasterMode, Rasterizer};
+use games101_core::shadow::ShadowMap;
+use nalgebra_glm::{vec3, Vec3};
+
+/// 地面 y = 0 上方 y = 1 处有一块遮挡板
+fn load_scene(r: &mut Rasterizer) -> impl Fn(&mut Rasterizer) {
+    let positions = vec![
+        vec3(-2.0, 0.0, 2.0),
+        vec3(2.0, 0.0, 2.0),
+        vec3(2.0, 0.0, -2.0),
+        vec3(-2.0, 0.0, -2.0),
+        vec3(-0.5, 1.0, 0.5),
+        vec3(0.5, 1.0, 0.5),
+        vec3(0.5, 1.0, -0.5),
+        vec3(-0.5, 1.0, -0.5),
+    ];
+    let pos = r.load_positions(positions);
+    let ind = r.load_indices(vec![
+        vec3(0.0, 1.0, 2.0),
+        vec3(0.0, 2.0, 3.0),
+        vec3(4.0, 5.0, 6.0),
+        vec3(4.0, 6.0, 7.0),
+    ]);
+    let col = r.load_colors(vec![Color::WHITE; 8]);
+    let norm = r.load_normals(vec![vec3(0.0, 1.0, 0.0); 8]);
+    move |r: &mut Rasterizer| {
+        r.bind_normals(Some(norm));
+        r.draw(pos, ind, col, Primitive::Triangle);
+    }
+}
+
+fn setup(light: Light) -> (Rasterizer, impl Fn(&mut Rasterizer)) {
+    let mut r = Rasterizer::new(32, 32);
+    r.set_raster_mode(RasterMode::EdgeFunction);
+    let draw = load_scene(&mut r);
+    r.set_material(Some(Material {
+        ka: Vec3::zeros(),
+        ks: Vec3::zeros(),
+        ..Material::default()
+    }));
+    r.set_lights(vec![light]);
+    // 从正上方俯视，屏幕 x 为世界 x，屏幕上方为世界 -z
+    r.set_view(nalgebra_glm::look_at_rh(
+        &vec3(0.0, 5.0, 0.0),
+        &Vec3::zeros(),
+        &vec3(0.0, 0.0, -1.0),
+    ));
+    r.set_projection(nalgebra_glm::ortho_rh_no(-2.0, 2.0, -2.0, 2.0, 0.1, 10.0));
+    (r, draw)
+}
+
+fn render(r: &mut Rasterizer, draw: &impl Fn(&mut Rasterizer)) {
+    r.begin_shadow_pass(0);
+    draw(r);
+    r.end_shadow_pass();
+    r.clear(Buffers::all());
+    draw(r);
+}
+
+/// 地面上世界坐标 (x, 0, 0) 处的像素
+fn ground(r: &Rasterizer, x: f32) -> Color {
+    let px = ((x + 2.0) / 4.0 * 32.0) as usize;
+    r.framebuffer()[15 * 32 + px]
+}
+
+#[test]
+fn directional_light_casts_shadow() {
+    // 光线向 +x 方向倾斜 45°，遮挡板的影子落在 x ∈ [0.5, 1.5]
+    let (mut r, draw) = setup(Light::directional(
+        vec3(1.0, -1.0, 0.0),
+        vec3(1.0, 1.0, 1.0),
+    ));
+    render(&mut r, &draw);
+    // 没有阴影贴图时地面都被照亮
+    assert!(ground(&r, 1.0).r > 150);
+    assert!(ground(&r, -1.0).r > 150);
+
+    r.set_shadow_map(0, Some(ShadowMap::new(256, Vec3::zeros(), 3.0)));
+    render(&mut r, &draw);
+    assert_eq!(ground(&r, 1.0).r, 0);
+    assert!(ground(&r, -1.0).r > 150);
+    // 遮挡板本身朝向光源，不在阴影中
+    assert!(ground(&r, 0.0).r > 150);
+
+    r.set_shadow_map(0, None);
+    render(&mut r, &draw);
+    assert!(ground(&r, 1.0).r > 150);
+}
+
+#[test]
+fn depth_bias_prevents_shadow_acne() {
+    let (mut r, draw) = setup(Light::directional(
+        vec3(1.0, -1.0, 0.0),
+        vec3(1.0, 1.0, 1.0),
+    ));
+    r.set_shadow_map(0, Some(ShadowMap::new(256, Vec3::zeros(), 3.0)));
+    render(&mut r, &draw);
+    let map = r.shadow_map(0).unwrap();
+    let cos = std::f32::consts::FRAC_1_SQRT_2;
+    // 影子以外的地面处处可见
+    for i in 0..40 {
+        let x = -1.9 + i as f32 * 0.06;
+        for z in [-1.5, -0.2, 0.7, 1.6] {
+            if (0.3..1.7).contains(&x) && z == -0.2 {
+                continue;
+            }
+            assert_eq!(map.visibility(vec3(x, 0.0, z), cos), 1.0, "{} {}", x, z);
+        }
+    }
+
+    // 偏移随 PCF 半径增大，低分辨率、大半径时也没有自遮挡
+    for radius in [0, 2, 4] {
+        let mut map = ShadowMap::new(32, Vec3::zeros(), 3.0);
+        map.pcf_radius = radius;
+        r.set_shadow_map(0, Some(map));
+        render(&mut r, &draw);
+        let map = r.shadow_map(0).unwrap();
+        for i in 0..40 {
+            let p = vec3(-1.9 + i as f32 * 0.03, 0.0, 1.6);
+            assert_eq!(map.visibility(p, cos), 1.0, "{} {:?}", radius, p);
+        }
+    }
+}
+
+#[test]
+fn pcf_softens_shadow_edges() {
+    let light = Light::directional(vec3(1.0, -1.0, 0.0), vec3(1.0, 1.0, 1.0));
+    let (mut r, draw) = setup(light);
+    let cos = std::f32::consts::FRAC_1_SQRT_2;
+    // 影子的左边缘在 x = 0.5，深度图的纹素宽度约为 0.09
+    let edge: Vec<f32> = (0..9).map(|i| 0.1 + i as f32 * 0.1).collect();
+
+    let mut hard = ShadowMap::new(64, Vec3::zeros(), 3.0);
+    hard.pcf_radius = 0;
+    r.set_shadow_map(0, Some(hard));
+    render(&mut r, &draw);
+    let map = r.shadow_map(0).unwrap();
+    for &x in &edge {
+        let v = map.visibility(vec3(x, 0.0, 0.0), cos);
+        assert!(v == 0.0 || v == 1.0, "{}: {}", x, v);
+    }
+
+    r.set_shadow_map(0, Some(ShadowMap::new(64, Vec3::zeros(), 3.0)));
+    render(&mut r, &draw);
+    let map = r.shadow_map(0).unwrap();
+    let visibility: Vec<f32> = edge
+        .iter()
+        .map(|&x| map.visibility(vec3(x, 0.0, 0.0), cos))
+        .collect();
+    assert_eq!(visibility[0], 1.0);
+    assert_eq!(visibility[8], 0.0);
+    assert!(visibility.iter().any(|&v| v > 0.0 && v < 1.0));
+    // 从亮到暗单调变化
+    assert!(
+        visibility.windows(2).all(|w| w[0] >= w[1]),
+        "{:?}",
+        visibility
+    );
+}
+
+#[test]
+fn spot_light_casts_shadow() {
+    let spot = Light::spot(
+        vec3(-2.0, 3.0, 0.0),
+        vec3(2.0, -3.0, 0.0),
+        vec3(20.0, 20.0, 20.0),
+        50f32.to_radians(),
+        60f32.to_radians(),
+    );
+    let (mut r, draw) = setup(spot);
+    r.set_shadow_map(0, Some(ShadowMap::new(256, Vec3::zeros(), 3.0)));
+    render(&mut r, &draw);
+    // 光源在左上方，影子落在遮挡板右侧 x ∈ [0.5, 1.75] 之间
+    assert_eq!(ground(&r, 1.0).r, 0);
+    assert!(ground(&r, -1.0).r > 100);
+    assert!(ground(&r, 1.9).r > 50);
+}
+
+#[test]
+fn point_lights_have_no_shadow() {
+    let (mut r, draw) = setup(Light::point(vec3(-2.0, 3.0, 0.0), vec3(20.0, 20.0, 20.0)));
+    r.set_shadow_map(0, Some(ShadowMap::new(64, Vec3::zeros(), 3.0)));
+    render(&mut r, &draw);
+    assert!(r
+        .shadow_map(0)
+        .unwrap()
+        .depth()
+        .iter()
+        .all(|&d| d == f32::MAX));
+    assert!(ground(&r, 1.0).r > 50);
+
+    // 阴影贴图渲染期间不修改帧缓冲
+    r.clear(Buffers::all());
+    r.begin_shadow_pass(0);
+    draw(&mut r);
+    r.end_shadow_pass();
+    assert!(r.framebuffer().iter().all(|&c| c == Color::BLACK));
+}
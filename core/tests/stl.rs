@@ -0,0 +1,81 @@
+use games101_core::mesh::{Mesh, MeshError};
+use games101_core::stl::{self, StlFormat};
+use nalgebra_glm::vec3;
+
+/// 单位四面体，顶点按第一次出现的顺序排列，与读取时合并后的顺序相同
+fn tetrahedron() -> Mesh {
+    Mesh {
+        positions: vec![
+            vec3(0.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            vec3(1.0, 0.0, 0.0),
+            vec3(0.0, 0.0, 1.0),
+        ],
+        indices: vec![[0, 1, 2], [0, 2, 3], [0, 3, 1], [2, 1, 3]],
+        ..Default::default()
+    }
+}
+
+fn to_bytes(mesh: &Mesh, format: StlFormat) -> Vec<u8> {
+    let mut bytes = vec![];
+    stl::write(mesh, &mut bytes, format).unwrap();
+    bytes
+}
+
+#[test]
+fn round_trips_both_formats() {
+    let mesh = tetrahedron();
+    for format in [StlFormat::Ascii, StlFormat::Binary] {
+        let bytes = to_bytes(&mesh, format);
+        // 每个三角形单独存储顶点，读取时按位置合并
+        assert_eq!(stl::read(&bytes).unwrap(), mesh, "{:?}", format);
+    }
+    assert_eq!(to_bytes(&mesh, StlFormat::Binary).len(), 84 + 4 * 50);
+}
+
+#[test]
+fn writes_facet_normals() {
+    let text = String::from_utf8(to_bytes(&tetrahedron(), StlFormat::Ascii)).unwrap();
+    assert!(text.starts_with("solid"));
+    assert!(text.contains("facet normal 0 0 -1"));
+
+    let bytes = to_bytes(&tetrahedron(), StlFormat::Binary);
+    let normal: Vec<f32> = bytes[84..96]
+        .chunks(4)
+        .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+        .collect();
+    assert_eq!(normal, [0.0, 0.0, -1.0]);
+}
+
+#[test]
+fn binary_header_may_start_with_solid() {
+    // 一些导出工具写出的二进制文件头也以 "solid" 开头
+    let mut bytes = to_bytes(&tetrahedron(), StlFormat::Binary);
+    bytes[..5].copy_from_slice(b"solid");
+    assert_eq!(stl::read(&bytes).unwrap(), tetrahedron());
+}
+
+#[test]
+fn saves_and_loads_files() {
+    let path = std::env::temp_dir().join(format!("games101-stl-{}.stl", std::process::id()));
+    stl::save(&tetrahedron(), &path, StlFormat::Ascii).unwrap();
+    let mut mesh = stl::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    mesh.compute_normals();
+    assert!(mesh.normals.iter().all(|n| (n.norm() - 1.0).abs() < 1e-5));
+}
+
+#[test]
+fn rejects_invalid_files() {
+    let cases: [&[u8]; 3] = [
+        b"ply\n",
+        b"solid mesh\nfacet normal 0 0 1\nouter loop\nvertex 0 0 0\nvertex 1 0 0\nendloop\nendfacet\n",
+        b"solid mesh\nfacet normal 0 0 1\nouter loop\nvertex 0 0 x\n",
+    ];
+    for case in cases {
+        assert!(matches!(stl::read(case), Err(MeshError::Format(_))));
+    }
+    // 二进制数据被截断
+    let bytes = to_bytes(&tetrahedron(), StlFormat::Binary);
+    assert!(stl::read(&bytes[..bytes.len() - 10]).is_err());
+}
@@ -0,0 +1,178 @@
+use games101_core::color::Color;
+use games101_core::lighting::{Light, Material, ShadingMode};
+use games101_core::rasterizer::{Buffers, Primitive, RasterMode, Rasterizer};
+use games101_core::surface::{self, SurfaceMap};
+use games101_core::texture::{Filter, Texture};
+use nalgebra_glm::{vec2, vec3, Vec3};
+
+const FLAT: Vec3 = Vec3::new(0.0, 0.0, 1.0);
+const FRAME: (Vec3, Vec3) = (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+
+fn solid(color: Color) -> Texture {
+    Texture::new(1, 1, vec![color])
+}
+
+fn assert_close(a: Vec3, b: Vec3) {
+    assert!((a - b).norm() < 1e-2, "{:?} != {:?}", a, b);
+}
+
+#[test]
+fn tangent_frame_from_positions_and_uvs() {
+    let positions = [
+        vec3(0.0, 0.0, 0.0),
+        vec3(2.0, 0.0, 0.0),
+        vec3(0.0, 4.0, 0.0),
+    ];
+    let (t, b) =
+        surface::tangent_frame(positions, [vec2(0.0, 0.0), vec2(1.0, 0.0), vec2(0.0, 1.0)])
+            .unwrap();
+    // 切线是坐标对纹理坐标的偏导数，长度与纹理的拉伸有关
+    assert_close(t, vec3(2.0, 0.0, 0.0));
+    assert_close(b, vec3(0.0, 4.0, 0.0));
+
+    // 纹理坐标旋转 90° 后切线跟着旋转
+    let (t, b) =
+        surface::tangent_frame(positions, [vec2(0.0, 0.0), vec2(0.0, 1.0), vec2(-1.0, 0.0)])
+            .unwrap();
+    assert_close(t, vec3(0.0, -4.0, 0.0));
+    assert_close(b, vec3(2.0, 0.0, 0.0));
+
+    // 纹理坐标退化为一条线时没有切线
+    assert!(
+        surface::tangent_frame(positions, [vec2(0.0, 0.0), vec2(1.0, 1.0), vec2(2.0, 2.0)])
+            .is_none()
+    );
+}
+
+#[test]
+fn normal_map_in_tangent_space() {
+    let p = vec3(1.0, 2.0, 3.0);
+    // (128, 128, 255) 表示不扰动
+    let flat = solid(Color::new_rgb(128, 128, 255));
+    let (q, n) = SurfaceMap::Normal.apply(&flat, vec2(0.5, 0.5), p, FLAT, FRAME);
+    assert_eq!(q, p);
+    assert_close(n.normalize(), FLAT);
+
+    // 指向切线方向，切线不需要与法线正交
+    let tilted = solid(Color::new_rgb(255, 128, 128));
+    let frame = (vec3(1.0, 0.0, 1.0), vec3(0.0, 1.0, 0.0));
+    let (_, n) = SurfaceMap::Normal.apply(&tilted, vec2(0.5, 0.5), p, FLAT, frame);
+    assert_close(n.normalize(), vec3(1.0, 0.0, 0.0));
+
+    // 副切线与 N × T 相反时（纹理镜像）绿色通道沿副切线方向
+    let up = solid(Color::new_rgb(128, 255, 128));
+    let mirrored = (FRAME.0, -FRAME.1);
+    let (_, n) = SurfaceMap::Normal.apply(&up, vec2(0.5, 0.5), p, FLAT, mirrored);
+    assert_close(n.normalize(), vec3(0.0, -1.0, 0.0));
+
+    // 没有切线时不扰动
+    let (_, n) = SurfaceMap::Normal.apply(&tilted, vec2(0.5, 0.5), p, FLAT, (FLAT, FLAT));
+    assert_eq!(n, FLAT);
+}
+
+#[test]
+fn bump_map_follows_height_gradient() {
+    // 左半边高度为 0，右半边为 1
+    let mut step = Texture::new(
+        2,
+        2,
+        vec![Color::BLACK, Color::WHITE, Color::BLACK, Color::WHITE],
+    );
+    step.set_filter(Filter::Nearest);
+    let bump = SurfaceMap::Bump { strength: 1.0 };
+
+    // 在台阶左侧，沿 u 方向高度升高，法线向 -u 倾斜
+    let (_, n) = bump.apply(&step, vec2(0.25, 0.5), Vec3::zeros(), FLAT, FRAME);
+    assert_close(n, vec3(-1.0, 0.0, 1.0));
+    // 沿 v 方向高度不变
+    let (_, n) = bump.apply(&step, vec2(0.75, 0.25), Vec3::zeros(), FLAT * 2.0, FRAME);
+    assert_close(n, vec3(1.0, 0.0, 1.0));
+
+    // 高度处处相同时法线不变
+    let (_, n) = bump.apply(
+        &solid(Color::WHITE),
+        vec2(0.3, 0.3),
+        Vec3::zeros(),
+        FLAT,
+        FRAME,
+    );
+    assert_close(n, FLAT);
+}
+
+#[test]
+fn displacement_moves_shading_point() {
+    let gray = solid(Color::new_rgb(51, 51, 51));
+    let map = SurfaceMap::Displacement {
+        strength: 1.0,
+        depth: 0.5,
+    };
+    let p = vec3(1.0, 1.0, -2.0);
+    let (q, n) = map.apply(&gray, vec2(0.5, 0.5), p, FLAT * 3.0, FRAME);
+    // 沿单位法线移动 depth * h = 0.5 * 0.2
+    assert_close(q, p + vec3(0.0, 0.0, 0.1));
+    assert_close(n, FLAT);
+}
+
+/// 正对相机的正方形，纹理坐标沿 x、y 方向展开
+fn draw_quad(r: &mut Rasterizer) {
+    let pos = r.load_positions(vec![
+        vec3(-1.0, -1.0, 0.0),
+        vec3(1.0, -1.0, 0.0),
+        vec3(1.0, 1.0, 0.0),
+        vec3(-1.0, 1.0, 0.0),
+    ]);
+    let ind = r.load_indices(vec![vec3(0.0, 1.0, 2.0), vec3(0.0, 2.0, 3.0)]);
+    let col = r.load_colors(vec![Color::WHITE; 4]);
+    let norm = r.load_normals(vec![FLAT; 4]);
+    let uv = r.load_tex_coords(vec![
+        vec2(0.0, 0.0),
+        vec2(1.0, 0.0),
+        vec2(1.0, 1.0),
+        vec2(0.0, 1.0),
+    ]);
+    r.bind_normals(Some(norm));
+    r.bind_tex_coords(Some(uv));
+    r.set_view(nalgebra_glm::translation(&vec3(0.0, 0.0, -3.0)));
+    r.set_projection(nalgebra_glm::perspective_rh_no(
+        1.0,
+        90f32.to_radians(),
+        0.1,
+        50.0,
+    ));
+    r.clear(Buffers::all());
+    r.draw(pos, ind, col, Primitive::Triangle);
+}
+
+#[test]
+fn rasterizer_applies_surface_map_per_pixel() {
+    let mut r = Rasterizer::new(32, 32);
+    r.set_raster_mode(RasterMode::EdgeFunction);
+    r.set_material(Some(Material {
+        ka: Vec3::zeros(),
+        ks: Vec3::zeros(),
+        ..Material::default()
+    }));
+    // 光线与正方形平行，没有贴图时照不亮
+    r.set_lights(vec![Light::directional(
+        vec3(-1.0, 0.0, 0.0),
+        vec3(1.0, 1.0, 1.0),
+    )]);
+    draw_quad(&mut r);
+    assert_eq!(r.framebuffer()[16 * 32 + 16].r, 0);
+
+    // 法线贴图把法线转向 +x
+    let map = r.load_texture(solid(Color::new_rgb(255, 128, 128)));
+    r.bind_surface_map(Some((map, SurfaceMap::Normal)));
+    draw_quad(&mut r);
+    assert!(r.framebuffer()[16 * 32 + 16].r > 240);
+
+    // 面法线着色不使用贴图
+    r.set_shading_mode(ShadingMode::Flat);
+    draw_quad(&mut r);
+    assert_eq!(r.framebuffer()[16 * 32 + 16].r, 0);
+
+    r.set_shading_mode(ShadingMode::Phong);
+    r.bind_surface_map(None);
+    draw_quad(&mut r);
+    assert_eq!(r.framebuffer()[16 * 32 + 16].r, 0);
+}
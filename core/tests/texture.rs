@@ -0,0 +1,172 @@
+use games101_core::color::Color;
+use games101_core::rasterizer::{Buffers, Primitive, RasterMode, Rasterizer};
+use games101_core::texture::{Filter, Texture, WrapMode};
+use nalgebra_glm::{vec2, vec3, Vec2, Vec3};
+
+const SIZE: u32 = 64;
+
+/// 2x2 纹理，上面一行红、绿，下面一行蓝、白
+fn quad_texture() -> Texture {
+    Texture::new(
+        2,
+        2,
+        vec![Color::RED, Color::GREEN, Color::BLUE, Color::WHITE],
+    )
+}
+
+#[test]
+fn wrap_modes() {
+    let mut t = Texture::new(3, 1, vec![Color::RED, Color::GREEN, Color::BLUE]);
+    let row = |t: &Texture| (-4..7).map(|x| t.texel(x, 0)).collect::<Vec<_>>();
+    let (r, g, b) = (Color::RED, Color::GREEN, Color::BLUE);
+
+    t.set_wrap_mode(WrapMode::Repeat);
+    assert_eq!(row(&t), [b, r, g, b, r, g, b, r, g, b, r]);
+    t.set_wrap_mode(WrapMode::ClampToEdge);
+    assert_eq!(row(&t), [r, r, r, r, r, g, b, b, b, b, b]);
+    t.set_wrap_mode(WrapMode::MirroredRepeat);
+    assert_eq!(row(&t), [b, b, g, r, r, g, b, b, g, r, r]);
+}
+
+#[test]
+fn nearest_and_bilinear_sampling() {
+    let mut t = quad_texture();
+    t.set_filter(Filter::Nearest);
+    assert_eq!(t.sample(0.25, 0.75), Color::RED);
+    assert_eq!(t.sample(0.75, 0.75), Color::GREEN);
+    assert_eq!(t.sample(0.25, 0.25), Color::BLUE);
+    assert_eq!(t.sample(0.75, 0.25), Color::WHITE);
+    // Repeat 下纹理坐标 1.25 与 0.25 相同
+    assert_eq!(t.sample(1.25, -0.75), Color::BLUE);
+
+    t.set_filter(Filter::Bilinear);
+    // 纹素中心处与最近点采样一致
+    assert_eq!(t.sample(0.25, 0.75), Color::RED);
+    assert_eq!(t.sample(0.75, 0.25), Color::WHITE);
+    // 四个纹素中间取平均
+    let c = t.sample(0.5, 0.5);
+    assert_eq!((c.r, c.g, c.b), (128, 128, 128));
+    // 两个纹素中间
+    let c = t.sample(0.5, 0.75);
+    assert_eq!((c.r, c.g, c.b), (128, 128, 0));
+
+    // ClampToEdge 下超出边缘只取边缘纹素，不会混入另一侧的颜色
+    t.set_wrap_mode(WrapMode::ClampToEdge);
+    assert_eq!(t.sample(0.0, 1.0), Color::RED);
+    assert_eq!(t.sample(-3.0, 1.0), Color::RED);
+}
+
+fn draw_textured(
+    mode: RasterMode,
+    threads: usize,
+    projection: bool,
+    pos: Vec<Vec3>,
+    uv: Vec<Vec2>,
+    texture: Texture,
+) -> Rasterizer {
+    let mut r = Rasterizer::new(SIZE, SIZE);
+    r.set_raster_mode(mode);
+    r.set_threads(threads);
+    if projection {
+        r.set_projection(nalgebra_glm::perspective_rh_no(
+            1.0,
+            90f32.to_radians(),
+            0.1,
+            50.0,
+        ));
+    }
+    let cols = vec![Color::BLACK; pos.len()];
+    let pos_id = r.load_positions(pos);
+    let ind_id = r.load_indices(vec![vec3(0.0, 1.0, 2.0), vec3(0.0, 2.0, 3.0)]);
+    let col_id = r.load_colors(cols);
+    let uv_id = r.load_tex_coords(uv);
+    let texture_id = r.load_texture(texture);
+    r.bind_tex_coords(Some(uv_id));
+    r.bind_texture(Some(texture_id));
+    r.clear(Buffers::all());
+    r.draw(pos_id, ind_id, col_id, Primitive::Triangle);
+    r
+}
+
+fn full_uv() -> Vec<Vec2> {
+    vec![
+        vec2(0.0, 0.0),
+        vec2(1.0, 0.0),
+        vec2(1.0, 1.0),
+        vec2(0.0, 1.0),
+    ]
+}
+
+#[test]
+fn textured_quad_samples_texture() {
+    let pos = vec![
+        vec3(-1.0, -1.0, 0.0),
+        vec3(1.0, -1.0, 0.0),
+        vec3(1.0, 1.0, 0.0),
+        vec3(-1.0, 1.0, 0.0),
+    ];
+    for mode in [RasterMode::EdgeFunction, RasterMode::Scanline] {
+        for threads in [1, 4] {
+            let mut texture = quad_texture();
+            texture.set_filter(Filter::Nearest);
+            let r = draw_textured(mode, threads, false, pos.clone(), full_uv(), texture);
+            let fb = r.framebuffer();
+            let at = |x: u32, y: u32| fb[(y * SIZE + x) as usize];
+            // 帧缓冲第一行是屏幕最上方，对应 v = 1
+            assert_eq!(at(0, 0), Color::RED);
+            assert_eq!(at(SIZE - 1, 0), Color::GREEN);
+            assert_eq!(at(0, SIZE - 1), Color::BLUE);
+            assert_eq!(at(SIZE - 1, SIZE - 1), Color::WHITE);
+            assert!(fb.iter().all(|&c| c != Color::BLACK));
+        }
+    }
+}
+
+#[test]
+fn texture_coordinates_are_perspective_correct() {
+    // 右侧比左侧远三倍，两侧都正好铺满屏幕。
+    // 纹理左红右绿，分界线 u = 0.5 在世界空间的中点 (1, 0, -2)，投影到 NDC 的 x = 0.5
+    let pos = vec![
+        vec3(-1.0, -1.0, -1.0),
+        vec3(3.0, -3.0, -3.0),
+        vec3(3.0, 3.0, -3.0),
+        vec3(-1.0, 1.0, -1.0),
+    ];
+    let mut texture = Texture::new(2, 1, vec![Color::RED, Color::GREEN]);
+    texture.set_filter(Filter::Nearest);
+    texture.set_wrap_mode(WrapMode::ClampToEdge);
+    let r = draw_textured(RasterMode::EdgeFunction, 1, true, pos, full_uv(), texture);
+
+    let boundary = SIZE * 3 / 4;
+    for row in r.framebuffer().chunks(SIZE as usize) {
+        for (x, &c) in row.iter().enumerate() {
+            let x = x as u32;
+            if x + 1 < boundary {
+                assert_eq!(c, Color::RED, "x = {}", x);
+            } else if x > boundary {
+                assert_eq!(c, Color::GREEN, "x = {}", x);
+            }
+        }
+    }
+}
+
+#[test]
+fn unbound_texture_falls_back_to_vertex_colors() {
+    let mut r = Rasterizer::new(SIZE, SIZE);
+    r.set_raster_mode(RasterMode::EdgeFunction);
+    let pos_id = r.load_positions(vec![
+        vec3(-1.0, -1.0, 0.0),
+        vec3(1.0, -1.0, 0.0),
+        vec3(1.0, 1.0, 0.0),
+    ]);
+    let ind_id = r.load_indices(vec![vec3(0.0, 1.0, 2.0)]);
+    let col_id = r.load_colors(vec![Color::GREEN; 3]);
+    let texture_id = r.load_texture(quad_texture());
+    r.bind_texture(Some(texture_id));
+    r.bind_texture(None);
+    r.clear(Buffers::all());
+    r.draw(pos_id, ind_id, col_id, Primitive::Triangle);
+    // 顶点颜色插值会有舍入误差
+    assert!(r.framebuffer().iter().any(|c| c.g > 250));
+    assert!(r.framebuffer().iter().all(|c| c.r == 0 && c.b == 0));
+}
@@ -0,0 +1,152 @@
+use games101_core::color::Color;
+use games101_core::lighting::{Light, Material};
+use games101_core::rasterizer::{Buffers, Exercises, Primitive, RasterMode, Rasterizer};
+use games101_core::texture::{Filter, Texture};
+use games101_core::triangle::Triangle;
+use nalgebra_glm::{vec2, vec3, Vec2, Vec3, Vec4};
+use std::cell::Cell;
+
+// 宽和高都不是 tile 大小的整数倍
+const WIDTH: u32 = 150;
+const HEIGHT: u32 = 130;
+const MODES: [RasterMode; 3] = [
+    RasterMode::BoundingBox,
+    RasterMode::EdgeFunction,
+    RasterMode::Scanline,
+];
+
+thread_local! {
+    static CALLS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn inside(x: u32, y: u32, v: [Vec4; 3]) -> bool {
+    let (alpha, beta, gamma) = Rasterizer::compute_barycentric2d(x, y, v);
+    alpha >= 0.0 && beta >= 0.0 && gamma >= 0.0
+}
+
+/// 作业 2 的 `rasterize_triangle`，记录在当前线程上被调用的次数
+fn rasterize(r: &mut Rasterizer, t: &Triangle) {
+    CALLS.with(|c| c.set(c.get() + 1));
+    let v = t.to_vector4();
+    let (xs, ys) = (v.map(|p| p.x), v.map(|p| p.y));
+    let min = |c: [f32; 3]| c[0].min(c[1]).min(c[2]).floor().max(0.0) as u32;
+    let max = |c: [f32; 3], size: u32| (c[0].max(c[1]).max(c[2]).ceil() as u32).min(size - 1);
+    let (min_x, min_y) = (min(xs), min(ys));
+    let (max_x, max_y) = (max(xs, r.width()), max(ys, r.height()));
+    for x in min_x..=max_x {
+        for y in min_y..=max_y {
+            if !inside(x, y, v) {
+                continue;
+            }
+            let (alpha, beta, gamma) = Rasterizer::compute_barycentric2d(x, y, v);
+            let w = 1.0 / (alpha / v[0].w + beta / v[1].w + gamma / v[2].w);
+            let z =
+                (alpha * v[0].z / v[0].w + beta * v[1].z / v[1].w + gamma * v[2].z / v[2].w) * w;
+            let color = r.shader().shade(t, alpha, beta, gamma);
+            r.set_pixel(x, y, color, z);
+        }
+    }
+}
+
+/// 伪随机的互相重叠的三角形，模型、视图、投影矩阵都是单位矩阵，顶点坐标就是 NDC 坐标
+fn scene() -> (Vec<Vec3>, Vec<Vec3>, Vec<Color>, Vec<Vec2>) {
+    let mut seed = 12345u32;
+    let mut random = move || {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        (seed >> 8) as f32 / (1 << 24) as f32
+    };
+    let (mut pos, mut ind, mut col, mut uv) = (vec![], vec![], vec![], vec![]);
+    for i in 0..40 {
+        let center = vec3(random() * 2.2 - 1.1, random() * 2.2 - 1.1, random() - 0.5);
+        for _ in 0..3 {
+            let offset = vec3(random() - 0.5, random() - 0.5, 0.2 * random() - 0.1) * 0.9;
+            pos.push(center + offset);
+            col.push(Color::new_rgb(
+                (random() * 255.0) as u8,
+                (random() * 255.0) as u8,
+                (random() * 255.0) as u8,
+            ));
+            uv.push(vec2(random(), random()));
+        }
+        let base = 3.0 * i as f32;
+        ind.push(vec3(base, base + 1.0, base + 2.0));
+    }
+    (pos, ind, col, uv)
+}
+
+fn render(mode: RasterMode, threads: usize, textured: bool) -> Vec<Color> {
+    let mut r = Rasterizer::new(WIDTH, HEIGHT);
+    r.set_raster_mode(mode);
+    r.set_threads(threads);
+    r.set_exercises(Exercises {
+        inside_triangle: inside,
+        rasterize_triangle: rasterize,
+    });
+    let (pos, ind, col, uv) = scene();
+    let normals = vec![vec3(0.0, 0.0, 1.0); pos.len()];
+    let (pos, ind, col) = (
+        r.load_positions(pos),
+        r.load_indices(ind),
+        r.load_colors(col),
+    );
+    if textured {
+        let data = (0..64 * 64)
+            .map(|i| Color::new_rgb((i % 64 * 4) as u8, (i / 64 * 4) as u8, 128))
+            .collect();
+        let mut texture = Texture::new(64, 64, data);
+        // 三线性过滤按 2x2 像素块光栅化
+        texture.set_filter(Filter::Trilinear);
+        texture.generate_mipmaps();
+        let texture = r.load_texture(texture);
+        r.bind_texture(Some(texture));
+        let uv = r.load_tex_coords(uv);
+        r.bind_tex_coords(Some(uv));
+        let normals = r.load_normals(normals);
+        r.bind_normals(Some(normals));
+        r.set_material(Some(Material::default()));
+        r.set_lights(vec![Light::point(
+            vec3(1.0, 1.0, 3.0),
+            vec3(20.0, 20.0, 20.0),
+        )]);
+    }
+    r.clear(Buffers::all());
+    r.draw(pos, ind, col, Primitive::Triangle);
+    r.framebuffer().to_vec()
+}
+
+#[test]
+fn every_mode_matches_serial() {
+    for mode in MODES {
+        let serial = render(mode, 1, false);
+        assert!(serial.iter().filter(|&&c| c != Color::BLACK).count() > 1000);
+        for threads in [2, 3, 8] {
+            assert!(
+                render(mode, threads, false) == serial,
+                "{:?} with {} threads",
+                mode,
+                threads
+            );
+        }
+    }
+}
+
+#[test]
+fn textured_and_lit_match_serial() {
+    for mode in MODES {
+        let serial = render(mode, 1, true);
+        assert!(render(mode, 4, true) == serial, "{:?} with 4 threads", mode);
+    }
+}
+
+#[test]
+fn bounding_box_calls_exercises_on_every_triangle() {
+    for threads in [1, 4] {
+        CALLS.with(|c| c.set(0));
+        render(RasterMode::BoundingBox, threads, false);
+        // 在调用 draw 的线程上串行调用，每个三角形一次
+        assert_eq!(CALLS.with(Cell::get), 40, "{} threads", threads);
+    }
+    CALLS.with(|c| c.set(0));
+    render(RasterMode::EdgeFunction, 4, false);
+    assert_eq!(CALLS.with(Cell::get), 0);
+}
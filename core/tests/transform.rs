@@ -0,0 +1,141 @@
+use games101_core::transform::*;
+use nalgebra_glm::{vec3, Mat4, Vec3};
+use std::f32::consts::FRAC_PI_2;
+
+fn assert_close(a: &Mat4, b: &Mat4) {
+    assert!((a - b).abs().max() < 1e-5, "{} {}", a, b);
+}
+
+fn apply(m: &Mat4, p: Vec3) -> Vec3 {
+    let h = m * p.push(1.0);
+    h.xyz() / h.w
+}
+
+#[test]
+fn basic_transforms_match_glm() {
+    let t = vec3(1.0, -2.0, 3.0);
+    assert_close(&translation(t), &nalgebra_glm::translation(&t));
+    let s = vec3(2.0, 0.5, -1.0);
+    assert_close(&scaling(s), &nalgebra_glm::scaling(&s));
+    for angle in [0.3f32, -1.2, 2.5] {
+        assert_close(
+            &rotation_x(angle),
+            &nalgebra_glm::rotation(angle, &Vec3::x()),
+        );
+        assert_close(
+            &rotation_y(angle),
+            &nalgebra_glm::rotation(angle, &Vec3::y()),
+        );
+        assert_close(
+            &rotation_z(angle),
+            &nalgebra_glm::rotation(angle, &Vec3::z()),
+        );
+    }
+    // 逆时针旋转 90°
+    assert!((apply(&rotation_z(FRAC_PI_2), Vec3::x()) - Vec3::y()).norm() < 1e-6);
+    assert_close(
+        &perspective(45f32.to_radians(), 1.5, 0.1, 50.0),
+        &nalgebra_glm::perspective_rh_no(1.5, 45f32.to_radians(), 0.1, 50.0),
+    );
+    assert_close(
+        &infinite_perspective(45f32.to_radians(), 1.5, 0.1),
+        &nalgebra_glm::infinite_perspective_rh_no(1.5, 45f32.to_radians(), 0.1),
+    );
+    assert_close(
+        &orthographic(-1.0, 3.0, -2.0, 0.5, 0.1, 10.0),
+        &nalgebra_glm::ortho_rh_no(-1.0, 3.0, -2.0, 0.5, 0.1, 10.0),
+    );
+}
+
+#[test]
+fn reversed_depth_swaps_near_and_far() {
+    for projection in [
+        perspective(1.0, 1.0, 0.5, 20.0),
+        orthographic(-1.0, 1.0, -1.0, 1.0, 0.5, 20.0),
+    ] {
+        let reversed = reverse_depth(&projection);
+        for (z, expected) in [(-0.5, 1.0), (-20.0, 0.0)] {
+            let p = vec3(0.3, -0.2, z);
+            assert!((apply(&reversed, p).z - expected).abs() < 1e-5);
+            // x、y 不变
+            let (a, b) = (apply(&reversed, p), apply(&projection, p));
+            assert!((a.xy() - b.xy()).norm() < 1e-6);
+        }
+    }
+}
+
+#[test]
+fn axis_angle_rotation() {
+    // 轴不需要归一化
+    let axis = vec3(1.0, 2.0, -2.0);
+    let m = rotation(0.7, axis);
+    assert_close(&m, &nalgebra_glm::rotation(0.7, &axis));
+    assert_close(&rotation(0.7, Vec3::z() * 3.0), &rotation_z(0.7));
+    // 轴上的点不动，转过的角度正确
+    assert!((apply(&m, axis) - axis).norm() < 1e-5);
+    let p = vec3(2.0, -1.0, 0.0);
+    assert!(p.dot(&axis).abs() < 1e-6);
+    let q = apply(&m, p);
+    assert!((p.angle(&q) - 0.7).abs() < 1e-5);
+    assert!((q.norm() - p.norm()).abs() < 1e-5);
+}
+
+#[test]
+fn euler_and_quaternion_agree() {
+    let angles = vec3(0.4, -0.9, 1.3);
+    let m = euler_xyz(angles);
+    assert_close(&m, &(rotation_z(1.3) * rotation_y(-0.9) * rotation_x(0.4)));
+    assert_close(&rotation_from_quat(&quat_from_euler_xyz(angles)), &m);
+
+    let axis = vec3(-1.0, 0.5, 2.0);
+    let q = quat_from_axis_angle(1.1, axis);
+    assert!((q.norm() - 1.0).abs() < 1e-6);
+    assert_close(&rotation_from_quat(&q), &rotation(1.1, axis));
+    // q 与 -q 表示同一个旋转
+    assert_close(&rotation_from_quat(&-q), &rotation(1.1, axis));
+}
+
+#[test]
+fn shear_moves_along_axes() {
+    let m = shear(0.5, 0.0, 0.0, 0.0, 0.0, -1.0);
+    assert_eq!(apply(&m, vec3(0.0, 2.0, 0.0)), vec3(1.0, 2.0, -2.0));
+    assert_eq!(apply(&m, vec3(3.0, 0.0, 4.0)), vec3(3.0, 0.0, 4.0));
+    // 切变不改变体积
+    assert!((m.determinant() - 1.0).abs() < 1e-6);
+    assert!(Transform::from_matrix(&m).is_none());
+}
+
+#[test]
+fn trs_round_trip_and_inverse() {
+    let t = Transform::new(
+        vec3(1.0, 2.0, -3.0),
+        quat_from_axis_angle(0.8, vec3(1.0, 1.0, 0.0)),
+        vec3(2.0, 0.5, 3.0),
+    );
+    let m = t.matrix();
+    assert_close(
+        &m,
+        &(translation(t.translation) * rotation(0.8, vec3(1.0, 1.0, 0.0)) * scaling(t.scale)),
+    );
+    assert_close(&(m * t.inverse_matrix()), &Mat4::identity());
+    assert_close(&t.inverse_matrix(), &m.try_inverse().unwrap());
+
+    let back = Transform::from_matrix(&m).unwrap();
+    assert!((back.translation - t.translation).norm() < 1e-5);
+    assert!((back.scale - t.scale).norm() < 1e-5);
+    assert_close(&back.matrix(), &m);
+
+    // 镜像
+    let mirror = Transform {
+        scale: vec3(1.0, -2.0, 1.0),
+        ..t
+    };
+    assert_close(
+        &Transform::from_matrix(&mirror.matrix()).unwrap().matrix(),
+        &mirror.matrix(),
+    );
+    assert_eq!(Transform::default().matrix(), Mat4::identity());
+    // 投影和退化的矩阵不能分解
+    assert!(Transform::from_matrix(&perspective(1.0, 1.0, 0.1, 10.0)).is_none());
+    assert!(Transform::from_matrix(&scaling(vec3(1.0, 0.0, 1.0))).is_none());
+}
@@ -0,0 +1,161 @@
+use games101_core::color::Color;
+use games101_core::lighting::{Light, Material, ShadingMode};
+use games101_core::rasterizer::{Buffers, Primitive, RasterMode, Rasterizer};
+use games101_core::transform;
+use nalgebra_glm::{vec3, Mat4, Vec3};
+
+const SIZE: u32 = 160;
+
+/// 经纬度网格的球面，相邻的三角形共享顶点
+struct Sphere {
+    positions: Vec<Vec3>,
+    colors: Vec<Color>,
+    indices: Vec<Vec3>,
+}
+
+fn sphere(segments: u32) -> Sphere {
+    let (mut positions, mut colors, mut indices) = (vec![], vec![], vec![]);
+    for i in 0..=segments {
+        let theta = std::f32::consts::PI * i as f32 / segments as f32;
+        for j in 0..=segments {
+            let phi = std::f32::consts::TAU * j as f32 / segments as f32;
+            positions.push(vec3(
+                theta.sin() * phi.cos(),
+                theta.cos(),
+                theta.sin() * phi.sin(),
+            ));
+            colors.push(Color::new_rgb(
+                (i * 255 / segments) as u8,
+                (j * 255 / segments) as u8,
+                160,
+            ));
+        }
+    }
+    let row = segments + 1;
+    for i in 0..segments {
+        for j in 0..segments {
+            let (a, b) = (i * row + j, (i + 1) * row + j);
+            indices.push(vec3(a as f32, b as f32, (a + 1) as f32));
+            indices.push(vec3((a + 1) as f32, b as f32, (b + 1) as f32));
+        }
+    }
+    Sphere {
+        positions,
+        colors,
+        indices,
+    }
+}
+
+impl Sphere {
+    /// 每个三角形使用自己的三个顶点，顶点缓存永远不会命中
+    fn unrolled(&self) -> Sphere {
+        let corners = || self.indices.iter().flat_map(|i| [i.x, i.y, i.z]);
+        Sphere {
+            positions: corners().map(|k| self.positions[k as usize]).collect(),
+            colors: corners().map(|k| self.colors[k as usize]).collect(),
+            indices: (0..self.indices.len())
+                .map(|i| {
+                    let base = 3.0 * i as f32;
+                    vec3(base, base + 1.0, base + 2.0)
+                })
+                .collect(),
+        }
+    }
+}
+
+fn model(degrees: f32) -> Mat4 {
+    transform::rotation_y(degrees.to_radians()) * transform::rotation_x(20f32.to_radians())
+}
+
+fn rasterizer(mode: RasterMode, shading: Option<ShadingMode>) -> Rasterizer {
+    let mut r = Rasterizer::new(SIZE, SIZE);
+    r.set_raster_mode(mode);
+    r.set_view(transform::translation(vec3(0.0, 0.0, -3.0)));
+    r.set_projection(transform::perspective(45f32.to_radians(), 1.0, 0.1, 50.0));
+    if let Some(shading) = shading {
+        r.set_material(Some(Material::default()));
+        r.set_lights(vec![Light::point(
+            vec3(10.0, 10.0, 10.0),
+            Vec3::repeat(300.0),
+        )]);
+        r.set_shading_mode(shading);
+    }
+    r
+}
+
+/// 在 `r` 中用 `mesh` 渲染一帧，单位球的法线就是顶点位置
+fn render(r: &mut Rasterizer, mesh: &Sphere, degrees: f32) -> Vec<Color> {
+    let normals = r.load_normals(mesh.positions.clone());
+    r.bind_normals(Some(normals));
+    let pos = r.load_positions(mesh.positions.clone());
+    let ind = r.load_indices(mesh.indices.clone());
+    let col = r.load_colors(mesh.colors.clone());
+    r.set_model(model(degrees));
+    r.clear(Buffers::all());
+    r.draw(pos, ind, col, Primitive::Triangle);
+    r.framebuffer().to_vec()
+}
+
+#[test]
+fn shared_vertices_render_like_unrolled_mesh() {
+    let indexed = sphere(24);
+    let unrolled = indexed.unrolled();
+    assert_eq!(unrolled.positions.len(), 3 * indexed.indices.len());
+    let shadings = [None, Some(ShadingMode::Gouraud), Some(ShadingMode::Phong)];
+    for mode in [RasterMode::EdgeFunction, RasterMode::Scanline] {
+        for shading in shadings {
+            let cached = render(&mut rasterizer(mode, shading), &indexed, 30.0);
+            let uncached = render(&mut rasterizer(mode, shading), &unrolled, 30.0);
+            assert!(
+                cached.iter().filter(|&&c| c != Color::BLACK).count() > 5000,
+                "{:?} {:?}",
+                mode,
+                shading
+            );
+            assert!(cached == uncached, "{:?} {:?}", mode, shading);
+        }
+    }
+}
+
+#[test]
+fn cache_is_rebuilt_on_every_draw() {
+    let shading = Some(ShadingMode::Gouraud);
+    let large = sphere(24);
+    let small = sphere(8);
+    // 同一个光栅化器先后绘制不同的模型矩阵和更小的网格，结果与新建的光栅化器相同
+    let mut r = rasterizer(RasterMode::EdgeFunction, shading);
+    render(&mut r, &large, 0.0);
+    let rotated = render(&mut r, &large, 75.0);
+    let fresh = render(
+        &mut rasterizer(RasterMode::EdgeFunction, shading),
+        &large,
+        75.0,
+    );
+    assert!(rotated == fresh);
+    let smaller = render(&mut r, &small, 75.0);
+    let fresh = render(
+        &mut rasterizer(RasterMode::EdgeFunction, shading),
+        &small,
+        75.0,
+    );
+    assert!(smaller == fresh);
+    assert_eq!(r.stats().submitted, small.indices.len());
+}
+
+#[test]
+fn vertex_stage_runs_once_per_unique_index() {
+    let mut indexed = sphere(16);
+    // 没有被索引引用的顶点不经过顶点阶段
+    indexed.positions.push(Vec3::x());
+    indexed.colors.push(Color::WHITE);
+    let unrolled = indexed.unrolled();
+    for shading in [None, Some(ShadingMode::Gouraud)] {
+        let mut r = rasterizer(RasterMode::EdgeFunction, shading);
+        render(&mut r, &indexed, 30.0);
+        let stats = r.stats();
+        assert_eq!(stats.submitted, indexed.indices.len());
+        assert_eq!(stats.vertices, 17 * 17, "{:?}", shading);
+        render(&mut r, &unrolled, 30.0);
+        assert_eq!(r.stats().vertices, 3 * indexed.indices.len());
+    }
+}
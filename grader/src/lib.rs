@@ -0,0 +1,3 @@
+//! 作业评分：在固定的输入上调用作业中的函数，与参考实现的结果比较并打分。
+pub mod report;
+pub mod tasks;
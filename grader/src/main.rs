@@ -0,0 +1,52 @@
+use games101_core::cli::ArgsError;
+use grader::tasks::{grade, Options, Submission};
+use std::env;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: grader [--json report.json|-] [--images dir] [--solution]";
+
+fn main() -> ExitCode {
+    let (submission, options, json) = match parse(env::args().skip(1)) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("grader: {}\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let report = grade(&submission, &options);
+    match json.as_deref() {
+        Some("-") => println!("{}", report.to_json()),
+        Some(path) => {
+            print!("{}", report);
+            if let Err(e) = std::fs::write(path, report.to_json() + "\n") {
+                eprintln!("failed to write {}: {}", path, e);
+                return ExitCode::FAILURE;
+            }
+        }
+        None => print!("{}", report),
+    }
+    ExitCode::SUCCESS
+}
+
+/// 解析不含程序名的参数，返回被评分的函数、评分选项和 JSON 报告的路径
+fn parse(
+    args: impl IntoIterator<Item = String>,
+) -> Result<(Submission, Options, Option<String>), ArgsError> {
+    let mut options = Options::default();
+    let mut json = None;
+    let mut submission = Submission::student();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |option: &'static str| args.next().ok_or(ArgsError::MissingValue(option));
+        match arg.as_str() {
+            "--json" => json = Some(value("--json")?),
+            "--images" => options.image_dir = Some(PathBuf::from(value("--images")?)),
+            // 给参考实现评分，检查评分程序本身
+            "--solution" => submission = Submission::reference(),
+            _ => return Err(ArgsError::Unknown(arg)),
+        }
+    }
+    Ok((submission, options, json))
+}
@@ -0,0 +1,106 @@
+use std::fmt;
+use std::fmt::Write;
+
+/// 一个评分项的结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskResult {
+    /// 作业编号
+    pub assignment: u32,
+    pub name: String,
+    pub score: f32,
+    pub max_score: f32,
+    /// 通过的用例数
+    pub passed: usize,
+    pub cases: usize,
+    /// 没有通过的用例的说明
+    pub messages: Vec<String>,
+}
+
+/// 所有评分项的结果
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Report {
+    pub tasks: Vec<TaskResult>,
+}
+
+impl Report {
+    pub fn score(&self) -> f32 {
+        self.tasks.iter().map(|t| t.score).sum()
+    }
+
+    pub fn max_score(&self) -> f32 {
+        self.tasks.iter().map(|t| t.max_score).sum()
+    }
+
+    /// 单行的 JSON，方便其他程序读取
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        write!(
+            json,
+            "{{\"score\":{},\"max_score\":{},\"tasks\":[",
+            self.score(),
+            self.max_score()
+        )
+        .unwrap();
+        for (i, task) in self.tasks.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            write!(
+                json,
+                "{{\"assignment\":{},\"name\":{},\"score\":{},\"max_score\":{},\"passed\":{},\"cases\":{},\"messages\":[",
+                task.assignment,
+                json_string(&task.name),
+                task.score,
+                task.max_score,
+                task.passed,
+                task.cases
+            )
+            .unwrap();
+            let messages: Vec<String> = task.messages.iter().map(|m| json_string(m)).collect();
+            json.push_str(&messages.join(","));
+            json.push_str("]}");
+        }
+        json.push_str("]}");
+        json
+    }
+}
+
+/// 按作业分组的文本报告
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut assignment = None;
+        for task in &self.tasks {
+            if assignment != Some(task.assignment) {
+                assignment = Some(task.assignment);
+                writeln!(f, "Assignment {}", task.assignment)?;
+            }
+            writeln!(
+                f,
+                "  {:<24}{:>5.1} / {:<5.1} {}/{} cases passed",
+                task.name, task.score, task.max_score, task.passed, task.cases
+            )?;
+            for message in &task.messages {
+                writeln!(f, "    - {}", message)?;
+            }
+        }
+        writeln!(f, "Total {:.1} / {:.1}", self.score(), self.max_score())
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
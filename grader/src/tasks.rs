@@ -0,0 +1,427 @@
+use crate::report::{Report, TaskResult};
+use games101_core::color::Color;
+use games101_core::rasterizer::{Buffers, Exercises, Primitive, RasterMode, Rasterizer};
+use games101_core::transform;
+use nalgebra_glm::{vec3, vec4, Mat4, Vec3, Vec4};
+use std::any::Any;
+use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::Once;
+
+/// 矩阵元素允许的相对误差
+const MATRIX_TOLERANCE: f32 = 1e-4;
+/// 颜色分量允许的误差
+const CHANNEL_TOLERANCE: u8 = 2;
+/// 不一致的像素占被覆盖像素的比例上限，允许三角形边上的个别像素不同
+const PIXEL_TOLERANCE: f32 = 0.02;
+/// 渲染用例的图像大小
+const SIZE: u32 = 128;
+
+/// 被评分的一组作业函数
+#[derive(Debug, Clone, Copy)]
+pub struct Submission {
+    pub get_model_matrix: fn(f32) -> Mat4,
+    pub get_projection_matrix: fn(f32, f32, f32, f32) -> Mat4,
+    pub exercises: Exercises,
+}
+
+impl Submission {
+    /// assignment1 和 assignment2 中自己写的函数
+    pub fn student() -> Self {
+        Self {
+            get_model_matrix: assignment1::transform::get_model_matrix,
+            get_projection_matrix: assignment1::transform::get_projection_matrix,
+            exercises: assignment2::rasterizer::EXERCISES,
+        }
+    }
+
+    /// 参考实现，得分应该是满分
+    pub fn reference() -> Self {
+        Self {
+            get_model_matrix: assignment1::solution::get_model_matrix,
+            get_projection_matrix: assignment1::solution::get_projection_matrix,
+            exercises: assignment2::solution::EXERCISES,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    /// 渲染结果不一致时，把作业和参考实现的图像保存到这个文件夹
+    pub image_dir: Option<PathBuf>,
+}
+
+/// 一个评分项，每个用例的分数相同
+struct Task {
+    assignment: u32,
+    name: &'static str,
+    points: f32,
+    run: fn(&mut Cases),
+}
+
+const TASKS: [Task; 5] = [
+    Task {
+        assignment: 1,
+        name: "get_model_matrix",
+        points: 10.0,
+        run: model_matrix,
+    },
+    Task {
+        assignment: 1,
+        name: "get_projection_matrix",
+        points: 10.0,
+        run: projection_matrix,
+    },
+    Task {
+        assignment: 1,
+        name: "wireframe",
+        points: 10.0,
+        run: wireframe,
+    },
+    Task {
+        assignment: 2,
+        name: "inside_triangle",
+        points: 10.0,
+        run: inside_triangle,
+    },
+    Task {
+        assignment: 2,
+        name: "rasterize_triangle",
+        points: 20.0,
+        run: rasterize_triangle,
+    },
+];
+
+/// 运行所有评分项。作业中的函数 panic（例如还是 `todo!()`）时对应的用例不得分
+pub fn grade(submission: &Submission, options: &Options) -> Report {
+    // 不打印作业中的 panic 信息，改为记录在报告中
+    let _silent = SilentPanics::new();
+    let tasks = TASKS
+        .iter()
+        .map(|task| {
+            let mut cases = Cases {
+                task: task.name,
+                submission,
+                options,
+                passed: 0,
+                total: 0,
+                messages: Vec::new(),
+            };
+            (task.run)(&mut cases);
+            TaskResult {
+                assignment: task.assignment,
+                name: task.name.to_string(),
+                score: task.points * cases.passed as f32 / cases.total.max(1) as f32,
+                max_score: task.points,
+                passed: cases.passed,
+                cases: cases.total,
+                messages: cases.messages,
+            }
+        })
+        .collect();
+    Report { tasks }
+}
+
+thread_local! {
+    static SILENT: Cell<bool> = const { Cell::new(false) };
+}
+
+/// 存在期间当前线程上的 panic 不打印信息，离开作用域时（包括 panic 时）恢复。
+///
+/// panic hook 是整个进程共用的，只在第一次使用时安装一次，由线程局部的标志决定是否打印，
+/// 多个线程同时评分时不会互相影响
+struct SilentPanics {
+    previous: bool,
+}
+
+impl SilentPanics {
+    fn new() -> Self {
+        static INSTALL: Once = Once::new();
+        INSTALL.call_once(|| {
+            let default = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                if !SILENT.with(Cell::get) {
+                    default(info);
+                }
+            }));
+        });
+        Self {
+            previous: SILENT.with(|s| s.replace(true)),
+        }
+    }
+}
+
+impl Drop for SilentPanics {
+    fn drop(&mut self) {
+        SILENT.with(|s| s.set(self.previous));
+    }
+}
+
+struct Cases<'a> {
+    task: &'static str,
+    submission: &'a Submission,
+    options: &'a Options,
+    passed: usize,
+    total: usize,
+    messages: Vec<String>,
+}
+
+impl Cases<'_> {
+    fn check(&mut self, name: &str, case: impl FnOnce(&Submission) -> Result<(), String>) {
+        self.total += 1;
+        let submission = self.submission;
+        match panic::catch_unwind(AssertUnwindSafe(|| case(submission))) {
+            Ok(Ok(())) => self.passed += 1,
+            Ok(Err(e)) => self.messages.push(format!("{}: {}", name, e)),
+            Err(payload) => {
+                self.messages
+                    .push(format!("{}: panicked: {}", name, panic_message(&*payload)))
+            }
+        }
+    }
+
+    /// 比较作业和参考实现渲染的图像
+    fn check_frame(
+        &mut self,
+        name: &str,
+        render: impl FnOnce(&Submission) -> Rasterizer,
+        reference: Rasterizer,
+    ) {
+        let task = self.task;
+        let image_dir = self.options.image_dir.clone();
+        self.check(name, |submission| {
+            let actual = render(submission);
+            let result = compare_frames(actual.framebuffer(), reference.framebuffer());
+            if let (Err(e), Some(dir)) = (&result, image_dir) {
+                let path = |kind: &str| {
+                    let file = format!("{}-{}-{}.png", task, name.replace(' ', "-"), kind);
+                    dir.join(file).to_string_lossy().into_owned()
+                };
+                let saved = std::fs::create_dir_all(&dir)
+                    .map_err(|e| e.to_string())
+                    .and_then(|_| {
+                        actual
+                            .save_framebuffer_to_png(&path("actual"))
+                            .and_then(|_| reference.save_framebuffer_to_png(&path("expected")))
+                            .map_err(|e| e.to_string())
+                    });
+                if let Err(save_error) = saved {
+                    return Err(format!("{}, failed to save images: {}", e, save_error));
+                }
+            }
+            result
+        });
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+fn compare_matrix(actual: &Mat4, expected: &Mat4) -> Result<(), String> {
+    if actual.iter().any(|x| !x.is_finite()) {
+        return Err(format!("matrix contains NaN or infinity: {}", actual));
+    }
+    let diff = (actual - expected).abs().max();
+    if diff > MATRIX_TOLERANCE * expected.abs().max().max(1.0) {
+        return Err(format!("max difference {:.4}", diff));
+    }
+    Ok(())
+}
+
+/// 颜色分量误差超过 `CHANNEL_TOLERANCE` 的像素不能太多，只统计至少一张图中不是背景的像素
+fn compare_frames(actual: &[Color], expected: &[Color]) -> Result<(), String> {
+    let mut covered = 0;
+    let mut different = 0;
+    for (a, e) in actual.iter().zip(expected) {
+        if *a == Color::BLACK && *e == Color::BLACK {
+            continue;
+        }
+        covered += 1;
+        let channels = [(a.r, e.r), (a.g, e.g), (a.b, e.b)];
+        if channels
+            .iter()
+            .any(|(a, e)| a.abs_diff(*e) > CHANNEL_TOLERANCE)
+        {
+            different += 1;
+        }
+    }
+    if different as f32 > covered as f32 * PIXEL_TOLERANCE {
+        return Err(format!("{} of {} pixels differ", different, covered));
+    }
+    Ok(())
+}
+
+fn model_matrix(cases: &mut Cases) {
+    let reference = Submission::reference();
+    for angle in [0.0, 30.0, 45.0, 90.0, -60.0, 180.0, 400.0] {
+        cases.check(&format!("angle {}", angle), |s| {
+            compare_matrix(
+                &(s.get_model_matrix)(angle),
+                &(reference.get_model_matrix)(angle),
+            )
+        });
+    }
+}
+
+fn projection_matrix(cases: &mut Cases) {
+    let reference = Submission::reference();
+    for (fov, aspect, near, far) in [
+        (45.0, 1.0, 0.1, 50.0),
+        (60.0, 1.5, 0.5, 100.0),
+        (90.0, 0.75, 1.0, 10.0),
+        (30.0, 2.0, 0.1, 1000.0),
+    ] {
+        let name = format!("fov {} aspect {} near {} far {}", fov, aspect, near, far);
+        cases.check(&name, |s| {
+            compare_matrix(
+                &(s.get_projection_matrix)(fov, aspect, near, far),
+                &(reference.get_projection_matrix)(fov, aspect, near, far),
+            )
+        });
+    }
+}
+
+/// 作业 1 的三角形线框
+fn render_wireframe(s: &Submission, angle: f32) -> Rasterizer {
+    let mut r = Rasterizer::new(SIZE, SIZE);
+    let pos = r.load_positions(vec![
+        vec3(2.0, 0.0, -2.0),
+        vec3(0.0, 2.0, -2.0),
+        vec3(-2.0, 0.0, -2.0),
+    ]);
+    let ind = r.load_indices(vec![vec3(0.0, 1.0, 2.0)]);
+    let col = r.load_colors(vec![Color::RED, Color::GREEN, Color::BLUE]);
+    r.set_model((s.get_model_matrix)(angle));
+    r.set_view(transform::translation(vec3(0.0, 0.0, -5.0)));
+    r.set_projection((s.get_projection_matrix)(45.0, 1.0, 0.1, 50.0));
+    r.clear(Buffers::all());
+    r.draw(pos, ind, col, Primitive::Line);
+    r
+}
+
+fn wireframe(cases: &mut Cases) {
+    for angle in [0.0, 20.0, 135.0] {
+        let reference = render_wireframe(&Submission::reference(), angle);
+        cases.check_frame(
+            &format!("angle {}", angle),
+            |s| render_wireframe(s, angle),
+            reference,
+        );
+    }
+}
+
+fn inside_triangle(cases: &mut Cases) {
+    let reference = Submission::reference().exercises.inside_triangle;
+    let triangles = [
+        ("counter-clockwise", [(4.0, 3.0), (28.0, 8.0), (12.0, 29.0)]),
+        ("clockwise", [(30.0, 30.0), (20.0, 1.0), (2.0, 20.0)]),
+        ("axis-aligned", [(0.0, 0.0), (16.0, 0.0), (0.0, 16.0)]),
+        ("thin", [(0.5, 0.5), (31.5, 2.5), (31.5, 4.0)]),
+    ];
+    for (name, [a, b, c]) in triangles {
+        let v: [Vec4; 3] = [
+            vec4(a.0, a.1, 0.0, 1.0),
+            vec4(b.0, b.1, 0.0, 1.0),
+            vec4(c.0, c.1, 0.0, 1.0),
+        ];
+        cases.check(name, |s| {
+            let mut wrong = 0;
+            let mut tested = 0;
+            for x in 0..32 {
+                for y in 0..32 {
+                    // 中心正好在边上的像素算在里面或外面都可以
+                    let (alpha, beta, gamma) = Rasterizer::compute_barycentric2d(x, y, v);
+                    if alpha.abs().min(beta.abs()).min(gamma.abs()) < 1e-4 {
+                        continue;
+                    }
+                    tested += 1;
+                    if (s.exercises.inside_triangle)(x, y, v) != reference(x, y, v) {
+                        wrong += 1;
+                    }
+                }
+            }
+            if wrong > 0 {
+                return Err(format!("{} of {} pixels wrong", wrong, tested));
+            }
+            Ok(())
+        });
+    }
+}
+
+/// 用参考的变换矩阵渲染三角形，只有光栅化使用作业中的函数
+fn render_triangles(
+    s: &Submission,
+    positions: &[Vec3],
+    colors: &[Color],
+    angle: f32,
+) -> Rasterizer {
+    let mut r = Rasterizer::new(SIZE, SIZE);
+    r.set_exercises(s.exercises);
+    r.set_raster_mode(RasterMode::BoundingBox);
+    let pos = r.load_positions(positions.to_vec());
+    let indices = (0..positions.len() / 3)
+        .map(|i| vec3(3.0 * i as f32, 3.0 * i as f32 + 1.0, 3.0 * i as f32 + 2.0))
+        .collect();
+    let ind = r.load_indices(indices);
+    let col = r.load_colors(colors.to_vec());
+    r.set_model(transform::rotation_z(angle.to_radians()));
+    r.set_view(transform::translation(vec3(0.0, 0.0, -5.0)));
+    r.set_projection(transform::perspective(45f32.to_radians(), 1.0, 0.1, 50.0));
+    r.clear(Buffers::all());
+    r.draw(pos, ind, col, Primitive::Triangle);
+    r
+}
+
+fn rasterize_triangle(cases: &mut Cases) {
+    // 作业 2 的两个互相遮挡的三角形
+    let overlapping = [
+        vec3(2.0, 0.0, -2.0),
+        vec3(0.0, 2.0, -2.0),
+        vec3(-2.0, 0.0, -2.0),
+        vec3(3.5, -1.0, -5.0),
+        vec3(2.5, 1.5, -5.0),
+        vec3(-1.0, 0.5, -5.0),
+    ];
+    let overlapping_colors = [
+        Color::BLUE,
+        Color::GREEN,
+        Color::RED,
+        Color::new_rgb(185, 217, 238),
+        Color::new_rgb(185, 217, 238),
+        Color::new_rgb(185, 217, 238),
+    ];
+    // 超出屏幕的三角形
+    let clipped = [
+        vec3(-6.0, -1.0, -2.0),
+        vec3(3.0, -5.0, -2.0),
+        vec3(1.0, 6.0, -2.0),
+    ];
+    let clipped_colors = [Color::YELLOW, Color::CYAN, Color::MAGENTA];
+
+    let scenes: [(&str, &[Vec3], &[Color], f32); 3] = [
+        ("overlapping", &overlapping, &overlapping_colors, 0.0),
+        (
+            "overlapping rotated",
+            &overlapping,
+            &overlapping_colors,
+            40.0,
+        ),
+        ("off-screen", &clipped, &clipped_colors, 10.0),
+    ];
+    for (name, positions, colors, angle) in scenes {
+        let reference = render_triangles(&Submission::reference(), positions, colors, angle);
+        cases.check_frame(
+            name,
+            |s| render_triangles(s, positions, colors, angle),
+            reference,
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use games101_core::rasterizer::Rasterizer;
+use games101_core::transform;
+use grader::report::{Report, TaskResult};
+use grader::tasks::{grade, Options, Submission};
+use nalgebra_glm::{Mat4, Vec4};
+use std::process::Command;
+
+fn task<'a>(report: &'a Report, name: &str) -> &'a TaskResult {
+    report.tasks.iter().find(|t| t.name == name).unwrap()
+}
+
+#[test]
+fn reference_gets_full_score() {
+    let report = grade(&Submission::reference(), &Options::default());
+    assert_eq!(report.tasks.len(), 5);
+    for t in &report.tasks {
+        assert_eq!(t.passed, t.cases, "{:?}", t);
+        assert!(t.messages.is_empty());
+    }
+    assert_eq!(report.score(), report.max_score());
+    assert_eq!(report.max_score(), 60.0);
+}
+
+#[test]
+fn unimplemented_functions_score_zero() {
+    fn model(_: f32) -> Mat4 {
+        todo!()
+    }
+    fn rasterize(_: &mut Rasterizer, _: &games101_core::triangle::Triangle) {
+        todo!()
+    }
+    let mut submission = Submission::reference();
+    submission.get_model_matrix = model;
+    submission.exercises.rasterize_triangle = rasterize;
+    let report = grade(&submission, &Options::default());
+
+    let model = task(&report, "get_model_matrix");
+    assert_eq!((model.score, model.passed), (0.0, 0));
+    assert!(model.messages[0].contains("panicked: not yet implemented"));
+    // 线框用到了模型矩阵
+    assert_eq!(task(&report, "wireframe").score, 0.0);
+    assert_eq!(task(&report, "rasterize_triangle").score, 0.0);
+    assert_eq!(task(&report, "get_projection_matrix").score, 10.0);
+    assert_eq!(task(&report, "inside_triangle").score, 10.0);
+    assert_eq!(report.score(), 20.0);
+}
+
+#[test]
+fn concurrent_grading_gives_the_same_report() {
+    fn model(_: f32) -> Mat4 {
+        todo!()
+    }
+    let mut submission = Submission::reference();
+    submission.get_model_matrix = model;
+    let expected = grade(&submission, &Options::default());
+    // panic hook 是进程共用的，多个线程同时评分时各自的结果不受影响
+    let reports: Vec<Report> = std::thread::scope(|s| {
+        let handles: Vec<_> = (0..4)
+            .map(|_| s.spawn(|| grade(&submission, &Options::default())))
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    for report in reports {
+        assert_eq!(report, expected);
+    }
+    assert_eq!(expected.score(), 40.0);
+}
+
+#[test]
+fn usage_errors() {
+    for args in [&["--json"][..], &["--images"], &["--bogus"]] {
+        let output = Command::new(env!("CARGO_BIN_EXE_grader"))
+            .args(args)
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("usage: grader"), "{}", stderr);
+        let expected = match args[0] {
+            "--bogus" => "unknown argument '--bogus'".to_string(),
+            option => format!("missing value for {}", option),
+        };
+        assert!(stderr.contains(&expected), "{}", stderr);
+    }
+}
+
+#[test]
+fn partial_credit_per_case() {
+    // 忘了把角度转换成弧度
+    fn model(angle: f32) -> Mat4 {
+        transform::rotation_z(angle)
+    }
+    // 只能判断逆时针的三角形
+    fn inside(x: u32, y: u32, v: [Vec4; 3]) -> bool {
+        let p = nalgebra_glm::vec2(x as f32 + 0.5, y as f32 + 0.5);
+        (0..3).all(|i| {
+            let (a, b) = (v[i].xy(), v[(i + 1) % 3].xy());
+            let (e, d) = (b - a, p - a);
+            e.x * d.y - e.y * d.x >= 0.0
+        })
+    }
+    let mut submission = Submission::reference();
+    submission.get_model_matrix = model;
+    submission.exercises.inside_triangle = inside;
+    let report = grade(&submission, &Options::default());
+
+    // 只有 0° 的用例是对的
+    let model = task(&report, "get_model_matrix");
+    assert_eq!((model.passed, model.cases), (1, 7));
+    assert!((model.score - 10.0 / 7.0).abs() < 1e-5);
+    assert!(model.messages[0].starts_with("angle 30: max difference"));
+    assert_eq!(task(&report, "wireframe").passed, 1);
+
+    let inside = task(&report, "inside_triangle");
+    assert_eq!((inside.passed, inside.cases), (3, 4));
+    assert!(inside.messages[0].starts_with("clockwise: "));
+    assert_eq!(task(&report, "rasterize_triangle").score, 20.0);
+}
+
+#[test]
+fn report_formats() {
+    let report = Report {
+        tasks: vec![TaskResult {
+            assignment: 2,
+            name: "inside_triangle".to_string(),
+            score: 2.5,
+            max_score: 10.0,
+            passed: 1,
+            cases: 4,
+            messages: vec!["thin: \"3\" of\n10 pixels wrong".to_string()],
+        }],
+    };
+    assert_eq!(
+        report.to_json(),
+        r#"{"score":2.5,"max_score":10,"tasks":[{"assignment":2,"name":"inside_triangle","score":2.5,"max_score":10,"passed":1,"cases":4,"messages":["thin: \"3\" of\n10 pixels wrong"]}]}"#
+    );
+    let text = report.to_string();
+    assert!(text.starts_with("Assignment 2\n  inside_triangle"));
+    assert!(text.contains("2.5 / 10.0  1/4 cases passed"));
+    assert!(text.ends_with("Total 2.5 / 10.0\n"));
+}
+
+#[test]
+fn failed_frames_are_saved() {
+    fn model(angle: f32) -> Mat4 {
+        transform::rotation_z(-angle.to_radians())
+    }
+    let dir = std::env::temp_dir().join(format!("games101-grader-{}", std::process::id()));
+    let options = Options {
+        image_dir: Some(dir.clone()),
+    };
+    let mut submission = Submission::reference();
+    submission.get_model_matrix = model;
+    let report = grade(&submission, &options);
+    assert_eq!(task(&report, "wireframe").passed, 1);
+    for kind in ["actual", "expected"] {
+        assert!(dir
+            .join(format!("wireframe-angle-20-{}.png", kind))
+            .exists());
+    }
+    // 通过的用例不保存
+    assert!(!dir.join("wireframe-angle-0-actual.png").exists());
+    std::fs::remove_dir_all(dir).unwrap();
+}